@@ -0,0 +1,182 @@
+use std::ops::Range;
+
+use crate::error::PlotHelperError;
+
+
+/// define the scale of an axis
+/// NOTE : the data is transformed in the scale space before the compression and the drawing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AxisScale {
+    Linear,
+    /// logarithmic scale in base 10, the values <= 0 are not drawn
+    Log10,
+    /// logarithmic scale in base 2, the values <= 0 are not drawn
+    Log2,
+    /// symmetric logarithmic scale, linear around 0 (in the given threshold) and logarithmic after (see AxisScale::symlog)
+    Symlog(SymlogThreshold),
+}
+
+/// the threshold of a symmetric logarithmic scale, a finite value > 0
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SymlogThreshold(f64);
+
+impl SymlogThreshold {
+    /// NOTE : an error if the threshold is not a finite value > 0 (no value could be drawn)
+    pub fn new(threshold : f64) -> Result<Self, PlotHelperError> {
+        if !(threshold.is_finite() && threshold > 0.0) {
+            return Err(PlotHelperError::InvalidParameter(format!("the threshold of a symlog scale must be > 0, got {}", threshold)));
+        }
+        Ok(Self(threshold))
+    }
+
+    pub fn get(&self) -> f64 {
+        self.0
+    }
+}
+
+impl AxisScale {
+    /// create a symmetric logarithmic scale (an error if the threshold is not a finite value > 0)
+    pub fn symlog(threshold : f64) -> Result<Self, PlotHelperError> {
+        Ok(AxisScale::Symlog(SymlogThreshold::new(threshold)?))
+    }
+
+    /// transform a value of the data to the scale space (None if the value can't be drawn in this scale)
+    pub fn transform(&self, value : f64) -> Option<f64> {
+        let transformed = match self {
+            AxisScale::Linear => value,
+            AxisScale::Log10 if value > 0.0 => value.log10(),
+            AxisScale::Log2 if value > 0.0 => value.log2(),
+            AxisScale::Log10 | AxisScale::Log2 => return None,
+            AxisScale::Symlog(threshold) => value.signum() * (1.0 + value.abs() / threshold.get()).log10(),
+        };
+        if transformed.is_finite() { Some(transformed) } else { None }
+    }
+
+    /// get the value of the data from a value of the scale space
+    pub fn inverse(&self, value : f64) -> f64 {
+        match self {
+            AxisScale::Linear => value,
+            AxisScale::Log10 => 10.0_f64.powf(value),
+            AxisScale::Log2 => 2.0_f64.powf(value),
+            AxisScale::Symlog(threshold) => value.signum() * threshold.get() * (10.0_f64.powf(value.abs()) - 1.0),
+        }
+    }
+}
+
+/// define the configuration of an axis
+/// scale : the scale of the axis
+/// range : the fixed range of the axis (in the data space), the data outside the range is not drawn. Computed from the data if None
+/// inverted : if true, the axis go from the max to the min
+#[derive(Debug, Clone, PartialEq)]
+pub struct AxisConfig {
+    pub scale : AxisScale,
+    pub range : Option<Range<f64>>,
+    pub inverted : bool,
+}
+
+impl Default for AxisConfig {
+    fn default() -> Self {
+        Self::new(AxisScale::Linear)
+    }
+}
+
+impl AxisConfig {
+    pub fn new(scale : AxisScale) -> Self {
+        Self {
+            scale,
+            range : None,
+            inverted : false,
+        }
+    }
+
+    pub fn with_range(mut self, range : Range<f64>) -> Self {
+        self.range = Some(range);
+        self
+    }
+
+    pub fn with_inverted(mut self, inverted : bool) -> Self {
+        self.inverted = inverted;
+        self
+    }
+
+    /// transform a value of the data to the plot space (scale then inversion)
+    /// None if the value can't be drawn (out of the scale domain or of the fixed range)
+    pub fn to_plot_value(&self, value : f64) -> Option<f64> {
+        if let Some(range) = &self.range {
+            if value < range.start.min(range.end) || value > range.start.max(range.end) {
+                return None;
+            }
+        }
+        let transformed = self.scale.transform(value)?;
+        Some(if self.inverted { -transformed } else { transformed })
+    }
+
+    /// get the value of the data from a value of the plot space
+    pub fn from_plot_value(&self, value : f64) -> f64 {
+        let value = if self.inverted { -value } else { value };
+        // add 0 to avoid the negative zero of the inversion
+        self.scale.inverse(value) + 0.0
+    }
+
+    /// get the fixed range of the axis in the plot space (None if the range is not fixed or can't be drawn in the scale)
+    pub fn get_plot_range(&self) -> Option<Range<f64>> {
+        let range = self.range.as_ref()?;
+        let start = self.to_plot_value(range.start)?;
+        let end = self.to_plot_value(range.end)?;
+        Some(start.min(end)..start.max(end))
+    }
+}
+
+/// the configuration of the two axes of a subplot
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AxesConfig {
+    pub x : AxisConfig,
+    pub y : AxisConfig,
+}
+
+impl AxesConfig {
+    pub fn new(x : AxisConfig, y : AxisConfig) -> Self {
+        Self {
+            x,
+            y,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_axis_scale_round_trip() {
+        for scale in [AxisScale::Linear, AxisScale::Log10, AxisScale::Log2, AxisScale::symlog(1.0).unwrap()] {
+            for value in [0.5_f64, 1.0, 42.0, 1e6] {
+                let transformed = scale.transform(value).unwrap();
+                assert_relative_eq!(scale.inverse(transformed), value, max_relative = 1e-4);
+            }
+        }
+        assert_eq!(AxisScale::Log10.transform(100.0), Some(2.0));
+        assert_eq!(AxisScale::Log2.transform(8.0), Some(3.0));
+        assert_eq!(AxisScale::Log10.transform(0.0), None);
+        assert_eq!(AxisScale::Log2.transform(-1.0), None);
+        assert_eq!(AxisScale::symlog(1.0).unwrap().transform(-9.0), Some(-1.0));
+        assert_eq!(AxisScale::symlog(1.0).unwrap().transform(0.0), Some(0.0));
+        // the threshold is validated
+        for threshold in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            assert!(matches!(AxisScale::symlog(threshold), Err(PlotHelperError::InvalidParameter(_))));
+        }
+    }
+
+    #[test]
+    fn test_axis_config_plot_value() {
+        let axis = AxisConfig::new(AxisScale::Log10).with_range(1.0..1000.0).with_inverted(true);
+        assert_eq!(axis.to_plot_value(10.0), Some(-1.0));
+        assert_eq!(axis.to_plot_value(0.5), None);
+        assert_eq!(axis.to_plot_value(2000.0), None);
+        assert_relative_eq!(axis.from_plot_value(-2.0), 100.0, max_relative = 1e-5);
+        assert_eq!(axis.get_plot_range(), Some(-3.0..0.0));
+        assert_eq!(AxisConfig::default().get_plot_range(), None);
+    }
+}
@@ -0,0 +1,164 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Duration, NaiveTime, SecondsFormat, Timelike, Utc};
+
+use crate::error::PlotHelperError;
+
+const SECONDS_PER_DAY : f64 = 86400.0;
+
+/// a point in time (in UTC), the value of a datetime serie
+/// NOTE : a datetime key is numeric, its numeric value is the number of seconds since the unix epoch (see to_value) :
+/// the datetimes are filtered, aggregated and drawn as the other numeric series, only the labels are formatted as dates
+/// NOTE : the values are f64, so their precision is under the microsecond for the current dates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp(DateTime<Utc>);
+
+impl From<DateTime<Utc>> for Timestamp {
+    fn from(datetime : DateTime<Utc>) -> Self {
+        Self(datetime)
+    }
+}
+
+impl Timestamp {
+    /// get the timestamp from a number of seconds since the unix epoch (None if it is not finite or out of the supported dates)
+    pub fn from_epoch_seconds(seconds : f64) -> Option<Self> {
+        if !seconds.is_finite() {
+            return None;
+        }
+        let whole_seconds = seconds.floor();
+        let nanoseconds = (((seconds - whole_seconds) * 1e9).round() as u32).min(999_999_999);
+        DateTime::from_timestamp(whole_seconds as i64, nanoseconds).map(Self)
+    }
+
+    /// get the timestamp from a value of a datetime serie (see to_value)
+    pub fn from_value(value : f64) -> Option<Self> {
+        Self::from_epoch_seconds(value)
+    }
+
+    /// get the value of the timestamp in a datetime serie : the number of seconds since the unix epoch
+    pub fn to_value(&self) -> f64 {
+        self.get_epoch_seconds()
+    }
+
+    pub fn get_epoch_seconds(&self) -> f64 {
+        self.0.timestamp() as f64 + self.0.timestamp_subsec_nanos() as f64 / 1e9
+    }
+
+    pub fn get_datetime(&self) -> &DateTime<Utc> {
+        &self.0
+    }
+
+    /// format the timestamp with the strftime syntax of chrono ("%Y-%m-%d" for example)
+    pub fn format(&self, format : &str) -> String {
+        self.0.format(format).to_string()
+    }
+}
+
+impl FromStr for Timestamp {
+    type Err = PlotHelperError;
+
+    /// parse a RFC 3339 datetime ("2024-03-01T12:00:00Z", "2024-03-01T14:00:00+02:00"),
+    /// or a number of seconds since the unix epoch ("1709294400")
+    fn from_str(value : &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        if let Ok(seconds) = value.parse::<f64>() {
+            return Self::from_epoch_seconds(seconds)
+                .ok_or_else(|| PlotHelperError::Parse(format!("the epoch {} is not a supported date", value)));
+        }
+        DateTime::parse_from_rfc3339(value)
+            .map(|datetime| Self(datetime.with_timezone(&Utc)))
+            .map_err(|error| PlotHelperError::Parse(format!("{} is neither a RFC 3339 datetime nor an epoch ({})", value, error)))
+    }
+}
+
+impl Display for Timestamp {
+    /// the RFC 3339 format, in UTC ("2024-03-01T12:00:00Z")
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+    }
+}
+
+/// the duration of the buckets of a time aggregation
+/// NOTE : the buckets are aligned on the calendar (in UTC), the weeks start on monday
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimeBucket {
+    Hour,
+    Day,
+    Week,
+    Month,
+}
+
+impl TimeBucket {
+    /// get the start of the bucket containing the timestamp
+    pub fn floor(&self, timestamp : &Timestamp) -> Timestamp {
+        let date = timestamp.0.date_naive();
+        let start = match self {
+            TimeBucket::Hour => date.and_time(NaiveTime::MIN) + Duration::hours(timestamp.0.hour() as i64),
+            TimeBucket::Day => date.and_time(NaiveTime::MIN),
+            TimeBucket::Week => (date - Duration::days(date.weekday().num_days_from_monday() as i64)).and_time(NaiveTime::MIN),
+            TimeBucket::Month => date.with_day(1).unwrap_or(date).and_time(NaiveTime::MIN),
+        };
+        Timestamp(start.and_utc())
+    }
+
+    /// get the start of the bucket containing the value of a datetime serie (NaN if the value is not a date)
+    pub fn floor_value(&self, value : f64) -> f64 {
+        Timestamp::from_value(value).map(|timestamp| self.floor(&timestamp).to_value()).unwrap_or(f64::NAN)
+    }
+}
+
+/// format the value of a datetime serie for the label of an axis, the precision depends on the span of the axis (in seconds)
+pub(crate) fn format_datetime_value(value : f64, span : f64) -> String {
+    let format = if span.abs() <= 2.0 * SECONDS_PER_DAY { "%Y-%m-%d %H:%M" } else { "%Y-%m-%d" };
+    Timestamp::from_value(value).map(|timestamp| timestamp.format(format)).unwrap_or_default()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timestamp_parsing() {
+        let timestamp : Timestamp = "2024-03-01T14:00:00+02:00".parse().unwrap();
+        assert_eq!(timestamp.to_string(), "2024-03-01T12:00:00Z");
+        assert_eq!(timestamp.get_epoch_seconds(), 1709294400.0);
+        assert_eq!("1709294400".parse::<Timestamp>().unwrap(), timestamp);
+        assert_eq!(" 1709294400.5 ".parse::<Timestamp>().unwrap().to_string(), "2024-03-01T12:00:00.500Z");
+
+        assert!("2024-03-01".parse::<Timestamp>().is_err());
+        assert!("yesterday".parse::<Timestamp>().is_err());
+        assert!("1e30".parse::<Timestamp>().is_err());
+    }
+
+    #[test]
+    fn test_timestamp_value() {
+        let timestamp : Timestamp = "2024-03-01T00:00:00Z".parse().unwrap();
+        assert_eq!(Timestamp::from_value(timestamp.to_value()), Some(timestamp));
+        // the seconds are kept
+        let timestamp : Timestamp = "2024-03-01T13:00:07Z".parse().unwrap();
+        assert_eq!(Timestamp::from_value(timestamp.to_value()), Some(timestamp));
+        assert_eq!(Timestamp::from_value(f64::NAN), None);
+    }
+
+    #[test]
+    fn test_time_bucket_floor() {
+        // a wednesday
+        let timestamp : Timestamp = "2024-03-13T17:45:12Z".parse().unwrap();
+        let floor = |bucket : TimeBucket| bucket.floor(&timestamp).to_string();
+        assert_eq!(floor(TimeBucket::Hour), "2024-03-13T17:00:00Z");
+        assert_eq!(floor(TimeBucket::Day), "2024-03-13T00:00:00Z");
+        assert_eq!(floor(TimeBucket::Week), "2024-03-11T00:00:00Z");
+        assert_eq!(floor(TimeBucket::Month), "2024-03-01T00:00:00Z");
+        assert!(TimeBucket::Day.floor_value(f64::NAN).is_nan());
+    }
+
+    #[test]
+    fn test_format_datetime_value() {
+        let value = "2024-03-13T00:00:00Z".parse::<Timestamp>().unwrap().to_value();
+        assert_eq!(format_datetime_value(value, 3600.0), "2024-03-13 00:00");
+        assert_eq!(format_datetime_value(value, 30.0 * SECONDS_PER_DAY), "2024-03-13");
+        assert_eq!(format_datetime_value(f64::NAN, 3600.0), "");
+    }
+}
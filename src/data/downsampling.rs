@@ -0,0 +1,180 @@
+use std::ops::Range;
+
+use crate::utils::{compress_data_serie, sort_by_x, SERIE_DIVISION};
+
+use super::linspace::Linspace;
+
+/// the way the points of a serie are reduced before being drawn, to accelerate the plotting
+/// NOTE : the resolution is the number of divisions of each axis (Grid), the number of kept points (Lttb),
+/// or the number of x buckets (MinMax). The result is always sorted by x
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Downsampling {
+    /// average the points in each cell of a resolution x resolution grid (for the scatters, lose the extremes)
+    Grid(usize),
+    /// Largest-Triangle-Three-Buckets : keep the given number of points, chosen to preserve the shape of the line
+    Lttb(usize),
+    /// keep the points with the minimum and the maximum y in each x bucket (for the lines, preserve the peaks)
+    MinMax(usize),
+    /// keep all the points
+    None,
+}
+
+impl Default for Downsampling {
+    fn default() -> Self {
+        Downsampling::Grid(SERIE_DIVISION)
+    }
+}
+
+impl Downsampling {
+    /// the default downsampling of a line : the min and max of SERIE_DIVISION buckets
+    pub fn line() -> Self {
+        Downsampling::MinMax(SERIE_DIVISION)
+    }
+
+    /// downsample the serie (all the points must be in the ranges)
+    pub(crate) fn apply(&self, serie : Vec<(f64, f64)>, range_x : &Range<f64>, range_y : &Range<f64>) -> Vec<(f64, f64)> {
+        match self {
+            Downsampling::Grid(resolution) => compress_data_serie(serie, range_x, range_y, *resolution),
+            Downsampling::Lttb(nb_of_points) => lttb(serie, *nb_of_points),
+            Downsampling::MinMax(nb_of_buckets) => min_max(serie, range_x, *nb_of_buckets),
+            Downsampling::None => {
+                let mut serie = serie;
+                sort_by_x(&mut serie);
+                serie
+            },
+        }
+    }
+
+    /// downsample the points of the serie in the ranges (the other points are removed)
+    pub(crate) fn apply_in(&self, serie : Vec<(f64, f64)>, range_x : &Range<f64>, range_y : &Range<f64>) -> Vec<(f64, f64)> {
+        let serie = serie.into_iter()
+            .filter(|(x, y)| range_x.start <= *x && *x <= range_x.end && range_y.start <= *y && *y <= range_y.end)
+            .collect();
+        self.apply(serie, range_x, range_y)
+    }
+}
+
+/// keep nb_of_points points of the serie with the Largest-Triangle-Three-Buckets algorithm
+/// the first and the last points are kept, then in each bucket the point which forms the largest triangle
+/// with the previous kept point and the average of the next bucket
+/// NOTE : the serie is sorted by x, and returned as is if it has less than nb_of_points points (or nb_of_points < 3)
+pub fn lttb(mut serie : Vec<(f64, f64)>, nb_of_points : usize) -> Vec<(f64, f64)> {
+    sort_by_x(&mut serie);
+    if nb_of_points < 3 || serie.len() <= nb_of_points {
+        return serie;
+    }
+
+    // the buckets split the points between the first and the last ones
+    let bucket_size = (serie.len() - 2) as f64 / (nb_of_points - 2) as f64;
+    let bucket_bounds = |bucket : usize| {
+        let start = (bucket as f64 * bucket_size) as usize + 1;
+        let end = (((bucket + 1) as f64 * bucket_size) as usize + 1).min(serie.len() - 1);
+        start..end
+    };
+
+    let mut sampled = Vec::with_capacity(nb_of_points);
+    sampled.push(serie[0]);
+    let mut previous = serie[0];
+    for bucket in 0..nb_of_points - 2 {
+        // the average of the next bucket (the last point for the last bucket)
+        let next = if bucket + 1 < nb_of_points - 2 { bucket_bounds(bucket + 1) } else { serie.len() - 1..serie.len() };
+        let next_len = next.len().max(1) as f64;
+        let (average_x, average_y) = serie[next].iter()
+            .fold((0.0, 0.0), |(x, y), point| (x + point.0, y + point.1));
+        let (average_x, average_y) = (average_x / next_len, average_y / next_len);
+
+        let selected = serie[bucket_bounds(bucket)].iter().copied().max_by(|a, b| {
+            let area = |point : &(f64, f64)| {
+                ((previous.0 - average_x) * (point.1 - previous.1)
+                    - (previous.0 - point.0) * (average_y - previous.1)).abs()
+            };
+            area(a).total_cmp(&area(b))
+        });
+        if let Some(selected) = selected {
+            sampled.push(selected);
+            previous = selected;
+        }
+    }
+    sampled.push(serie[serie.len() - 1]);
+    sampled
+}
+
+/// keep the points with the minimum and the maximum y of each of the nb_of_buckets buckets of the same width of range_x
+/// NOTE : the points are returned sorted by x (the extremes of a bucket keep their order)
+pub fn min_max(mut serie : Vec<(f64, f64)>, range_x : &Range<f64>, nb_of_buckets : usize) -> Vec<(f64, f64)> {
+    sort_by_x(&mut serie);
+    let nb_of_buckets = nb_of_buckets.max(1);
+    if serie.len() <= 2 * nb_of_buckets {
+        return serie;
+    }
+    let edges = Linspace::new(range_x.start, range_x.end, nb_of_buckets + 1);
+
+    // (index of the min, index of the max) of each bucket
+    let mut extremes : Vec<Option<(usize, usize)>> = vec![None; nb_of_buckets];
+    for (index, (x, y)) in serie.iter().enumerate() {
+        let bucket = edges.index_of(x).unwrap_or(0).min(nb_of_buckets - 1);
+        extremes[bucket] = match extremes[bucket] {
+            None => Some((index, index)),
+            Some((min, max)) => Some((
+                if *y < serie[min].1 { index } else { min },
+                if *y > serie[max].1 { index } else { max },
+            )),
+        };
+    }
+
+    let mut kept = Vec::with_capacity(2 * nb_of_buckets);
+    for (min, max) in extremes.into_iter().flatten() {
+        kept.push(serie[min.min(max)]);
+        if min != max {
+            kept.push(serie[min.max(max)]);
+        }
+    }
+    kept
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_serie() -> Vec<(f64, f64)> {
+        // a flat line with a peak at x = 50, in reverse order
+        (0..100).rev().map(|x| (x as f64, if x == 50 { 100.0 } else { (x % 2) as f64 })).collect()
+    }
+
+    fn is_sorted_by_x(serie : &[(f64, f64)]) -> bool {
+        serie.windows(2).all(|w| w[0].0 <= w[1].0)
+    }
+
+    #[test]
+    fn test_lttb() {
+        let sampled = lttb(get_serie(), 10);
+        assert_eq!(sampled.len(), 10);
+        assert!(is_sorted_by_x(&sampled));
+        assert_eq!(sampled.first(), Some(&(0.0, 0.0)));
+        assert_eq!(sampled.last(), Some(&(99.0, 1.0)));
+        assert!(sampled.contains(&(50.0, 100.0)));
+
+        // not enough points
+        assert_eq!(lttb(vec![(2.0, 1.0), (1.0, 2.0)], 10), vec![(1.0, 2.0), (2.0, 1.0)]);
+    }
+
+    #[test]
+    fn test_min_max() {
+        let sampled = min_max(get_serie(), &(0.0..99.0), 5);
+        assert!(sampled.len() <= 10);
+        assert!(is_sorted_by_x(&sampled));
+        assert!(sampled.contains(&(50.0, 100.0)));
+        assert!(sampled.iter().any(|(_, y)| *y == 0.0));
+    }
+
+    #[test]
+    fn test_downsampling_apply() {
+        let range = 0.0..100.0;
+        for downsampling in [Downsampling::Grid(10), Downsampling::Lttb(10), Downsampling::MinMax(10), Downsampling::None] {
+            assert!(is_sorted_by_x(&downsampling.apply(get_serie(), &range, &range)));
+        }
+        assert_eq!(Downsampling::None.apply(get_serie(), &range, &range).len(), 100);
+        assert_eq!(Downsampling::default(), Downsampling::Grid(SERIE_DIVISION));
+    }
+}
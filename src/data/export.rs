@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::data::plot_data::LegendOrder;
+use crate::data::plottable::key::SerieKey;
+use crate::error::PlotHelperError;
+use crate::stats::stats_serie::{MetricName, StatsSerie};
+
+/// the format of an exported data file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DataFormat {
+    /// a csv file, the first line is the header
+    Csv,
+    /// a json array, with an object per row (the keys are the headers)
+    Json,
+}
+
+impl DataFormat {
+    /// get the extension of the files of this format
+    pub fn get_extension(&self) -> &'static str {
+        match self {
+            DataFormat::Csv => "csv",
+            DataFormat::Json => "json",
+        }
+    }
+}
+
+/// a value of an exported table
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExportValue {
+    Number(f64),
+    Text(String),
+}
+
+impl ExportValue {
+    /// get the value as written in a csv file (with the full precision, NaN is an empty cell)
+    fn to_csv_field(&self) -> String {
+        match self {
+            ExportValue::Number(value) if value.is_nan() => String::new(),
+            ExportValue::Number(value) => value.to_string(),
+            ExportValue::Text(text) => text.clone(),
+        }
+    }
+
+    /// get the value as written in a json file (NaN and the infinites are null)
+    fn to_json_value(&self) -> serde_json::Value {
+        match self {
+            ExportValue::Number(value) => serde_json::Value::from(*value),
+            ExportValue::Text(text) => serde_json::Value::from(text.as_str()),
+        }
+    }
+}
+
+impl From<f64> for ExportValue {
+    fn from(value : f64) -> Self {
+        ExportValue::Number(value)
+    }
+}
+
+impl From<String> for ExportValue {
+    fn from(text : String) -> Self {
+        ExportValue::Text(text)
+    }
+}
+
+impl From<&str> for ExportValue {
+    fn from(text : &str) -> Self {
+        ExportValue::Text(text.to_string())
+    }
+}
+
+/// a tidy table to export the numbers behind a figure : one column per variable, one row per observation
+/// NOTE : the rows are written in insertion order
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportTable {
+    headers : Vec<String>,
+    /// NOTE : a value per header in each row
+    pub(crate) rows : Vec<Vec<ExportValue>>,
+}
+
+impl ExportTable {
+    pub fn new(headers : Vec<String>) -> Self {
+        Self {
+            headers,
+            rows : Vec::new(),
+        }
+    }
+
+    /// add a row, with a value per header
+    /// NOTE : return an error if the number of values is not the number of headers
+    pub fn add_row(&mut self, row : Vec<ExportValue>) -> Result<(), PlotHelperError> {
+        if row.len() != self.headers.len() {
+            return Err(PlotHelperError::InvalidParameter(format!("the row has {} values, but the table has {} columns", row.len(), self.headers.len())));
+        }
+        self.rows.push(row);
+        Ok(())
+    }
+
+    pub fn get_headers(&self) -> &Vec<String> {
+        &self.headers
+    }
+
+    pub fn get_rows(&self) -> &Vec<Vec<ExportValue>> {
+        &self.rows
+    }
+
+    /// save the table in a csv file (with the full precision), the first line is the header
+    /// NOTE : the file can be loaded back as an Array with Array::from_csv
+    pub fn save_to_csv(&self, dir_path : &str, file_name : &str) -> Result<(), PlotHelperError> {
+        self.save_to_path(&Path::new(dir_path).join(format!("{}.csv", file_name)), DataFormat::Csv)
+    }
+
+    /// save the table in a json file, as an array of objects (one per row)
+    pub fn save_to_json(&self, dir_path : &str, file_name : &str) -> Result<(), PlotHelperError> {
+        self.save_to_path(&Path::new(dir_path).join(format!("{}.json", file_name)), DataFormat::Json)
+    }
+
+    /// save the table in the given file, with the given format (whatever the extension of the path)
+    pub fn save_to_path(&self, path : &Path, format : DataFormat) -> Result<(), PlotHelperError> {
+        match format {
+            DataFormat::Csv => {
+                let mut writer = csv::Writer::from_path(path)?;
+                writer.write_record(&self.headers)?;
+                for row in self.rows.iter() {
+                    writer.write_record(row.iter().map(|value| value.to_csv_field()))?;
+                }
+                writer.flush()?;
+            },
+            DataFormat::Json => {
+                let objects : Vec<serde_json::Value> = self.rows.iter().map(|row| {
+                    let object = self.headers.iter().cloned()
+                        .zip(row.iter().map(|value| value.to_json_value()))
+                        .collect::<serde_json::Map<String, serde_json::Value>>();
+                    serde_json::Value::Object(object)
+                }).collect();
+                fs::write(path, serde_json::to_string_pretty(&objects)?)?;
+            },
+        }
+        Ok(())
+    }
+}
+
+/// get the table of the stats collected by Plottable::collect_stats_sorted_by_unique_values :
+/// a row per (sort value, serie), with the number of values and a column per metric
+/// NOTE : the rows are sorted by the sort values (numerically if they are numbers), then by the name of the series
+pub fn sorted_stats_table<Key>(
+    stats : &HashMap<String, HashMap<Key, StatsSerie>>,
+    sort_header : &str,
+    metrics : &[MetricName],
+) -> ExportTable
+where
+    Key : SerieKey,
+{
+    let headers = [sort_header.to_string(), "serie".to_string(), "count".to_string()].into_iter()
+        .chain(metrics.iter().map(|metric| metric.to_string()))
+        .collect();
+    let mut table = ExportTable::new(headers);
+
+    let mut sort_values : Vec<String> = stats.keys().cloned().collect();
+    LegendOrder::Numeric.sort(&mut sort_values);
+    for sort_value in sort_values.iter() {
+        let mut series : Vec<(&Key, &StatsSerie)> = stats[sort_value].iter().collect();
+        series.sort_by_key(|(key, _)| key.get_display_name());
+        for (key, serie) in series {
+            let row = [ExportValue::from(sort_value.as_str()), key.get_display_name().into(), (serie.serie.len() as f64).into()].into_iter()
+                .chain(metrics.iter().map(|metric| serie.get_stats(*metric).value.into()))
+                .collect();
+            table.rows.push(row);
+        }
+    }
+    table
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_table() -> ExportTable {
+        let mut table = ExportTable::new(vec!["legend".to_string(), "x".to_string(), "y".to_string()]);
+        table.add_row(vec!["a, b".into(), 1.0.into(), 0.1.into()]).unwrap();
+        table.add_row(vec!["c".into(), "cat".into(), f64::NAN.into()]).unwrap();
+        table
+    }
+
+    #[test]
+    fn test_export_table_rows() {
+        let mut table = get_table();
+        assert!(table.add_row(vec![1.0.into()]).is_err());
+        assert_eq!(table.get_rows().len(), 2);
+    }
+
+    #[test]
+    fn test_export_table_files() {
+        let dir = std::env::temp_dir();
+        let dir_path = dir.to_str().unwrap();
+        let table = get_table();
+
+        table.save_to_csv(dir_path, "plot_helper_export_table").unwrap();
+        let csv = fs::read_to_string(dir.join("plot_helper_export_table.csv")).unwrap();
+        assert_eq!(csv, "legend,x,y\n\"a, b\",1,0.1\nc,cat,\n");
+
+        table.save_to_json(dir_path, "plot_helper_export_table").unwrap();
+        let json : serde_json::Value = serde_json::from_str(&fs::read_to_string(dir.join("plot_helper_export_table.json")).unwrap()).unwrap();
+        assert_eq!(json, serde_json::json!([
+            {"legend" : "a, b", "x" : 1.0, "y" : 0.1},
+            {"legend" : "c", "x" : "cat", "y" : null},
+        ]));
+    }
+}
@@ -2,6 +2,7 @@
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator, IndexedParallelIterator};
 
 use crate::data::plottable::key::SerieKey;
+use crate::error::PlotHelperError;
 
 
 
@@ -31,6 +32,11 @@ impl Mask {
         }
     }
 
+    /// get the number of data kept by the mask
+    pub fn count(&self) -> usize {
+        self.mask.iter().filter(|m| **m).count()
+    }
+
     #[cfg(not(feature = "parrallelize"))]
     pub fn apply<U>(&self, data : &Vec<U>) -> Vec<U> where U : Clone {
         let mut filtered_data = Vec::new();
@@ -52,54 +58,97 @@ impl Mask {
 
 
 
+/// a filter condition which can be evaluated outside of the closures (pushed down in a sql query for example)
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterCondition {
+    /// keep the numeric values in [min, max] (an infinite bound is not checked)
+    Range(f64, f64),
+    /// keep the string values equal to one of the given values
+    OneOf(Vec<String>),
+}
+
 /// Define a filter
 pub struct Filter<Key> 
 where 
     Key : SerieKey
 {
     key : Key,
-    filter_number : Option<Box<dyn Fn(f32) -> bool>>,
+    filter_number : Option<Box<dyn Fn(f64) -> bool>>,
     filter_str : Option<Box<dyn Fn(&str) -> bool>>,
+    condition : Option<FilterCondition>,
 }
 
 impl<Key> Filter<Key>
 where 
     Key : SerieKey
 {
-    pub fn new_number<F>(key : Key, filter_fn : F) -> Self
+    /// create a filter on a numeric key (error if the key is not numeric)
+    pub fn new_number<F>(key : Key, filter_fn : F) -> Result<Self, PlotHelperError>
     where 
-        F : Fn(f32) -> bool + 'static
+        F : Fn(f64) -> bool + 'static
     {
-        assert!(key.is_numeric());
-        Self {
+        if !key.is_numeric() {
+            return Err(PlotHelperError::expected_numeric(key.get_display_name()));
+        }
+        Ok(Self {
             key,
             filter_number : Some(Box::new(filter_fn)),
             filter_str : None,
-        }
+            condition : None,
+        })
     }
 
-    pub fn new_str<F>(key : Key, filter_fn : F) -> Self
+    /// create a filter on a string key (error if the key is not a string)
+    pub fn new_str<F>(key : Key, filter_fn : F) -> Result<Self, PlotHelperError>
     where 
         F : Fn(&str) -> bool + 'static
     {
-        assert!(key.is_string());
-        Self {
+        if !key.is_string() {
+            return Err(PlotHelperError::expected_string(key.get_display_name()));
+        }
+        Ok(Self {
             key,
             filter_number : None,
             filter_str : Some(Box::new(filter_fn)),
-        }
+            condition : None,
+        })
+    }
+
+    /// create a filter keeping the values of a numeric key in [min, max] (error if the key is not numeric)
+    /// NOTE : use f64::NEG_INFINITY or f64::INFINITY for a bound not checked
+    /// NOTE : the condition is known, so it can be pushed down (see FilterCondition)
+    pub fn new_range(key : Key, min : f64, max : f64) -> Result<Self, PlotHelperError> {
+        let mut filter = Self::new_number(key, move |value| min <= value && value <= max)?;
+        filter.condition = Some(FilterCondition::Range(min, max));
+        Ok(filter)
+    }
+
+    /// create a filter keeping the values of a string key equal to one of the given values (error if the key is not a string)
+    /// NOTE : the condition is known, so it can be pushed down (see FilterCondition)
+    pub fn new_one_of(key : Key, values : Vec<String>) -> Result<Self, PlotHelperError> {
+        let kept_values = values.clone();
+        let mut filter = Self::new_str(key, move |value| kept_values.iter().any(|kept_value| kept_value == value))?;
+        filter.condition = Some(FilterCondition::OneOf(values));
+        Ok(filter)
     }
 
     pub fn get_key(&self) -> &Key {
         &self.key
     }
 
-    pub fn get_filter_number(&self) -> &Box<dyn Fn(f32) -> bool> {
-        self.filter_number.as_ref().unwrap()
+    /// get the closure of a filter on a numeric key (error if the key is not numeric)
+    pub fn get_filter_number(&self) -> Result<&dyn Fn(f64) -> bool, PlotHelperError> {
+        self.filter_number.as_deref().ok_or_else(|| PlotHelperError::expected_numeric(self.key.get_display_name()))
+    }
+
+    /// get the closure of a filter on a string key (error if the key is not a string)
+    pub fn get_filter_str(&self) -> Result<&dyn Fn(&str) -> bool, PlotHelperError> {
+        self.filter_str.as_deref().ok_or_else(|| PlotHelperError::expected_string(self.key.get_display_name()))
     }
 
-    pub fn get_filter_str(&self) -> &Box<dyn Fn(&str) -> bool> {
-        self.filter_str.as_ref().unwrap()
+    /// get the condition of the filter, None if the filter is only a closure
+    pub fn get_condition(&self) -> Option<&FilterCondition> {
+        self.condition.as_ref()
     }
 }
 
@@ -0,0 +1,267 @@
+use crate::data::plot_data::LegendOrder;
+use crate::data::plottable::key::SerieKey;
+use crate::data::plottable::Plottable;
+use crate::utils::axe_number_formater;
+
+/// separator between the values of the keys in a composite legend
+pub const LEGEND_SEPARATOR : &str = " / ";
+
+/// define how the colors are given to the values of the color dimension (first key)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorScale {
+    /// a color of the palette for each value
+    Palette,
+    /// a continuous color map over the numeric values, the legend is a color bar
+    /// NOTE : fallback on the palette if the values are not numeric
+    Gradient,
+    /// the numeric values are binned into the given number of ranges of the same width, with a color of the palette for each range
+    Bins(usize),
+}
+
+/// define how the samples are separated in series, and how the values of their keys are turned into legends
+/// keys : the keys used to separate the data in series, the legend of a sample is the combination
+/// of the values of the keys (all the data is in one serie if empty)
+/// order : the order of the series (drawing and legend)
+/// second_key_as_style : if true, the color is given by the first key only, and the values of the other keys
+/// are mapped to a marker shape (scatter plot) or a line dash (line plot)
+/// color_scale : how the colors are given to the values of the first key (if numeric), the Bins replace the values by their range
+/// NOTE : the colors of the legends are given by plotter::legend::LegendConfig
+#[derive(Debug, Clone, PartialEq)]
+pub struct LegendKeys<Key>
+where
+    Key : SerieKey,
+{
+    pub keys : Vec<Key>,
+    pub order : LegendOrder,
+    pub second_key_as_style : bool,
+    pub color_scale : ColorScale,
+}
+
+impl<Key> LegendKeys<Key>
+where
+    Key : SerieKey,
+{
+    /// create the legend keys, with the numeric order for a single numeric key and the alphabetical order otherwise
+    pub fn new(keys : Vec<Key>) -> Self {
+        let order = if keys.len() == 1 && keys[0].is_numeric() {
+            LegendOrder::Numeric
+        } else {
+            LegendOrder::Lexicographic
+        };
+        Self {
+            keys,
+            order,
+            second_key_as_style : false,
+            color_scale : ColorScale::Palette,
+        }
+    }
+
+    pub fn with_order(mut self, order : LegendOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    pub fn with_second_key_as_style(mut self, second_key_as_style : bool) -> Self {
+        self.second_key_as_style = second_key_as_style;
+        self
+    }
+
+    pub fn with_color_scale(mut self, color_scale : ColorScale) -> Self {
+        self.color_scale = color_scale;
+        self
+    }
+
+    /// if the values of the color dimension are a numeric key binned into ranges
+    pub fn is_binned(&self) -> bool {
+        matches!(self.color_scale, ColorScale::Bins(_)) && self.keys.first().map(|key| key.is_numeric()).unwrap_or(false)
+    }
+
+    /// if the color dimension is a numeric key drawn with a continuous color map
+    pub fn is_gradient(&self) -> bool {
+        self.color_scale == ColorScale::Gradient
+            && self.keys.first().map(|key| key.is_numeric()).unwrap_or(false)
+            && (self.keys.len() == 1 || self.is_styled())
+    }
+
+    /// if the style (marker or dash) of the series is given by the second key
+    pub fn is_styled(&self) -> bool {
+        self.second_key_as_style && self.keys.len() > 1
+    }
+
+    /// get the title of the legend (the display names of the keys), None if there is no key
+    pub fn get_title(&self) -> Option<String> {
+        if self.keys.is_empty() {
+            return None;
+        }
+        Some(self.keys.iter().map(|key| key.get_display_name()).collect::<Vec<_>>().join(LEGEND_SEPARATOR))
+    }
+
+    /// get the title of the color dimension and of the style dimension (if styled)
+    pub fn get_dimension_titles(&self) -> (Option<String>, Option<String>) {
+        if !self.is_styled() {
+            return (self.get_title(), None);
+        }
+        let style_title = self.keys[1..].iter().map(|key| key.get_display_name()).collect::<Vec<_>>().join(LEGEND_SEPARATOR);
+        (Some(self.keys[0].get_display_name()), Some(style_title))
+    }
+
+    /// get the full label, the color part and the style part (if styled) of a legend, from the values of its keys
+    pub(crate) fn split_key_values(&self, key_values : &[String]) -> (String, String, Option<String>) {
+        let label = join_legend_key_values(key_values);
+        if !self.is_styled() {
+            return (label.clone(), label, None);
+        }
+        let color_label = key_values.first().cloned().unwrap_or_default();
+        let style_label = join_legend_key_values(key_values.get(1..).unwrap_or_default());
+        (label, color_label, Some(style_label))
+    }
+}
+
+impl<Key> Default for LegendKeys<Key>
+where
+    Key : SerieKey,
+{
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+/// bin the numeric values into nb_of_bins ranges of the same width, and get the range label of each value
+/// NOTE : the last range include its upper bound, the NaN values get an empty label
+pub(crate) fn bin_numeric_values(values : &[f64], nb_of_bins : usize) -> Vec<String> {
+    let finite_values = values.iter().filter(|v| v.is_finite());
+    let min = finite_values.clone().fold(f64::MAX, |min, v| min.min(*v));
+    let max = finite_values.fold(f64::MIN, |max, v| max.max(*v));
+    if min > max || nb_of_bins == 0 { // no finite value
+        return vec![String::new(); values.len()];
+    }
+    if min == max {
+        let label = format!("[{}, {}]", axe_number_formater(&min), axe_number_formater(&max));
+        return values.iter().map(|v| if v.is_finite() { label.clone() } else { String::new() }).collect();
+    }
+
+    let width = (max - min) / nb_of_bins as f64;
+    let bounds = (0..=nb_of_bins).map(|index| if index == nb_of_bins { max } else { min + index as f64 * width }).collect::<Vec<f64>>();
+    let bound_labels = format_unique_bounds(&bounds);
+    let labels = (0..nb_of_bins).map(|index| {
+        let closing = if index == nb_of_bins - 1 { ']' } else { ')' };
+        format!("[{}, {}{}", bound_labels[index], bound_labels[index + 1], closing)
+    }).collect::<Vec<String>>();
+
+    values.iter().map(|v| {
+        if !v.is_finite() {
+            return String::new();
+        }
+        let index = (((v - min) / width) as usize).min(nb_of_bins - 1);
+        labels[index].clone()
+    }).collect()
+}
+
+/// format the increasing bounds of the ranges, with the precision needed to get a different label for each bound
+/// NOTE : formatted as the axes if the labels are different (see axe_number_formater)
+fn format_unique_bounds(bounds : &[f64]) -> Vec<String> {
+    let are_unique = |labels : &[String]| labels.windows(2).all(|pair| pair[0] != pair[1]);
+    let labels = bounds.iter().map(axe_number_formater).collect::<Vec<String>>();
+    if are_unique(&labels) {
+        return labels;
+    }
+    // the number of decimals needed to separate the closest bounds
+    (0..=MAX_BOUND_DECIMALS)
+        .map(|decimals| bounds.iter().map(|bound| format!("{:.*}", decimals, bound)).collect::<Vec<String>>())
+        .find(|labels| are_unique(labels))
+        .unwrap_or_else(|| bounds.iter().map(|bound| bound.to_string()).collect())
+}
+
+/// the maximal number of decimals of the bounds of the ranges (see format_unique_bounds)
+const MAX_BOUND_DECIMALS : usize = 12;
+
+/// get the values of the legend keys of each sample of the data (["all"] if there is no legend key)
+/// NOTE : if the legend is binned, the values of the first key are replaced by their range
+pub(crate) fn get_legend_key_values<Key>(
+    data : &dyn Plottable<Key>,
+    legend : &LegendKeys<Key>,
+) -> Vec<Vec<String>>
+where
+    Key : SerieKey,
+{
+    if legend.keys.is_empty() {
+        return vec![vec!["all".to_string()]; data.get_number_of_samples()];
+    }
+    let series = legend.keys.iter().enumerate().map(|(index, key)| {
+        match legend.color_scale {
+            // the values of the color dimension are replaced by their range
+            ColorScale::Bins(nb_of_bins) if index == 0 && legend.is_binned() =>
+                bin_numeric_values(&data.get_numeric_series(key), nb_of_bins),
+            _ => data.get_series_as_string(key),
+        }
+    }).collect::<Vec<_>>();
+    (0..data.get_number_of_samples()).map(|index| {
+        series.iter().map(|serie| serie[index].clone()).collect()
+    }).collect()
+}
+
+/// get the legend of each sample of the data ("all" if there is no legend key)
+/// NOTE : with multiple keys, the legend is the combination of the values, separated by LEGEND_SEPARATOR
+/// NOTE : if the legend is binned, the values of the first key are replaced by their range
+pub(crate) fn get_legend_values<Key>(
+    data : &dyn Plottable<Key>,
+    legend : &LegendKeys<Key>,
+) -> Vec<String>
+where
+    Key : SerieKey,
+{
+    get_legend_key_values(data, legend).iter().map(|key_values| join_legend_key_values(key_values)).collect()
+}
+
+/// get the legend of the values of the legend keys of a sample (see get_legend_key_values)
+pub(crate) fn join_legend_key_values(key_values : &[String]) -> String {
+    key_values.join(LEGEND_SEPARATOR)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_derive::{Deserialize, Serialize};
+    use std::fmt::{Display, Formatter};
+    use crate::generate_plot_key;
+
+    generate_plot_key!(TestKey[
+        Number { "number", Numeric },
+        Name { "name", String }
+    ]);
+
+    #[test]
+    fn test_legend_keys() {
+        let legend = LegendKeys::new(vec![TestKey::Number]);
+        assert_eq!(legend.order, LegendOrder::Numeric);
+        assert!(!legend.is_binned());
+        assert!(legend.clone().with_color_scale(ColorScale::Bins(4)).is_binned());
+        assert!(!LegendKeys::new(vec![TestKey::Name]).with_color_scale(ColorScale::Bins(4)).is_binned());
+
+        let legend = LegendKeys::new(vec![TestKey::Name, TestKey::Number]).with_second_key_as_style(true);
+        assert_eq!(legend.order, LegendOrder::Lexicographic);
+        let key_values = vec!["a / b".to_string(), "1".to_string()];
+        assert_eq!(legend.split_key_values(&key_values), ("a / b / 1".to_string(), "a / b".to_string(), Some("1".to_string())));
+    }
+
+    #[test]
+    fn test_bin_numeric_values() {
+        let labels = bin_numeric_values(&[1.0, 2.0, 3.0, 5.0, f64::NAN], 2);
+        assert_eq!(labels, vec!["[1.000, 3.000)", "[1.000, 3.000)", "[3.000, 5.000]", "[3.000, 5.000]", ""]);
+
+        let labels = bin_numeric_values(&[3.0, 3.0], 4);
+        assert_eq!(labels, vec!["[3.000, 3.000]", "[3.000, 3.000]"]);
+
+        assert_eq!(bin_numeric_values(&[f64::NAN], 4), vec![""]);
+
+        // the bounds above 1000 are written with enough precision to get a label per range
+        let values = (0..100).map(|i| 1000.0 + 40.0 * i as f64).collect::<Vec<f64>>();
+        let labels = bin_numeric_values(&values, 20);
+        let mut unique_labels = labels.clone();
+        unique_labels.dedup();
+        assert_eq!(unique_labels.len(), 20);
+        assert_eq!(labels[0], "[1000, 1198)");
+        assert_eq!(labels[99], "[4762, 4960]");
+    }
+}
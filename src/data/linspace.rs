@@ -11,8 +11,9 @@ pub struct Linspace {
 
 impl Linspace {
     /// Create a new linspace array
+    /// NOTE : a degenerate linspace (less than 2 values, or start >= end) has a step of 0 : all the values of the range are at the index 0
     pub fn new(start : f64, end : f64, len : usize) -> Self {
-        let step = (end - start) / (len - 1) as f64;
+        let step = if len > 1 && end > start { (end - start) / (len - 1) as f64 } else { 0.0 };
         Self {
             start,
             end,
@@ -21,13 +22,17 @@ impl Linspace {
         }
     }
 
-    /// Get the index of a value in the linspace array
+    /// Get the index of a value in the linspace array (None if the value is out of the range, or NaN)
     pub fn index_of(&self, value : &f64) -> Option<usize> {
-        if *value < self.start || *value > self.end {
+        // written to reject the NaN values
+        if !(*value >= self.start && *value <= self.end) {
             return None;
         }
+        if self.step == 0.0 {
+            return Some(0);
+        }
         let index = ((value - self.start) / self.step) as usize;
-        Some(index)
+        Some(index.min(self.len.saturating_sub(1)))
     }
 
     /// get the length of the linspace array
@@ -85,6 +90,19 @@ mod tests {
         assert_eq!(linspace.index_of(&10.0001), None);
     }
 
+    #[test]
+    fn test_linspace_degenerate() {
+        // a single value
+        let linspace = Linspace::new(0.0, 10.0, 1);
+        assert_eq!(linspace.step, 0.0);
+        assert_eq!(linspace.index_of(&5.0), Some(0));
+        // a zero width range
+        let linspace = Linspace::new(2.0, 2.0, 11);
+        assert_eq!(linspace.index_of(&2.0), Some(0));
+        assert_eq!(linspace.index_of(&2.5), None);
+        assert_eq!(linspace.index_of(&f64::NAN), None);
+    }
+
     #[test]
     fn test_linspace_get_len() {
         let linspace = Linspace::new(-5.0, 5.0, 21);
@@ -0,0 +1,140 @@
+use std::ops::Range;
+
+use crate::error::PlotHelperError;
+use crate::static_html::presentation_data::Array;
+
+/// number of decimals used when the matrix is converted into an html array
+const ARRAY_PRECISION : usize = 4;
+
+/// a matrix of values with a label for each row and each column (a correlation matrix for example)
+/// name : the name of the values (used as the title of the color scale of a heatmap)
+/// range : the range of the color scale (the range of the finite values if None)
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelledMatrix {
+    name : String,
+    row_labels : Vec<String>,
+    column_labels : Vec<String>,
+    values : Vec<Vec<f64>>,
+    range : Option<Range<f64>>,
+}
+
+impl LabelledMatrix {
+    /// create a matrix, the values are given row by row
+    /// NOTE : return an error if the size of the values doesn't match the labels
+    pub fn new(
+        name : &str,
+        row_labels : Vec<String>,
+        column_labels : Vec<String>,
+        values : Vec<Vec<f64>>,
+    ) -> Result<Self, PlotHelperError> {
+        if values.len() != row_labels.len() {
+            return Err(PlotHelperError::LayoutMismatch(format!("the matrix has {} rows but {} row labels", values.len(), row_labels.len())));
+        }
+        if let Some(row) = values.iter().find(|row| row.len() != column_labels.len()) {
+            return Err(PlotHelperError::LayoutMismatch(format!("a row of the matrix has {} values but there are {} column labels", row.len(), column_labels.len())));
+        }
+        Ok(Self {
+            name : name.to_string(),
+            row_labels,
+            column_labels,
+            values,
+            range : None,
+        })
+    }
+
+    pub fn with_range(mut self, range : Range<f64>) -> Self {
+        self.range = Some(range);
+        self
+    }
+
+    pub fn get_name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn get_row_labels(&self) -> &Vec<String> {
+        &self.row_labels
+    }
+
+    pub fn get_column_labels(&self) -> &Vec<String> {
+        &self.column_labels
+    }
+
+    pub fn get_values(&self) -> &Vec<Vec<f64>> {
+        &self.values
+    }
+
+    /// get the value at the given row and column labels
+    pub fn get(&self, row_label : &str, column_label : &str) -> Option<f64> {
+        let row = self.row_labels.iter().position(|label| label == row_label)?;
+        let column = self.column_labels.iter().position(|label| label == column_label)?;
+        Some(self.values[row][column])
+    }
+
+    /// get the range of the color scale : the fixed range, or the range of the finite values (0..1 if there is none)
+    pub fn get_range(&self) -> Range<f64> {
+        if let Some(range) = &self.range {
+            return range.clone();
+        }
+        let finite_values = self.values.iter().flatten().filter(|v| v.is_finite());
+        let (min, max) = finite_values.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| (min.min(*v), max.max(*v)));
+        if min > max { 0.0..1.0 } else { min..max }
+    }
+
+    /// convert the matrix to an html array (the first column is the row labels), with the given number of decimals
+    pub fn to_array(&self, precision : usize) -> Array {
+        let header = std::iter::once(String::new()).chain(self.column_labels.iter().cloned()).collect();
+        let data = self.row_labels.iter().zip(self.values.iter()).map(|(label, row)| {
+            std::iter::once(label.clone())
+                .chain(row.iter().map(|value| format!("{:.*}", precision, value)))
+                .collect()
+        }).collect();
+        Array::new(header, data)
+    }
+}
+
+impl From<&LabelledMatrix> for Array {
+    fn from(matrix : &LabelledMatrix) -> Array {
+        matrix.to_array(ARRAY_PRECISION)
+    }
+}
+
+impl From<LabelledMatrix> for Array {
+    fn from(matrix : LabelledMatrix) -> Array {
+        matrix.to_array(ARRAY_PRECISION)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_matrix() -> LabelledMatrix {
+        LabelledMatrix::new(
+            "value",
+            vec!["a".to_string(), "b".to_string()],
+            vec!["x".to_string(), "y".to_string(), "z".to_string()],
+            vec![vec![1.0, 2.0, f64::NAN], vec![-1.0, 0.5, 3.0]],
+        ).unwrap()
+    }
+
+    #[test]
+    fn test_labelled_matrix() {
+        let matrix = get_matrix();
+        assert_eq!(matrix.get("b", "y"), Some(0.5));
+        assert_eq!(matrix.get("c", "y"), None);
+        assert_eq!(matrix.get_range(), -1.0..3.0);
+        assert_eq!(matrix.with_range(-5.0..5.0).get_range(), -5.0..5.0);
+
+        assert!(LabelledMatrix::new("value", vec!["a".to_string()], vec![], vec![]).is_err());
+        assert!(LabelledMatrix::new("value", vec!["a".to_string()], vec![], vec![vec![1.0]]).is_err());
+    }
+
+    #[test]
+    fn test_labelled_matrix_to_array() {
+        let array = get_matrix().to_array(1);
+        assert_eq!(array.get_header(), &vec!["".to_string(), "x".to_string(), "y".to_string(), "z".to_string()]);
+        assert_eq!(array.get_data()[0], vec!["a", "1.0", "2.0", "NaN"]);
+        assert_eq!(array.get_data()[1], vec!["b", "-1.0", "0.5", "3.0"]);
+    }
+}
@@ -4,5 +4,9 @@ pub mod plottable;
 pub mod filtering;
 pub mod plot_data;
 pub mod linspace;
-
-
+pub mod axis;
+pub mod downsampling;
+pub mod matrix;
+pub mod datetime;
+pub mod export;
+pub mod legend_values;
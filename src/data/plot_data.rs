@@ -1,57 +1,293 @@
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::mem;
 use std::ops::Range;
 
+use crate::data::axis::AxesConfig;
+use crate::data::datetime::TimeBucket;
+use crate::data::downsampling::Downsampling;
+use crate::data::export::{DataFormat, ExportTable, ExportValue};
+use crate::data::linspace::Linspace;
 use crate::stats::stats_serie::{MetricName, StatsSerie};
-use crate::utils::compress_data_serie;
+use crate::error::PlotHelperError;
 
 
 
 
 
 /// a (x, y) point
-type Point = (f32, f32);
+type Point = (f64, f64);
+
+/// define the order of the legends (and so of the series) when they are iterated
+#[derive(Debug, Clone, PartialEq)]
+pub enum LegendOrder {
+    /// the order in which the legends were first added
+    Insertion,
+    /// alphabetical order
+    Lexicographic,
+    /// the legends are parsed as numbers, or as the lower bound of a range (the non numeric legends are put at the end, in alphabetical order)
+    Numeric,
+    /// the given order, the legends not in the list are put at the end, in alphabetical order
+    Custom(Vec<String>),
+}
+
+impl LegendOrder {
+    /// sort the given legends (initially in insertion order) with the order
+    pub fn sort(&self, legends : &mut [String]) {
+        match self {
+            LegendOrder::Insertion => (),
+            LegendOrder::Lexicographic => legends.sort(),
+            LegendOrder::Numeric => legends.sort_by(|a, b| {
+                match (parse_legend_number(a), parse_legend_number(b)) {
+                    (Some(a_number), Some(b_number)) => a_number.partial_cmp(&b_number).unwrap_or_else(|| a.cmp(b)),
+                    (Some(_), None) => Ordering::Less,
+                    (None, Some(_)) => Ordering::Greater,
+                    (None, None) => a.cmp(b),
+                }
+            }),
+            LegendOrder::Custom(order) => legends.sort_by(|a, b| {
+                match (order.iter().position(|o| o == a), order.iter().position(|o| o == b)) {
+                    (Some(a_index), Some(b_index)) => a_index.cmp(&b_index),
+                    (Some(_), None) => Ordering::Less,
+                    (None, Some(_)) => Ordering::Greater,
+                    (None, None) => a.cmp(b),
+                }
+            }),
+        }
+    }
+}
+
+/// define the order of the categories of a categorical x axis (a string serie)
+#[derive(Debug, Clone, PartialEq)]
+pub enum CategoryOrder {
+    /// sorted by name, as the legends (LegendOrder::Insertion keeps the order in which the categories appear in the data)
+    Name(LegendOrder),
+    /// sorted by a metric of the y values of each category (of all the legends), the categories without value are put at the end
+    Metric {
+        metric : MetricName,
+        descending : bool,
+    },
+}
+
+impl Default for CategoryOrder {
+    fn default() -> Self {
+        CategoryOrder::Name(LegendOrder::Insertion)
+    }
+}
+
+/// get the index of each value in the list of the distinct values (in order of appearance), and this list
+/// NOTE : the x values of a categorical serie are the indices of their category (see PlotSeries::get_x_categories)
+pub(crate) fn index_categories(values : &[String]) -> (Vec<f64>, Vec<String>) {
+    let mut categories : Vec<String> = Vec::new();
+    let mut indices_of_categories : HashMap<&String, usize> = HashMap::new();
+    let indices = values.iter().map(|value| {
+        let index = *indices_of_categories.entry(value).or_insert_with(|| {
+            categories.push(value.clone());
+            categories.len() - 1
+        });
+        index as f64
+    }).collect();
+    (indices, categories)
+}
+
+/// get a range with a positive width from the min and the max of an axis (see PlotSeries::get_range)
+pub(crate) fn get_drawable_range(min : f64, max : f64) -> Range<f64> {
+    if min > max {// no value
+        0.0..1.0
+    } else if min == max {
+        min - 0.5..max + 0.5
+    } else {
+        min..max
+    }
+}
+
+/// the bounds of the finite values of points, updated point by point (the NaN and infinite values can't be drawn)
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PointBounds {
+    x_min : f64,
+    x_max : f64,
+    y_min : f64,
+    y_max : f64,
+}
+
+impl Default for PointBounds {
+    fn default() -> Self {
+        Self {
+            x_min : f64::INFINITY,
+            x_max : f64::NEG_INFINITY,
+            y_min : f64::INFINITY,
+            y_max : f64::NEG_INFINITY,
+        }
+    }
+}
+
+impl PointBounds {
+    pub(crate) fn add(&mut self, (x, y) : &Point) {
+        if x.is_finite() {
+            self.x_min = self.x_min.min(*x);
+            self.x_max = self.x_max.max(*x);
+        }
+        if y.is_finite() {
+            self.y_min = self.y_min.min(*y);
+            self.y_max = self.y_max.max(*y);
+        }
+    }
+
+    /// get the drawable ranges of the x and y values (see get_drawable_range)
+    pub(crate) fn get_range(&self) -> (Range<f64>, Range<f64>) {
+        (get_drawable_range(self.x_min, self.x_max), get_drawable_range(self.y_min, self.y_max))
+    }
+}
+
+/// parse a numeric legend, or the lower bound of a range legend (as "[1, 2)")
+pub(crate) fn parse_legend_number(legend : &str) -> Option<f64> {
+    let trimmed = legend.trim_start_matches(['[', '(']);
+    let lower_bound = trimmed.split(',').next().unwrap_or(trimmed).trim();
+    lower_bound.parse::<f64>().ok()
+}
 
 /// represent pluggable data, indexed by a legend, for one graph
+/// NOTE : the legends are kept in insertion order, to iterate over the series deterministically
 #[derive(Debug, Clone)]
 pub struct PlotSeries {
-    data:  HashMap<String, Vec<Point>>
+    data:  HashMap<String, Vec<Point>>,
+    /// the legends in insertion order
+    legends : Vec<String>,
+    /// the names of the x values if the x serie is categorical : the x value i is the category x_categories[i]
+    x_categories : Option<Vec<String>>,
 }
 
 impl PlotSeries {
     pub fn new() -> Self {
         Self {
             data : HashMap::new(),
+            legends : Vec::new(),
+            x_categories : None,
+        }
+    }
+
+    /// the x values are the indices of the given categories
+    pub fn with_x_categories(mut self, categories : Vec<String>) -> Self {
+        self.x_categories = Some(categories);
+        self
+    }
+
+    /// get the categories of the x values (None if the x serie is not categorical)
+    pub fn get_x_categories(&self) -> Option<&Vec<String>> {
+        self.x_categories.as_ref()
+    }
+
+    /// get the series with the categories of the x values in the given order (the x values are the new indices, the series are sorted by x)
+    /// NOTE : the series are returned unchanged if the x serie is not categorical
+    pub fn sort_x_categories(&self, order : &CategoryOrder) -> PlotSeries {
+        let Some(categories) = &self.x_categories else {
+            return self.clone();
+        };
+        let mut sorted_indices : Vec<usize> = (0..categories.len()).collect();
+        match order {
+            CategoryOrder::Name(legend_order) => {
+                let mut names = categories.clone();
+                legend_order.sort(&mut names);
+                let positions : HashMap<&String, usize> = categories.iter().enumerate().map(|(index, name)| (name, index)).collect();
+                sorted_indices = names.iter().map(|name| positions[name]).collect();
+            },
+            CategoryOrder::Metric { metric, descending } => {
+                let mut values_of_categories = vec![Vec::new(); categories.len()];
+                for (_, serie) in self.iter() {
+                    for (x, y) in serie.iter() {
+                        if let Some(values) = values_of_categories.get_mut(*x as usize) {
+                            values.push(*y);
+                        }
+                    }
+                }
+                let metrics : Vec<f64> = values_of_categories.iter()
+                    .map(|values| if values.is_empty() { f64::NAN } else { StatsSerie::new(values).get_stats(*metric).value })
+                    .collect();
+                sorted_indices.sort_by(|a, b| match (metrics[*a].is_nan(), metrics[*b].is_nan()) {
+                    (false, false) if *descending => metrics[*b].total_cmp(&metrics[*a]),
+                    (false, false) => metrics[*a].total_cmp(&metrics[*b]),
+                    (nan_a, nan_b) => nan_a.cmp(&nan_b),
+                });
+            },
         }
+
+        // the new index of each old index
+        let mut new_indices = vec![0.0; categories.len()];
+        for (new_index, old_index) in sorted_indices.iter().enumerate() {
+            new_indices[*old_index] = new_index as f64;
+        }
+        let mut sorted = PlotSeries::new()
+            .with_x_categories(sorted_indices.iter().map(|index| categories[*index].clone()).collect());
+        for (legend, serie) in self.iter() {
+            let mut sorted_serie : Vec<Point> = serie.iter()
+                .filter_map(|(x, y)| Some((*new_indices.get(*x as usize)?, *y)))
+                .collect();
+            sorted_serie.sort_by(|(x1, _), (x2, _)| x1.total_cmp(x2));
+            sorted.legends.push(legend.clone());
+            sorted.data.insert(legend.clone(), sorted_serie);
+        }
+        sorted
     }
 
     /// compress the data to accelerate the plotting
     pub fn compress(&mut self) -> &mut Self{
         let (range_x, range_y) = self.get_range();
-        let original_data = mem::replace(&mut self.data, HashMap::new()); // take out the map
+        self.compress_in(&range_x, &range_y)
+    }
+
+    /// compress the data in the given ranges (the points outside the ranges are removed)
+    pub fn compress_in(&mut self, range_x : &Range<f64>, range_y : &Range<f64>) -> &mut Self{
+        self.downsample_in(range_x, range_y, &Downsampling::default())
+    }
+
+    /// downsample each serie in the given ranges (the points outside the ranges are removed), the series are then sorted by x
+    pub fn downsample_in(&mut self, range_x : &Range<f64>, range_y : &Range<f64>, downsampling : &Downsampling) -> &mut Self{
+        let original_data = mem::take(&mut self.data); // take out the map
         // Transform the data.
         self.data = original_data.into_iter().map(|(key, serie)| {
-            // Now you can avoid cloning the key, as `key` is owned here due to `into_iter()`.
-            let compressed_serie = compress_data_serie(serie, &range_x, &range_y);
-            (key, compressed_serie) // No need to clone the key.
+            let compressed_serie = downsampling.apply_in(serie, range_x, range_y);
+            (key, compressed_serie)
         }).collect();
 
-        return self
+        self
+    }
+
+    /// get the series in the plot space of the given axes (scaled and inverted)
+    /// NOTE : the points which can't be drawn on the axes (out of the scale domain or of the fixed range) are removed,
+    /// and so are the series left empty
+    pub fn to_axes_space(&self, axes : &AxesConfig) -> PlotSeries {
+        let mut transformed = PlotSeries::new();
+        transformed.x_categories = self.x_categories.clone();
+        for (legend, serie) in self.iter() {
+            let serie : Vec<Point> = serie.iter()
+                .filter_map(|(x, y)| Some((axes.x.to_plot_value(*x)?, axes.y.to_plot_value(*y)?)))
+                .collect();
+            if serie.is_empty() {
+                continue;
+            }
+            transformed.legends.push(legend.clone());
+            transformed.data.insert(legend.clone(), serie);
+        }
+        transformed
     }
 
     pub fn add(&mut self, legend : String, point : Point) {
+        if !self.data.contains_key(&legend) {
+            self.legends.push(legend.clone());
+        }
         let entry = self.data.entry(legend).or_insert(Vec::new());
         entry.push(point);
     }
 
     /// aggregate the data and combine the value with the same x value with a specified metric
-    pub fn apply_aggregator(&self, aggregator : MetricName) -> Result<PlotSeries, Box<dyn std::error::Error>> {
-        let mut aggregated_data = HashMap::new();
-        for (key, serie) in self.data.iter() {
+    pub fn apply_aggregator(&self, aggregator : MetricName) -> Result<PlotSeries, PlotHelperError> {
+        let mut aggregated_data = PlotSeries::new();
+        aggregated_data.x_categories = self.x_categories.clone();
+        for (key, serie) in self.iter() {
             let mut serie = serie.clone();
-            serie.sort_by(|(x1, _), (x2, _)| x1.partial_cmp(x2).unwrap());
+            serie.sort_by(|(x1, _), (x2, _)| x1.total_cmp(x2));
             let mut aggregated_serie = Vec::new(); // new serie
-            let mut current_x = f32::MIN; // current x value for the aggregation
+            let mut current_x = f64::MIN; // current x value for the aggregation
             let mut current_y = Vec::new(); // all the y values for the current x value
             for (x, y) in serie.iter() {
                 if *x == current_x { // if the x value is the same as the current one, add the y value to the current y values
@@ -60,7 +296,7 @@ impl PlotSeries {
                     if current_y.len() != 0 {
                         // calculate the metrics
                         let stats = StatsSerie::new(&current_y).get_stats(aggregator);
-                        aggregated_serie.push((current_x, stats.value as f32));
+                        aggregated_serie.push((current_x, stats.value));
                     }
                     current_x = *x;
                     current_y = Vec::new();
@@ -70,68 +306,197 @@ impl PlotSeries {
             if current_y.len() != 0 {
                 // calculate the metrics
                 let stats = StatsSerie::new(&current_y).get_stats(aggregator);
-                aggregated_serie.push((current_x, stats.value as f32));
+                aggregated_serie.push((current_x, stats.value));
 
             }
             // replace the serie with the aggregated one
-            aggregated_data.insert(key.clone(), aggregated_serie);
+            aggregated_data.legends.push(key.clone());
+            aggregated_data.data.insert(key.clone(), aggregated_serie);
         }
-        Ok(aggregated_data.into())
+        Ok(aggregated_data)
     }
 
-    pub fn get_range(&self) -> (Range<f32>, Range<f32>) {
-        let mut y_min = f32::MAX;
-        let mut y_max = f32::MIN;
-    
-        let mut x_min = f32::MAX;
-        let mut x_max = f32::MIN;
-    
-        // parcour the data and get the min and max of each axis
-        for (_, serie) in self.data.iter() {
+    /// aggregate the data by time bucket : the x values (of a datetime serie) are replaced by the start of their bucket,
+    /// then the values in the same bucket are combined with the metric (see apply_aggregator)
+    /// NOTE : the x values which are not dates are removed
+    pub fn apply_time_bucket_aggregator(&self, aggregator : MetricName, bucket : TimeBucket) -> Result<PlotSeries, PlotHelperError> {
+        let mut bucketed = PlotSeries::new();
+        for (legend, serie) in self.iter() {
             for (x, y) in serie.iter() {
-                if *x < x_min {
-                    x_min = *x;
-                }
-                if *x > x_max {
-                    x_max = *x;
-                }
-    
-                if *y < y_min {
-                    y_min = *y;
-                }
-                if *y > y_max {
-                    y_max = *y;
+                let x = bucket.floor_value(*x);
+                if !x.is_nan() {
+                    bucketed.add(legend.clone(), (x, *y));
                 }
             }
         }
-    
-        if y_min == f32::MAX || y_max == f32::MIN {// if the data is empty
-            y_min = 0.0;
-            y_max = 1.0;
+        bucketed.apply_aggregator(aggregator)
+    }
+
+    /// get the range of the finite values of the data on each axis
+    /// NOTE : an axis without finite value gets the range 0..1, and an axis with a single value v gets the range v - 0.5..v + 0.5
+    pub fn get_range(&self) -> (Range<f64>, Range<f64>) {
+        let mut bounds = PointBounds::default();
+        self.data.values().flatten().for_each(|point| bounds.add(point));
+        bounds.get_range()
+    }
+
+    /// true if there is no point in the series
+    pub fn is_empty(&self) -> bool {
+        self.data.values().all(|serie| serie.is_empty())
+    }
+
+    /// count the x values of each serie in nb_of_bins bins of the same width, between range_x.start and range_x.end
+    /// NOTE : each bin is represented by the point (bin start, count), the values outside the range are ignored
+    pub fn to_histogram(&self, range_x : &Range<f64>, nb_of_bins : usize) -> PlotSeries {
+        let nb_of_bins = nb_of_bins.max(1);
+        let edges = Linspace::new(range_x.start, range_x.end, nb_of_bins + 1);
+        let bin_width = (range_x.end - range_x.start) / nb_of_bins as f64;
+        let mut histogram = PlotSeries::new();
+        for (legend, serie) in self.iter() {
+            let mut counts = vec![0_usize; nb_of_bins];
+            for (x, _) in serie.iter() {
+                if let Some(index) = edges.index_of(x) {
+                    // the end of the range is in the last bin
+                    counts[index.min(nb_of_bins - 1)] += 1;
+                }
+            }
+            histogram.legends.push(legend.clone());
+            histogram.data.insert(legend.clone(), counts.into_iter().enumerate()
+                .map(|(index, count)| (range_x.start + index as f64 * bin_width, count as f64))
+                .collect());
         }
-    
-        if x_min == f32::MAX || x_max == f32::MIN {// if the data is empty
-            x_min = 0.0;
-            x_max = 1.0;
+        histogram
+    }
+
+    /// count the points of all the series in a grid of nb_of_bins x nb_of_bins cells of the same size, covering range_x and range_y
+    /// NOTE : the points outside the ranges are ignored
+    pub fn to_density(&self, range_x : &Range<f64>, range_y : &Range<f64>, nb_of_bins : usize) -> DensityGrid {
+        let nb_of_bins = nb_of_bins.max(1);
+        let x_edges = Linspace::new(range_x.start, range_x.end, nb_of_bins + 1);
+        let y_edges = Linspace::new(range_y.start, range_y.end, nb_of_bins + 1);
+        let mut counts = vec![vec![0_usize; nb_of_bins]; nb_of_bins];
+        for (_, serie) in self.iter() {
+            for (x, y) in serie.iter() {
+                if let (Some(x_index), Some(y_index)) = (x_edges.index_of(x), y_edges.index_of(y)) {
+                    // the end of the range is in the last cell
+                    counts[x_index.min(nb_of_bins - 1)][y_index.min(nb_of_bins - 1)] += 1;
+                }
+            }
         }
-    
-        if x_min == x_max {
-            x_min -= 0.5;
-            x_max += 0.5;
+        DensityGrid {
+            range_x : range_x.clone(),
+            range_y : range_y.clone(),
+            counts,
         }
-    
-        (x_min..x_max, y_min..y_max)
+    }
+
+    /// get the range of the data in the plot space of the given axes (the fixed ranges of the axes take precedence)
+    /// NOTE : the series must already be in the plot space (see to_axes_space)
+    pub fn get_range_in_axes(&self, axes : &AxesConfig) -> (Range<f64>, Range<f64>) {
+        let (range_x, range_y) = self.get_range();
+        (
+            axes.x.get_plot_range().unwrap_or(range_x),
+            axes.y.get_plot_range().unwrap_or(range_y),
+        )
     }
 
     pub fn get_data(&self) -> &HashMap<String, Vec<Point>> {
         &self.data
     }
+
+    /// get the legends, in insertion order
+    pub fn get_legends(&self) -> &Vec<String> {
+        &self.legends
+    }
+
+    /// iterate over the series in insertion order
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Vec<Point>)> {
+        self.legends.iter().map(move |legend| (legend, &self.data[legend]))
+    }
+
+    /// get the points as a tidy table : a row (legend, x, y) per point, in insertion order of the legends
+    /// NOTE : the x values of a categorical serie are the names of the categories
+    pub fn to_export_table(&self) -> ExportTable {
+        let mut table = ExportTable::new(vec!["legend".to_string(), "x".to_string(), "y".to_string()]);
+        for (legend, serie) in self.iter() {
+            for (x, y) in serie.iter() {
+                let x = match &self.x_categories {
+                    Some(categories) => ExportValue::from(categories.get(*x as usize).cloned().unwrap_or_default()),
+                    None => ExportValue::from(*x),
+                };
+                table.rows.push(vec![legend.as_str().into(), x, (*y).into()]);
+            }
+        }
+        table
+    }
+
+    /// save the points in a csv file, see to_export_table
+    pub fn save_to_csv(&self, dir_path : &str, file_name : &str) -> Result<(), PlotHelperError> {
+        self.to_export_table().save_to_csv(dir_path, file_name)
+    }
+
+    /// save the points in a json file, see to_export_table
+    pub fn save_to_json(&self, dir_path : &str, file_name : &str) -> Result<(), PlotHelperError> {
+        self.to_export_table().save_to_json(dir_path, file_name)
+    }
+
+    /// get the series sorted with the given legend order
+    pub fn get_ordered_data(&self, order : &LegendOrder) -> Vec<(&String, &Vec<Point>)> {
+        let mut legends = self.legends.clone();
+        order.sort(&mut legends);
+        legends.into_iter().map(|legend| {
+            let (legend, serie) = self.data.get_key_value(&legend).unwrap();
+            (legend, serie)
+        }).collect()
+    }
+}
+
+/// the number of points in each cell of a regular grid (see PlotSeries::to_density)
+#[derive(Debug, Clone, PartialEq)]
+pub struct DensityGrid {
+    range_x : Range<f64>,
+    range_y : Range<f64>,
+    /// the counts, indexed by [x index][y index]
+    counts : Vec<Vec<usize>>,
+}
+
+impl DensityGrid {
+    pub fn get_counts(&self) -> &Vec<Vec<usize>> {
+        &self.counts
+    }
+
+    pub fn get_max_count(&self) -> usize {
+        self.counts.iter().flatten().copied().max().unwrap_or(0)
+    }
+
+    /// get the size (width, height) of a cell
+    pub fn get_cell_size(&self) -> (f64, f64) {
+        let nb_of_bins = self.counts.len().max(1) as f64;
+        ((self.range_x.end - self.range_x.start) / nb_of_bins, (self.range_y.end - self.range_y.start) / nb_of_bins)
+    }
+
+    /// iterate over the cells with at least one point : (the bottom left corner of the cell, count)
+    pub fn iter_cells(&self) -> impl Iterator<Item = ((f64, f64), usize)> + '_ {
+        let (width, height) = self.get_cell_size();
+        self.counts.iter().enumerate().flat_map(move |(x_index, column)| {
+            column.iter().enumerate()
+                .filter(|(_, count)| **count > 0)
+                .map(move |(y_index, count)| {
+                    ((self.range_x.start + x_index as f64 * width, self.range_y.start + y_index as f64 * height), *count)
+                })
+        })
+    }
 }
 
+/// NOTE : the insertion order of the legends is the alphabetical one
 impl From<HashMap<String, Vec<Point>>> for PlotSeries {
     fn from(data : HashMap<String, Vec<Point>>) -> Self {
+        let mut legends = data.keys().cloned().collect::<Vec<String>>();
+        legends.sort();
         Self {
             data,
+            legends,
+            x_categories : None,
         }
     }
 }
@@ -144,9 +509,19 @@ impl Into<HashMap<String, Vec<Point>>> for PlotSeries {
 
 
 /// define a layout for the subplots
+/// title : the title of the whole figure (none if None)
+/// axes : the axes configuration of each subplot built by line_plot and scatter_plot (linear axes for all the subplots if None)
+/// NOTE : the subplots given to plot have their own axes (see Subplot::with_axes)
+/// share_x_range, share_y_range : if true, all the subplots use the same range on the axis (the union of the ranges)
+/// data_export : if Some, the data of the subplots is also saved next to the image, in the given format (see plot)
 pub struct Layout {
     pub height : usize,
     pub width : usize,
+    pub title : Option<String>,
+    pub axes : Option<Vec<AxesConfig>>,
+    pub share_x_range : bool,
+    pub share_y_range : bool,
+    pub data_export : Option<DataFormat>,
 }
 
 impl Layout {
@@ -154,8 +529,36 @@ impl Layout {
         Self {
             height,
             width,
+            title : None,
+            axes : None,
+            share_x_range : false,
+            share_y_range : false,
+            data_export : None,
         }
     }
+
+    pub fn with_title(mut self, title : &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    /// set the axes configuration of each subplot (one per subplot, in the layout order)
+    pub fn with_axes(mut self, axes : Vec<AxesConfig>) -> Self {
+        self.axes = Some(axes);
+        self
+    }
+
+    pub fn with_shared_ranges(mut self, share_x_range : bool, share_y_range : bool) -> Self {
+        self.share_x_range = share_x_range;
+        self.share_y_range = share_y_range;
+        self
+    }
+
+    /// save the data of the subplots next to the image (same path, with the extension of the format)
+    pub fn with_data_export(mut self, format : DataFormat) -> Self {
+        self.data_export = Some(format);
+        self
+    }
     /// get the plotter layout (row, col)
     pub fn get_plotter_layout(&self) -> (usize, usize) {
         (self.height, self.width)
@@ -164,4 +567,191 @@ impl Layout {
     pub fn get_nb_of_subplots(&self) -> usize {
         self.height * self.width
     }
+
+    /// get the axes configuration of each subplot
+    pub fn get_subplots_axes(&self) -> Result<Vec<AxesConfig>, PlotHelperError> {
+        match &self.axes {
+            None => Ok(vec![AxesConfig::default(); self.get_nb_of_subplots()]),
+            Some(axes) if axes.len() == self.get_nb_of_subplots() => Ok(axes.clone()),
+            Some(axes) => Err(PlotHelperError::InvalidParameter(format!(
+                "the number of axes configurations ({}) is not equal to the number of subplots ({})", axes.len(), self.get_nb_of_subplots()
+            ))),
+        }
+    }
+
+    /// share the ranges of the subplots, following the layout configuration
+    pub fn share_ranges(&self, ranges : &mut [(Range<f64>, Range<f64>)]) {
+        if self.share_x_range {
+            let shared = union_ranges(ranges.iter().map(|(range_x, _)| range_x));
+            ranges.iter_mut().for_each(|(range_x, _)| *range_x = shared.clone());
+        }
+        if self.share_y_range {
+            let shared = union_ranges(ranges.iter().map(|(_, range_y)| range_y));
+            ranges.iter_mut().for_each(|(_, range_y)| *range_y = shared.clone());
+        }
+    }
+}
+
+/// get the smallest range containing all the given ranges (0..1 if there is no range)
+fn union_ranges<'a>(ranges : impl Iterator<Item = &'a Range<f64>>) -> Range<f64> {
+    ranges.fold(None, |union : Option<Range<f64>>, range| match union {
+        None => Some(range.clone()),
+        Some(union) => Some(union.start.min(range.start)..union.end.max(range.end)),
+    }).unwrap_or(0.0..1.0)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plot_series_insertion_order() {
+        let mut series = PlotSeries::new();
+        series.add("b".to_string(), (1.0, 1.0));
+        series.add("10".to_string(), (1.0, 1.0));
+        series.add("b".to_string(), (2.0, 2.0));
+        series.add("9".to_string(), (1.0, 1.0));
+        assert_eq!(series.get_legends(), &vec!["b".to_string(), "10".to_string(), "9".to_string()]);
+
+        let aggregated = series.apply_aggregator(MetricName::Mean).unwrap();
+        assert_eq!(aggregated.iter().map(|(legend, _)| legend.as_str()).collect::<Vec<_>>(), vec!["b", "10", "9"]);
+    }
+
+    #[test]
+    fn test_plot_series_ordered_data() {
+        let mut series = PlotSeries::new();
+        for legend in ["b", "10", "a", "9"] {
+            series.add(legend.to_string(), (1.0, 1.0));
+        }
+        let get_order = |order : LegendOrder| {
+            series.get_ordered_data(&order).iter().map(|(legend, _)| legend.to_string()).collect::<Vec<_>>()
+        };
+        assert_eq!(get_order(LegendOrder::Insertion), vec!["b", "10", "a", "9"]);
+        assert_eq!(get_order(LegendOrder::Lexicographic), vec!["10", "9", "a", "b"]);
+        assert_eq!(get_order(LegendOrder::Numeric), vec!["9", "10", "a", "b"]);
+        assert_eq!(get_order(LegendOrder::Custom(vec!["a".to_string(), "9".to_string()])), vec!["a", "9", "10", "b"]);
+    }
+
+    #[test]
+    fn test_plot_series_time_bucket_aggregator() {
+        use crate::data::datetime::Timestamp;
+
+        let value = |datetime : &str| datetime.parse::<Timestamp>().unwrap().to_value();
+        let mut series = PlotSeries::new();
+        series.add("a".to_string(), (value("2024-03-11T08:00:00Z"), 1.0));
+        series.add("a".to_string(), (value("2024-03-11T20:00:00Z"), 3.0));
+        series.add("a".to_string(), (value("2024-03-13T12:00:00Z"), 5.0));
+        series.add("a".to_string(), (f64::NAN, 100.0));
+
+        let per_day = series.apply_time_bucket_aggregator(MetricName::Mean, TimeBucket::Day).unwrap();
+        assert_eq!(per_day.get_data()["a"], vec![(value("2024-03-11T00:00:00Z"), 2.0), (value("2024-03-13T00:00:00Z"), 5.0)]);
+        let per_week = series.apply_time_bucket_aggregator(MetricName::Median, TimeBucket::Week).unwrap();
+        assert_eq!(per_week.get_data()["a"], vec![(value("2024-03-11T00:00:00Z"), 3.0)]);
+    }
+
+    #[test]
+    fn test_plot_series_categories() {
+        let names = |series : &PlotSeries| series.get_x_categories().unwrap().clone();
+        let (indices, categories) = index_categories(&["b".to_string(), "a".to_string(), "b".to_string(), "10".to_string()]);
+        assert_eq!(indices, vec![0.0, 1.0, 0.0, 2.0]);
+        let mut series = PlotSeries::new().with_x_categories(categories);
+        for (x, y) in indices.into_iter().zip([1.0, 5.0, 3.0, 2.0]) {
+            series.add("l".to_string(), (x, y));
+        }
+        assert_eq!(series.apply_aggregator(MetricName::Mean).unwrap().get_data()["l"], vec![(0.0, 2.0), (1.0, 5.0), (2.0, 2.0)]);
+
+        let sorted = series.sort_x_categories(&CategoryOrder::Name(LegendOrder::Numeric));
+        assert_eq!(names(&sorted), vec!["10", "a", "b"]);
+        assert_eq!(sorted.get_data()["l"], vec![(0.0, 2.0), (1.0, 5.0), (2.0, 1.0), (2.0, 3.0)]);
+        // the mean of "b" is 2, of "a" 5, of "10" 2 : the ties keep their order
+        let sorted = series.sort_x_categories(&CategoryOrder::Metric { metric : MetricName::Mean, descending : true });
+        assert_eq!(names(&sorted), vec!["a", "b", "10"]);
+        let sorted = series.sort_x_categories(&CategoryOrder::Metric { metric : MetricName::Percentile(100), descending : false });
+        assert_eq!(names(&sorted), vec!["10", "b", "a"]);
+        assert!(PlotSeries::new().sort_x_categories(&CategoryOrder::default()).get_x_categories().is_none());
+    }
+
+    #[test]
+    fn test_legend_order_numeric_ranges() {
+        let mut legends = vec!["[10, 20]".to_string(), "[-5, 2.5)".to_string(), "[2.5, 10)".to_string()];
+        LegendOrder::Numeric.sort(&mut legends);
+        assert_eq!(legends, vec!["[-5, 2.5)", "[2.5, 10)", "[10, 20]"]);
+    }
+
+    #[test]
+    fn test_plot_series_axes_space() {
+        use crate::data::axis::{AxisConfig, AxisScale};
+
+        let mut series = PlotSeries::new();
+        series.add("a".to_string(), (0.0, 1.0));
+        series.add("a".to_string(), (10.0, 100.0));
+        series.add("a".to_string(), (1000.0, 5.0));
+        series.add("b".to_string(), (-1.0, 1.0));
+        let axes = AxesConfig::new(
+            AxisConfig::new(AxisScale::Log10),
+            AxisConfig::default().with_range(0.0..50.0).with_inverted(true),
+        );
+        let transformed = series.to_axes_space(&axes);
+        assert_eq!(transformed.get_legends(), &vec!["a".to_string()]);
+        assert_eq!(transformed.get_data()["a"], vec![(3.0, -5.0)]);
+
+        let (range_x, range_y) = transformed.get_range_in_axes(&axes);
+        assert_eq!(range_x, 2.5..3.5);
+        assert_eq!(range_y, -50.0..0.0);
+    }
+
+    #[test]
+    fn test_plot_series_compress_in_range() {
+        let mut series = PlotSeries::new();
+        series.add("a".to_string(), (0.0, 0.0));
+        series.add("a".to_string(), (5.0, 5.0));
+        series.add("a".to_string(), (20.0, 5.0));
+        series.compress_in(&(0.0..10.0), &(0.0..10.0));
+        let mut compressed = series.get_data()["a"].clone();
+        compressed.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(compressed, vec![(0.0, 0.0), (5.0, 5.0)]);
+    }
+
+    #[test]
+    fn test_plot_series_histogram() {
+        let mut series = PlotSeries::new();
+        for x in [0.0, 0.5, 1.0, 3.9, 4.0, 7.0] {
+            series.add("a".to_string(), (x, x));
+        }
+        let histogram = series.to_histogram(&(0.0..4.0), 4);
+        assert_eq!(histogram.get_data()["a"], vec![(0.0, 2.0), (1.0, 1.0), (2.0, 0.0), (3.0, 2.0)]);
+    }
+
+    #[test]
+    fn test_plot_series_density() {
+        let mut series = PlotSeries::new();
+        series.add("a".to_string(), (0.0, 0.0));
+        series.add("a".to_string(), (0.4, 0.1));
+        series.add("b".to_string(), (2.0, 2.0));
+        series.add("b".to_string(), (5.0, 1.0));
+        let density = series.to_density(&(0.0..2.0), &(0.0..2.0), 2);
+        assert_eq!(density.get_counts(), &vec![vec![2, 0], vec![0, 1]]);
+        assert_eq!(density.get_max_count(), 2);
+        assert_eq!(density.get_cell_size(), (1.0, 1.0));
+        assert_eq!(density.iter_cells().collect::<Vec<_>>(), vec![((0.0, 0.0), 2), ((1.0, 1.0), 1)]);
+    }
+
+    #[test]
+    fn test_layout_shared_ranges() {
+        let mut ranges = vec![(0.0..1.0, 0.0..10.0), (-1.0..0.5, 5.0..20.0)];
+        Layout::new(2, 1).with_shared_ranges(true, false).share_ranges(&mut ranges);
+        assert_eq!(ranges, vec![(-1.0..1.0, 0.0..10.0), (-1.0..1.0, 5.0..20.0)]);
+        Layout::new(2, 1).with_shared_ranges(false, true).share_ranges(&mut ranges);
+        assert_eq!(ranges, vec![(-1.0..1.0, 0.0..20.0), (-1.0..1.0, 0.0..20.0)]);
+    }
+
+    #[test]
+    fn test_layout_subplots_axes() {
+        use crate::data::axis::{AxisConfig, AxisScale};
+        assert_eq!(Layout::new(2, 1).get_subplots_axes().unwrap(), vec![AxesConfig::default(); 2]);
+        let axes = vec![AxesConfig::new(AxisConfig::new(AxisScale::Log10), AxisConfig::default()), AxesConfig::default()];
+        assert_eq!(Layout::new(2, 1).with_axes(axes.clone()).get_subplots_axes().unwrap(), axes);
+        assert!(Layout::new(1, 1).with_axes(axes).get_subplots_axes().is_err());
+    }
 }
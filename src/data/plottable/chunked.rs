@@ -0,0 +1,481 @@
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::mem;
+use std::str::FromStr;
+
+#[cfg(feature = "parrallelize")]
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+
+use crate::data::datetime::Timestamp;
+use crate::data::downsampling::Downsampling;
+use crate::data::filtering::{Filter, Mask};
+use crate::data::legend_values::{get_legend_key_values, join_legend_key_values, LegendKeys};
+use crate::data::plot_data::{PlotSeries, PointBounds};
+use crate::error::PlotHelperError;
+use crate::stats::stats_serie::MetricName;
+use crate::stats::streaming::StreamingStats;
+use crate::utils::{fill_compression_grid, sort_by_x, CompressionGrid};
+
+use super::key::SerieKey;
+use super::sample::{MultipleSample, Sample, SimpleSample};
+use super::{Plottable, PlottableSamples, PlottableStruct};
+
+/// a (x, y) point
+type Point = (f64, f64);
+
+/// the data of a list of files, read chunk by chunk : only the samples of a chunk of files are in memory at once
+/// (to analyse more samples than the memory can hold)
+/// NOTE : a file which can't be loaded stops the analysis with its error (a truncated file would change the results silently)
+/// NOTE : each analysis reads all the files again, the results are computed chunk by chunk with a bounded memory :
+/// the aggregation keeps a StreamingStats per (legend, x), the grid downsampling keeps a cell per (legend, cell of the grid)
+/// and the statistics keep a StreamingStats per key
+/// NOTE : the outliers can't be removed (they are computed on the whole serie), and the legends can't be binned
+/// NOTE : it is not a Plottable (a serie would hold all the samples in memory), plot the points of reduce instead
+pub struct ChunkedPlottable<SampleType, KeyType>
+where
+    KeyType : SerieKey,
+    SampleType : Sample<KeyType>,
+{
+    file_paths : Vec<String>,
+    files_per_chunk : usize,
+    load_file : fn(&str) -> Result<Vec<SampleType>, PlotHelperError>,
+    _key_type : PhantomData<KeyType>,
+}
+
+impl<SampleType, KeyType> ChunkedPlottable<SampleType, KeyType>
+where
+    KeyType : SerieKey,
+    SampleType : Sample<KeyType>,
+{
+    /// read the files of SimpleSample, files_per_chunk files at once
+    pub fn from_simple_sample_paths(file_paths : &[String], files_per_chunk : usize) -> Self
+    where
+        SampleType : SimpleSample<KeyType>,
+    {
+        Self {
+            file_paths : file_paths.to_vec(),
+            files_per_chunk : files_per_chunk.max(1),
+            load_file : load_simple_sample::<SampleType, KeyType>,
+            _key_type : PhantomData,
+        }
+    }
+
+    /// read the files of MultipleSample, files_per_chunk files at once
+    pub fn from_multiple_sample_paths(file_paths : &[String], files_per_chunk : usize) -> Self
+    where
+        SampleType : MultipleSample<KeyType>,
+    {
+        Self {
+            file_paths : file_paths.to_vec(),
+            files_per_chunk : files_per_chunk.max(1),
+            load_file : load_multiple_samples::<SampleType, KeyType>,
+            _key_type : PhantomData,
+        }
+    }
+
+    pub fn get_file_paths(&self) -> &Vec<String> {
+        &self.file_paths
+    }
+
+    /// call the function on each chunk of samples, in the order of the files (stop at the first error)
+    pub fn for_each_chunk<F>(&self, mut function : F) -> Result<(), PlotHelperError>
+    where
+        F : FnMut(&PlottableStruct<SampleType, KeyType>) -> Result<(), PlotHelperError>,
+    {
+        for file_paths in self.file_paths.chunks(self.files_per_chunk) {
+            function(&self.load_chunk(file_paths)?)?;
+        }
+        Ok(())
+    }
+
+    #[cfg(not(feature = "parrallelize"))]
+    fn load_chunk(&self, file_paths : &[String]) -> Result<PlottableStruct<SampleType, KeyType>, PlotHelperError> {
+        let samples = file_paths.iter().map(|path| (self.load_file)(path)).collect::<Result<Vec<_>, _>>()?;
+        Ok(PlottableStruct::new(samples.into_iter().flatten().collect()))
+    }
+
+    #[cfg(feature = "parrallelize")]
+    fn load_chunk(&self, file_paths : &[String]) -> Result<PlottableStruct<SampleType, KeyType>, PlotHelperError> {
+        let samples = file_paths.par_iter().map(|path| (self.load_file)(path)).collect::<Result<Vec<_>, _>>()?;
+        Ok(PlottableStruct::new(samples.into_iter().flatten().collect()))
+    }
+
+    /// get the number of samples kept by the filters
+    pub fn get_number_of_filtered_samples(&self, filters : &Option<Vec<&Filter<KeyType>>>) -> Result<usize, PlotHelperError> {
+        let mut nb_of_samples = 0;
+        self.for_each_chunk(|chunk| {
+            nb_of_samples += chunk.combine_filter(filters, &None).count();
+            Ok(())
+        })?;
+        Ok(nb_of_samples)
+    }
+
+    /// group the data by legend, chunk by chunk (see Plottable::aggregate)
+    /// NOTE : if aggregation_metric is Some, the y values with the same x value are combined with the metric (see StreamingStats),
+    /// else the points of each legend are downsampled over the range of the data (see PlotSeries::compress).
+    /// A Grid downsampling is computed chunk by chunk (the files are then read twice : for the range, then for the cells),
+    /// the other downsamplings need all the points of the legend in memory
+    /// NOTE : a string x serie is categorical, its categories are in order of appearance (see PlotSeries::get_x_categories),
+    /// the points of different categories are never averaged together
+    pub fn aggregate_by_chunk(
+        &self,
+        x_serie_key : &KeyType,
+        y_serie_key : &KeyType,
+        filters : &Option<Vec<&Filter<KeyType>>>,
+        legend : &LegendKeys<KeyType>,
+        aggregation_metric : Option<MetricName>,
+        downsampling : &Downsampling,
+    ) -> Result<PlotSeries, PlotHelperError> {
+        let reduction = self.reduce_points(x_serie_key, y_serie_key, filters, legend, aggregation_metric, downsampling)?;
+        let mut aggregate_data = PlotSeries::new();
+        if let Some(categories) = reduction.categories.get_categories() {
+            aggregate_data = aggregate_data.with_x_categories(categories);
+        }
+        for (legend_values, points) in reduction.series {
+            let legend = join_legend_key_values(&legend_values);
+            points.into_iter().for_each(|point| aggregate_data.add(legend.clone(), point));
+        }
+        Ok(aggregate_data)
+    }
+
+    /// get the points of aggregate_by_chunk as an in memory plottable, with a sample per point : the x and y series,
+    /// and the series of the legend keys. The subplots of these keys can then be plotted with plotter::plot::plot
+    /// NOTE : the filters and the aggregation are already applied, give the aggregation metric to the subplot to draw a line
+    /// NOTE : only the x, y and legend keys have a serie : a subplot using another key (in a filter, or to remove the outliers)
+    /// panics when it is plotted
+    pub fn reduce(
+        &self,
+        x_serie_key : &KeyType,
+        y_serie_key : &KeyType,
+        filters : &Option<Vec<&Filter<KeyType>>>,
+        legend : &LegendKeys<KeyType>,
+        aggregation_metric : Option<MetricName>,
+        downsampling : &Downsampling,
+    ) -> Result<ReducedPlottable<KeyType>, PlotHelperError> {
+        let reduction = self.reduce_points(x_serie_key, y_serie_key, filters, legend, aggregation_metric, downsampling)?;
+        let categories = reduction.categories.get_categories();
+
+        let mut reduced = ReducedPlottable {
+            numeric_series : HashMap::new(),
+            string_series : HashMap::new(),
+            nb_of_samples : 0,
+        };
+        let mut legend_series : Vec<Vec<String>> = vec![Vec::new(); legend.keys.len()];
+        let (mut x_serie, mut y_serie) = (Vec::new(), Vec::new());
+        for (legend_values, points) in reduction.series.iter() {
+            for (x, y) in points.iter() {
+                legend_series.iter_mut().zip(legend_values.iter()).for_each(|(serie, value)| serie.push(value.clone()));
+                x_serie.push(*x);
+                y_serie.push(*y);
+            }
+        }
+        reduced.nb_of_samples = y_serie.len();
+
+        for (key, serie) in legend.keys.iter().zip(legend_series) {
+            if key.is_numeric() {
+                reduced.numeric_series.insert(*key, serie.iter().map(|value| parse_numeric_value(key, value)).collect());
+            } else {
+                reduced.string_series.insert(*key, serie);
+            }
+        }
+        match categories {
+            Some(categories) => {
+                // the x values are the exact indices of the categories (see reduce_points)
+                reduced.string_series.insert(*x_serie_key, x_serie.iter().map(|x| categories[*x as usize].clone()).collect());
+            },
+            None => {
+                reduced.numeric_series.insert(*x_serie_key, x_serie);
+            },
+        }
+        reduced.numeric_series.insert(*y_serie_key, y_serie);
+        Ok(reduced)
+    }
+
+    /// compute the stats of each numeric key, chunk by chunk (the filters are applied before)
+    pub fn collect_streaming_stats(
+        &self,
+        stats_serie_keys : &[KeyType],
+        filters : &Option<Vec<&Filter<KeyType>>>,
+    ) -> Result<HashMap<KeyType, StreamingStats>, PlotHelperError> {
+        check_numeric_keys(stats_serie_keys)?;
+        let mut stats : HashMap<KeyType, StreamingStats> = HashMap::new();
+        self.for_each_chunk(|chunk| {
+            let filter_mask = chunk.combine_filter(filters, &None);
+            for key in stats_serie_keys.iter() {
+                let key_stats = stats.entry(*key).or_default();
+                filter_mask.apply(&chunk.get_numeric_series(key)).iter().for_each(|value| key_stats.add(*value));
+            }
+            Ok(())
+        })?;
+        Ok(stats)
+    }
+
+    /// compute the stats of each numeric key for each unique value of the sort key, chunk by chunk
+    /// (see Plottable::collect_stats_sorted_by_unique_values, the filters are applied before)
+    pub fn collect_streaming_stats_sorted_by_unique_values(
+        &self,
+        stats_serie_keys : &[KeyType],
+        sort_value_key : &KeyType,
+        filters : &Option<Vec<&Filter<KeyType>>>,
+    ) -> Result<HashMap<String, HashMap<KeyType, StreamingStats>>, PlotHelperError> {
+        check_numeric_keys(stats_serie_keys)?;
+        let mut stats : HashMap<String, HashMap<KeyType, StreamingStats>> = HashMap::new();
+        self.for_each_chunk(|chunk| {
+            let filter_mask = chunk.combine_filter(filters, &None);
+            let sort_serie = if sort_value_key.is_numeric() {
+                filter_mask.apply(&chunk.get_numeric_series(sort_value_key)).iter().map(|value| value.to_string()).collect()
+            } else {
+                filter_mask.apply(&chunk.get_string_series(sort_value_key))
+            };
+            for key in stats_serie_keys.iter() {
+                let key_serie = filter_mask.apply(&chunk.get_numeric_series(key));
+                for (value, sort_value) in key_serie.iter().zip(sort_serie.iter()) {
+                    stats.entry(sort_value.clone()).or_default().entry(*key).or_default().add(*value);
+                }
+            }
+            Ok(())
+        })?;
+        Ok(stats)
+    }
+
+    /// aggregate or downsample the points of each legend (see aggregate_by_chunk)
+    fn reduce_points(
+        &self,
+        x_serie_key : &KeyType,
+        y_serie_key : &KeyType,
+        filters : &Option<Vec<&Filter<KeyType>>>,
+        legend : &LegendKeys<KeyType>,
+        aggregation_metric : Option<MetricName>,
+        downsampling : &Downsampling,
+    ) -> Result<Reduction, PlotHelperError> {
+        if !y_serie_key.is_numeric() {
+            return Err(PlotHelperError::expected_numeric(y_serie_key.get_display_name()));
+        }
+        if legend.is_binned() {
+            return Err(PlotHelperError::InvalidParameter("the legends of a chunked plottable can't be binned".to_string()));
+        }
+        let mut reduction = Reduction::new(x_serie_key.is_numeric());
+
+        match (aggregation_metric, downsampling) {
+            (Some(metric), _) => {
+                let mut cells : HashMap<(usize, u64), StreamingStats> = HashMap::new();
+                self.for_each_chunk(|chunk| {
+                    for (legend_index, x, y) in reduction.get_chunk_points(chunk, x_serie_key, y_serie_key, filters, legend) {
+                        // 0.0 and -0.0 are the same x value
+                        let x = if x == 0.0 { 0.0 } else { x };
+                        cells.entry((legend_index, x.to_bits())).or_default().add(y);
+                    }
+                    Ok(())
+                })?;
+                for ((legend_index, x), stats) in cells {
+                    reduction.series[legend_index].1.push((f64::from_bits(x), stats.get_stats(metric).value));
+                }
+                reduction.series.iter_mut().for_each(|(_, points)| sort_by_x(points));
+            },
+            (None, Downsampling::Grid(resolution)) => {
+                let mut bounds = PointBounds::default();
+                let mut range_reduction = Reduction::new(x_serie_key.is_numeric());
+                self.for_each_chunk(|chunk| {
+                    range_reduction.get_chunk_points(chunk, x_serie_key, y_serie_key, filters, legend).iter()
+                        .for_each(|(_, x, y)| bounds.add(&(*x, *y)));
+                    Ok(())
+                })?;
+                let (range_x, range_y) = bounds.get_range();
+
+                // a grid per legend, and per category of a categorical x : the indices of the categories are kept out of the means
+                // NOTE : the points of each chunk are compressed in their own grid, merged into the grid of all the chunks
+                let mut grids : HashMap<(usize, Option<usize>), CompressionGrid> = HashMap::new();
+                self.for_each_chunk(|chunk| {
+                    let mut chunk_points : HashMap<(usize, Option<usize>), Vec<Point>> = HashMap::new();
+                    for (legend_index, x, y) in reduction.get_chunk_points(chunk, x_serie_key, y_serie_key, filters, legend) {
+                        let category = (!x_serie_key.is_numeric()).then_some(x as usize);
+                        chunk_points.entry((legend_index, category)).or_default().push((x, y));
+                    }
+                    for (grid_key, points) in chunk_points {
+                        let chunk_grid = fill_compression_grid(points, &range_x, &range_y, *resolution);
+                        match grids.get_mut(&grid_key) {
+                            Some(grid) => grid.merge(&chunk_grid),
+                            None => {
+                                grids.insert(grid_key, chunk_grid);
+                            },
+                        }
+                    }
+                    Ok(())
+                })?;
+                for ((legend_index, category), grid) in grids {
+                    let points = grid.into_points().into_iter().map(|(x, y)| (category.map_or(x, |category| category as f64), y));
+                    reduction.series[legend_index].1.extend(points);
+                }
+                reduction.series.iter_mut().for_each(|(_, points)| sort_by_x(points));
+            },
+            (None, downsampling) => {
+                // the other downsamplings select the points of the whole serie
+                self.for_each_chunk(|chunk| {
+                    for (legend_index, x, y) in reduction.get_chunk_points(chunk, x_serie_key, y_serie_key, filters, legend) {
+                        reduction.series[legend_index].1.push((x, y));
+                    }
+                    Ok(())
+                })?;
+                let mut bounds = PointBounds::default();
+                reduction.series.iter().flat_map(|(_, points)| points.iter()).for_each(|point| bounds.add(point));
+                let (range_x, range_y) = bounds.get_range();
+                reduction.series.iter_mut()
+                    .for_each(|(_, points)| *points = downsampling.apply_in(mem::take(points), &range_x, &range_y));
+            },
+        }
+
+        Ok(reduction)
+    }
+}
+
+/// the points of each legend (in order of appearance), reduced chunk by chunk
+struct Reduction {
+    /// the values of the legend keys, and the points
+    series : Vec<(Vec<String>, Vec<Point>)>,
+    indices_of_legends : HashMap<Vec<String>, usize>,
+    categories : Categories,
+}
+
+impl Reduction {
+    fn new(numeric_x : bool) -> Self {
+        Self {
+            series : Vec::new(),
+            indices_of_legends : HashMap::new(),
+            categories : if numeric_x { Categories::None } else { Categories::Indexed(Vec::new(), HashMap::new()) },
+        }
+    }
+
+    /// get the (index of the legend, x, y) of the samples of the chunk kept by the filters
+    /// NOTE : the legends and the categories are indexed in order of appearance
+    fn get_chunk_points<SampleType, KeyType>(
+        &mut self,
+        chunk : &PlottableStruct<SampleType, KeyType>,
+        x_serie_key : &KeyType,
+        y_serie_key : &KeyType,
+        filters : &Option<Vec<&Filter<KeyType>>>,
+        legend : &LegendKeys<KeyType>,
+    ) -> Vec<(usize, f64, f64)>
+    where
+        KeyType : SerieKey,
+        SampleType : Sample<KeyType>,
+    {
+        let filter_mask : Mask = chunk.combine_filter(filters, &None);
+        let x_serie = match &mut self.categories {
+            Categories::None => filter_mask.apply(&chunk.get_numeric_series(x_serie_key)),
+            categories => filter_mask.apply(&chunk.get_string_series(x_serie_key)).into_iter().map(|value| categories.index_of(value)).collect(),
+        };
+        let y_serie = filter_mask.apply(&chunk.get_numeric_series(y_serie_key));
+        let legend_key_values = filter_mask.apply(&get_legend_key_values(chunk, legend));
+
+        legend_key_values.into_iter().enumerate().map(|(index, legend_values)| {
+            let legend_index = match self.indices_of_legends.get(&legend_values) {
+                Some(legend_index) => *legend_index,
+                None => {
+                    self.series.push((legend_values.clone(), Vec::new()));
+                    self.indices_of_legends.insert(legend_values, self.series.len() - 1);
+                    self.series.len() - 1
+                },
+            };
+            (legend_index, x_serie[index], y_serie[index])
+        }).collect()
+    }
+}
+
+/// the categories of a categorical x serie, indexed in order of appearance
+enum Categories {
+    None,
+    Indexed(Vec<String>, HashMap<String, usize>),
+}
+
+impl Categories {
+    fn index_of(&mut self, value : String) -> f64 {
+        match self {
+            Categories::None => f64::NAN,
+            Categories::Indexed(categories, indices_of_categories) => {
+                let index = *indices_of_categories.entry(value).or_insert_with_key(|value| {
+                    categories.push(value.clone());
+                    categories.len() - 1
+                });
+                index as f64
+            },
+        }
+    }
+
+    fn get_categories(&self) -> Option<Vec<String>> {
+        match self {
+            Categories::None => None,
+            Categories::Indexed(categories, _) => Some(categories.clone()),
+        }
+    }
+}
+
+/// the points reduced from a ChunkedPlottable (see ChunkedPlottable::reduce), a sample per point
+/// NOTE : only the x, y and legend keys have a serie, the other keys panic (as a sample without the value)
+#[derive(Debug, Clone)]
+pub struct ReducedPlottable<Key>
+where
+    Key : SerieKey,
+{
+    numeric_series : HashMap<Key, Vec<f64>>,
+    string_series : HashMap<Key, Vec<String>>,
+    nb_of_samples : usize,
+}
+
+impl<Key> Plottable<Key> for ReducedPlottable<Key>
+where
+    Key : SerieKey,
+{
+    fn get_number_of_samples(&self) -> usize {
+        self.nb_of_samples
+    }
+
+    fn get_numeric_series(&self, key : &Key) -> Vec<f64> {
+        self.numeric_series.get(key)
+            .unwrap_or_else(|| panic!("the key {} has no numeric serie in the reduced data", key.get_display_name()))
+            .clone()
+    }
+
+    fn get_string_series(&self, key : &Key) -> Vec<String> {
+        self.string_series.get(key)
+            .unwrap_or_else(|| panic!("the key {} has no string serie in the reduced data", key.get_display_name()))
+            .clone()
+    }
+}
+
+fn load_simple_sample<SampleType, KeyType>(path : &str) -> Result<Vec<SampleType>, PlotHelperError>
+where
+    KeyType : SerieKey,
+    SampleType : SimpleSample<KeyType>,
+{
+    Ok(vec![SampleType::new_from_file_path(path)?])
+}
+
+fn load_multiple_samples<SampleType, KeyType>(path : &str) -> Result<Vec<SampleType>, PlotHelperError>
+where
+    KeyType : SerieKey,
+    SampleType : MultipleSample<KeyType>,
+{
+    SampleType::new_from_file_path(path)
+}
+
+fn check_numeric_keys<Key>(keys : &[Key]) -> Result<(), PlotHelperError>
+where
+    Key : SerieKey,
+{
+    match keys.iter().find(|key| !key.is_numeric()) {
+        Some(key) => Err(PlotHelperError::expected_numeric(key.get_display_name())),
+        None => Ok(()),
+    }
+}
+
+/// parse back a numeric value written by Plottable::get_series_as_string (NaN if it can't be parsed)
+fn parse_numeric_value<Key>(key : &Key, value : &str) -> f64
+where
+    Key : SerieKey,
+{
+    if key.is_datetime() {
+        Timestamp::from_str(value).map(|timestamp| timestamp.to_value()).unwrap_or(f64::NAN)
+    } else {
+        value.parse::<f64>().unwrap_or(f64::NAN)
+    }
+}
@@ -5,9 +5,14 @@ pub trait SerieKey : Eq + std::hash::Hash + Copy + Display + Ord + Send + Sync{
     /// get the display name of the serie
     fn get_display_name(&self) -> String;
 
-    /// if the serie is numeric
+    /// if the serie is numeric (true for a datetime serie)
     fn is_numeric(&self) -> bool;
 
+    /// if the serie is a datetime, its numeric values are the seconds since the unix epoch (see Timestamp)
+    fn is_datetime(&self) -> bool {
+        false
+    }
+
     /// if the serie is a string
     fn is_string(&self) -> bool;
 
@@ -17,6 +22,7 @@ pub trait SerieKey : Eq + std::hash::Hash + Copy + Display + Ord + Send + Sync{
 
 
 /// Define a fully initialized key for a serie
+/// The type of a key is Numeric, String, Object or Datetime (a numeric key drawn with dates, see Timestamp)
 /// Warn : must be called only once by file
 /// Need the dependances :
 /// ```
@@ -31,11 +37,14 @@ pub trait SerieKey : Eq + std::hash::Hash + Copy + Display + Ord + Send + Sync{
 ///     ],
 ///     SingleLineQueryKey[
 ///         ParsingTime { "parsing time (s)", Numeric },
-///         File { "file", String }
+///         File { "file", String },
+///         Date { "date", Datetime }
 ///     ]
 /// );
 /// assert_eq!(MultiLineQueryKey::ParsingTime.get_display_name(), "parsing time (s)");
 /// assert_eq!(MultiLineQueryKey::ParsingTime.is_numeric(), true);
+/// assert_eq!(SingleLineQueryKey::Date.is_numeric(), true);
+/// assert_eq!(SingleLineQueryKey::Date.is_datetime(), true);
 /// ```
 #[macro_export]
 macro_rules! generate_plot_key {
@@ -52,7 +61,9 @@ macro_rules! generate_plot_key {
             /// The key is a string
             String,
             /// The key is an object
-            Object
+            Object,
+            /// The key is a datetime (numeric)
+            Datetime
         }
 
         /// Define the keys
@@ -71,7 +82,12 @@ macro_rules! generate_plot_key {
                 }
                 fn is_numeric(&self) -> bool {
                     match self {
-                        $($key_name::$variant => KeyType::$key_type == KeyType::Numeric),*
+                        $($key_name::$variant => KeyType::$key_type == KeyType::Numeric || KeyType::$key_type == KeyType::Datetime),*
+                    }
+                }
+                fn is_datetime(&self) -> bool {
+                    match self {
+                        $($key_name::$variant => KeyType::$key_type == KeyType::Datetime),*
                     }
                 }
                 fn is_string(&self) -> bool {
@@ -117,7 +133,8 @@ mod tests {
         generate_plot_key!(TestKey[
             A { "A", Numeric },
             B { "B", String },
-            C { "C", Object }
+            C { "C", Object },
+            D { "D", Datetime }
         ]);
 
         let key = TestKey::A;
@@ -138,6 +155,11 @@ mod tests {
         assert_eq!(key.is_numeric(), false);
         assert_eq!(key.is_string(), false);
         assert_eq!(key.is_object(), true);
+
+        // a datetime is numeric
+        assert!(TestKey::D.is_numeric());
+        assert!(TestKey::D.is_datetime());
+        assert!(!TestKey::A.is_datetime());
     }
 
 
@@ -3,17 +3,27 @@ use std::collections::HashMap;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use serde_derive::{Deserialize, Serialize};
 
+use crate::data::datetime::Timestamp;
 use crate::data::plottable::key::SerieKey;
+use crate::error::PlotHelperError;
+use crate::stats::correlation::CorrelationMethod;
 use crate::stats::get_outliers;
+use crate::stats::group_by::{GroupByRow, GroupByTable};
 use crate::stats::stats_serie::{MetricName, StatsSerie};
 
 use self::sample::{MultipleSample, Sample, SimpleSample};
 
 use super::filtering::{Filter, Mask};
-use super::plot_data::PlotSeries;
+use super::matrix::LabelledMatrix;
+use super::plot_data::{index_categories, PlotSeries};
 
+pub mod chunked;
 pub mod key;
+#[cfg(feature = "arrow")]
+pub mod record_batch;
 pub mod sample;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
 
 /// Define method to be plottable by the plotter
 pub trait Plottable<KeyType> 
@@ -23,13 +33,15 @@ where
     /// Get the number of samples
     fn get_number_of_samples(&self) -> usize;
     /// Get the numeric series associated to the given key
-    fn get_numeric_series(&self, key : &KeyType) -> Vec<f32>;
+    fn get_numeric_series(&self, key : &KeyType) -> Vec<f64>;
     /// Get the string series associated to the given key
     fn get_string_series(&self, key : &KeyType) -> Vec<String>;
 
     /// aggregate the data and sort each point by legend
     /// Apply also the filters and remove the outliers
     /// NOTE : the data is aggregated by the x_serie_key if aggregation_metrics is Some
+    /// NOTE : a string x serie is categorical : its x values are the indices of the categories kept by the filters,
+    /// in order of appearance (see PlotSeries::get_x_categories)
     fn aggregate(&self, 
         x_serie_key : &KeyType,
         y_serie_key : &KeyType,
@@ -37,13 +49,19 @@ where
         legends : &Vec<String>,
         remove_outliers : &Option<Vec<KeyType>>,
         aggregation_metrics : Option<MetricName>,
-    ) -> Result<PlotSeries, Box<dyn std::error::Error>>{
+    ) -> Result<PlotSeries, PlotHelperError>{
         let mut aggregate_data = PlotSeries::new();
 
         // filter the data (filter and outliers)
         let filter_mask : Mask = self.combine_filter(filters, remove_outliers);
 
-        let x_serie = filter_mask.apply(&self.get_numeric_series(x_serie_key));
+        let x_serie = if x_serie_key.is_numeric() {
+            filter_mask.apply(&self.get_numeric_series(x_serie_key))
+        } else {
+            let (x_serie, categories) = index_categories(&filter_mask.apply(&self.get_string_series(x_serie_key)));
+            aggregate_data = aggregate_data.with_x_categories(categories);
+            x_serie
+        };
         let y_serie = filter_mask.apply(&self.get_numeric_series(y_serie_key));
         let legends = filter_mask.apply(&legends);
 
@@ -75,13 +93,14 @@ where
         let mut filter_mask : Mask = Mask::new(self.get_number_of_samples());
         match filters {
             Some(filters_vec) => {
+                // a filter has the closure of the type of its key (checked at its creation)
                 for filter in filters_vec {
-                    if filter.get_key().is_numeric() {
+                    if let Ok(filter_number) = filter.get_filter_number() {
                         let serie = self.get_numeric_series(filter.get_key());
-                        filter_mask.merge(&serie.iter().map(|v| filter.get_filter_number()(*v)).collect());
-                    } else {
+                        filter_mask.merge(&serie.iter().map(|v| filter_number(*v)).collect());
+                    } else if let Ok(filter_str) = filter.get_filter_str() {
                         let serie = self.get_string_series(filter.get_key());
-                        filter_mask.merge(&serie.iter().map(|v| filter.get_filter_str()(v)).collect());
+                        filter_mask.merge(&serie.iter().map(|v| filter_str(v)).collect());
                     }
                 }
             },
@@ -101,11 +120,12 @@ where
     /// Collect statistics for multiple series sorted by a the uniquee value of a specified key.
     /// This function is optimized for speed but not for memory (O(n)).
     /// Warning: Avoid calling this function multiple times with different metrics as it may be slow.
+    /// NOTE : all the stats serie keys must be numeric
     fn collect_stats_sorted_by_unique_values(
         &self, 
         stats_serie_keys : &Vec<KeyType>, 
         sort_value_key : &KeyType
-    ) -> HashMap<String, HashMap<KeyType, StatsSerie>> {
+    ) -> Result<HashMap<String, HashMap<KeyType, StatsSerie>>, PlotHelperError> {
         let mut serie_by_key:HashMap<KeyType, HashMap<String, StatsSerie>>  = HashMap::new();
         let sort_serie = if sort_value_key.is_numeric() {
             self.get_numeric_series(sort_value_key).iter().map(|f| f.to_string()).collect::<Vec<String>>()
@@ -113,8 +133,10 @@ where
             self.get_string_series(sort_value_key)
         };
         for key in stats_serie_keys {
-            assert!(key.is_numeric(), "stats serie key must be numeric");
-            let mut sorted_series: HashMap<String, Vec<f32>> = HashMap::new();
+            if !key.is_numeric() {
+                return Err(PlotHelperError::expected_numeric(key.get_display_name()));
+            }
+            let mut sorted_series: HashMap<String, Vec<f64>> = HashMap::new();
 
             let key_series = self.get_numeric_series(key);
             let iter = key_series.iter().zip(sort_serie.iter());
@@ -142,7 +164,102 @@ where
             }
         }
 
-        return serie_by_sort;
+        Ok(serie_by_sort)
+    }
+
+    /// Get the series associated to the given key as string (numeric values are converted, the datetimes are in the RFC 3339 format)
+    fn get_series_as_string(&self, key : &KeyType) -> Vec<String> {
+        if key.is_datetime() {
+            self.get_numeric_series(key).iter()
+                .map(|v| Timestamp::from_value(*v).map(|timestamp| timestamp.to_string()).unwrap_or_default())
+                .collect()
+        } else if key.is_numeric() {
+            self.get_numeric_series(key).iter().map(|v| v.to_string()).collect()
+        } else {
+            self.get_string_series(key)
+        }
+    }
+
+    /// Group the samples by the unique combinations of the group keys, and aggregate each
+    /// (value key, metric) on each group. The filters are applied before the grouping.
+    /// The rows of the returned table are sorted by the groups (see GroupByTable)
+    fn group_by(
+        &self,
+        group_keys : &[KeyType],
+        aggregations : &[(KeyType, MetricName)],
+        filters : &Option<Vec<&Filter<KeyType>>>,
+    ) -> Result<GroupByTable, PlotHelperError> {
+        for (value_key, _) in aggregations.iter() {
+            if !value_key.is_numeric() {
+                return Err(PlotHelperError::expected_numeric(value_key.get_display_name()));
+            }
+        }
+
+        let filter_mask : Mask = self.combine_filter(filters, &None);
+
+        let group_series = group_keys.iter()
+            .map(|key| filter_mask.apply(&self.get_series_as_string(key)))
+            .collect::<Vec<Vec<String>>>();
+        // load each value serie only once
+        let mut value_series : HashMap<KeyType, Vec<f64>> = HashMap::new();
+        for (value_key, _) in aggregations.iter() {
+            if !value_series.contains_key(value_key) {
+                value_series.insert(*value_key, filter_mask.apply(&self.get_numeric_series(value_key)));
+            }
+        }
+
+        // indexes of the samples of each group
+        let mut groups : HashMap<Vec<String>, Vec<usize>> = HashMap::new();
+        for index in 0..filter_mask.count() {
+            let group = group_series.iter().map(|serie| serie[index].clone()).collect::<Vec<String>>();
+            groups.entry(group).or_default().push(index);
+        }
+
+        let rows = groups.into_iter().map(|(group, indexes)| {
+            let mut stats_by_key : HashMap<KeyType, StatsSerie> = HashMap::new();
+            let values = aggregations.iter().map(|(value_key, metric)| {
+                let stats = stats_by_key.entry(*value_key).or_insert_with(|| {
+                    let serie = &value_series[value_key];
+                    StatsSerie::new(&indexes.iter().map(|i| serie[*i]).collect())
+                });
+                stats.get_stats(*metric).value
+            }).collect();
+            GroupByRow {
+                groups : group,
+                values,
+            }
+        }).collect();
+
+        Ok(GroupByTable::new(
+            group_keys.iter().map(|key| key.get_display_name()).collect(),
+            group_keys.iter().map(|key| key.is_numeric()).collect(),
+            aggregations.iter().map(|(key, metric)| format!("{} ({})", key.get_display_name(), metric)).collect(),
+            rows,
+        ))
+    }
+
+    /// Compute the correlation between each pair of the given numeric keys (the filters are applied before)
+    /// The rows and the columns of the matrix are the keys, in the given order
+    fn correlation_matrix(
+        &self,
+        keys : &[KeyType],
+        filters : &Option<Vec<&Filter<KeyType>>>,
+        method : CorrelationMethod,
+    ) -> Result<LabelledMatrix, PlotHelperError> {
+        if let Some(key) = keys.iter().find(|key| !key.is_numeric()) {
+            return Err(PlotHelperError::expected_numeric(key.get_display_name()));
+        }
+
+        let filter_mask : Mask = self.combine_filter(filters, &None);
+        let series = keys.iter()
+            .map(|key| filter_mask.apply(&self.get_numeric_series(key)))
+            .collect::<Vec<Vec<f64>>>();
+
+        let values = series.iter()
+            .map(|x| series.iter().map(|y| method.correlation(x, y)).collect())
+            .collect();
+        let labels : Vec<String> = keys.iter().map(|key| key.get_display_name()).collect();
+        Ok(LabelledMatrix::new(&method.to_string(), labels.clone(), labels, values)?.with_range(-1.0..1.0))
     }
 }
 
@@ -287,8 +404,8 @@ where
         self.samples.len()
     }
     #[cfg(not(feature = "parrallelize"))]
-    fn get_numeric_series(&self, key : &KeyType) -> Vec<f32> {
-        self.samples.iter().map(|sample| sample.get_numeric_value(key)).collect()
+    fn get_numeric_series(&self, key : &KeyType) -> Vec<f64> {
+        self.samples.iter().map(|sample| sample.get_numeric_value_f64(key)).collect()
     }
     #[cfg(not(feature = "parrallelize"))]
     fn get_string_series(&self, key : &KeyType) -> Vec<String> {
@@ -296,8 +413,8 @@ where
     }
 
     #[cfg(feature = "parrallelize")]
-    fn get_numeric_series(&self, key : &KeyType) -> Vec<f32> {
-        self.samples.par_iter().map(|sample| sample.get_numeric_value(key)).collect()
+    fn get_numeric_series(&self, key : &KeyType) -> Vec<f64> {
+        self.samples.par_iter().map(|sample| sample.get_numeric_value_f64(key)).collect()
     }
 
     #[cfg(feature = "parrallelize")]
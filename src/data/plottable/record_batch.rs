@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::sync::Arc;
+
+use arrow::array::{Array, ArrayRef, AsArray, RecordBatch, RecordBatchReader};
+use arrow::compute::{can_cast_types, cast};
+use arrow::datatypes::{DataType, Float64Type, SchemaRef, TimeUnit};
+use arrow::ipc::reader::FileReader;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ProjectionMask;
+
+use crate::error::PlotHelperError;
+
+use super::key::SerieKey;
+use super::Plottable;
+
+/// plottable data backed by arrow record batches (loaded from a parquet or an arrow ipc file for example), each key is served by a column
+/// NOTE : the values are read from the columns at each call, without building a sample per row :
+/// the float64 columns are copied as is, the other numeric columns are cast to float64,
+/// the temporal columns (timestamps, dates) are converted in seconds since the unix epoch (see Timestamp),
+/// and the string keys are cast to strings
+/// NOTE : the null values are NaN in a numeric serie, and empty in a string serie
+/// NOTE : all the batches have the given schema (the series of a data without batch are empty)
+#[derive(Debug, Clone)]
+pub struct RecordBatchPlottable<Key>
+where
+    Key : SerieKey,
+{
+    batches : Vec<RecordBatch>,
+    /// the index of the column of each key
+    columns : HashMap<Key, usize>,
+    nb_of_samples : usize,
+}
+
+impl<Key> RecordBatchPlottable<Key>
+where
+    Key : SerieKey,
+{
+    /// create the plottable from the batches of the schema, columns gives the name of the column of each key
+    /// NOTE : return an error if a column is missing, if a numeric key is not in a numeric (or temporal) column,
+    /// if a column can't be read as string for a string key, or if a batch doesn't have the schema
+    pub fn new(schema : SchemaRef, batches : Vec<RecordBatch>, columns : &[(Key, &str)]) -> Result<Self, PlotHelperError> {
+        if let Some(batch) = batches.iter().find(|batch| batch.schema() != schema) {
+            return Err(PlotHelperError::Parse(format!("the record batches have different schemas ({} and {})", schema, batch.schema())));
+        }
+
+        let mut column_indices = HashMap::new();
+        for (key, column) in columns.iter() {
+            let index = schema.index_of(column)?;
+            let data_type = schema.field(index).data_type();
+            if key.is_numeric() && !(data_type.is_numeric() || get_temporal_unit(data_type).is_some()) {
+                return Err(PlotHelperError::expected_numeric(format!("{} (column {} of type {})", key.get_display_name(), column, data_type)));
+            }
+            if !key.is_numeric() && !can_cast_types(data_type, &DataType::Utf8) {
+                return Err(PlotHelperError::expected_string(format!("{} (column {} of type {})", key.get_display_name(), column, data_type)));
+            }
+            column_indices.insert(*key, index);
+        }
+
+        Ok(Self {
+            nb_of_samples : batches.iter().map(|batch| batch.num_rows()).sum(),
+            batches,
+            columns : column_indices,
+        })
+    }
+
+    /// load the columns of the keys from a parquet file (the other columns are not read)
+    pub fn from_parquet(path : &str, columns : &[(Key, &str)]) -> Result<Self, PlotHelperError> {
+        let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(path)?)?;
+        let indices = get_column_indices(builder.schema(), columns)?;
+        let mask = ProjectionMask::roots(builder.parquet_schema(), indices);
+        let reader = builder.with_projection(mask).build()?;
+        let schema = reader.schema();
+        let batches = reader.collect::<Result<Vec<_>, _>>()?;
+        Self::new(schema, batches, columns)
+    }
+
+    /// load the columns of the keys from an arrow ipc file (the other columns are not read)
+    pub fn from_arrow_ipc(path : &str, columns : &[(Key, &str)]) -> Result<Self, PlotHelperError> {
+        let file_schema = FileReader::try_new(File::open(path)?, None)?.schema();
+        let indices = get_column_indices(&file_schema, columns)?;
+        // the schema of the reader is the schema of the file, the batches have the projected one
+        let schema = Arc::new(file_schema.project(&indices)?);
+        let batches = FileReader::try_new(File::open(path)?, Some(indices))?.collect::<Result<Vec<_>, _>>()?;
+        Self::new(schema, batches, columns)
+    }
+
+    pub fn get_batches(&self) -> &Vec<RecordBatch> {
+        &self.batches
+    }
+
+    /// get the arrays of the column of the key (one per batch), without copy
+    /// NOTE : None if the key has no column
+    pub fn get_arrays(&self, key : &Key) -> Option<Vec<&ArrayRef>> {
+        let index = self.columns.get(key)?;
+        Some(self.batches.iter().map(|batch| batch.column(*index)).collect())
+    }
+
+    /// get the arrays of the column of the key, panic if the key has no column (as a sample without the value)
+    fn get_key_arrays(&self, key : &Key) -> Vec<&ArrayRef> {
+        self.get_arrays(key)
+            .unwrap_or_else(|| panic!("the key {} has no column in the record batches", key.get_display_name()))
+    }
+}
+
+impl<Key> Plottable<Key> for RecordBatchPlottable<Key>
+where
+    Key : SerieKey,
+{
+    fn get_number_of_samples(&self) -> usize {
+        self.nb_of_samples
+    }
+
+    fn get_numeric_series(&self, key : &Key) -> Vec<f64> {
+        let mut serie = Vec::with_capacity(self.nb_of_samples);
+        for array in self.get_key_arrays(key) {
+            extend_with_numeric_values(&mut serie, array);
+        }
+        serie
+    }
+
+    fn get_string_series(&self, key : &Key) -> Vec<String> {
+        let mut serie = Vec::with_capacity(self.nb_of_samples);
+        for array in self.get_key_arrays(key) {
+            match cast(array, &DataType::Utf8) {
+                Ok(strings) => serie.extend(strings.as_string::<i32>().iter().map(|value| value.unwrap_or_default().to_string())),
+                // checked at the creation
+                Err(_) => serie.extend((0..array.len()).map(|_| String::new())),
+            }
+        }
+        serie
+    }
+}
+
+/// get the index of each column in the schema (an error if a column is missing)
+fn get_column_indices<Key>(schema : &arrow::datatypes::Schema, columns : &[(Key, &str)]) -> Result<Vec<usize>, PlotHelperError> {
+    let mut indices = columns.iter().map(|(_, column)| schema.index_of(column)).collect::<Result<Vec<_>, _>>()?;
+    indices.sort();
+    indices.dedup();
+    Ok(indices)
+}
+
+/// get the integer type of the values of a temporal type, and the number of seconds of a unit
+fn get_temporal_unit(data_type : &DataType) -> Option<(DataType, f64)> {
+    match data_type {
+        DataType::Timestamp(unit, _) => Some((DataType::Int64, match unit {
+            TimeUnit::Second => 1.0,
+            TimeUnit::Millisecond => 1e-3,
+            TimeUnit::Microsecond => 1e-6,
+            TimeUnit::Nanosecond => 1e-9,
+        })),
+        DataType::Date32 => Some((DataType::Int32, 86400.0)),
+        DataType::Date64 => Some((DataType::Int64, 1e-3)),
+        _ => None,
+    }
+}
+
+/// add the values of the array to the serie, as f64 (NaN for the null values)
+/// NOTE : the values of a float64 array without null are copied in one go
+fn extend_with_numeric_values(serie : &mut Vec<f64>, array : &ArrayRef) {
+    if let (DataType::Float64, 0) = (array.data_type(), array.null_count()) {
+        serie.extend_from_slice(array.as_primitive::<Float64Type>().values());
+        return;
+    }
+    let (values, seconds_per_unit) = match get_temporal_unit(array.data_type()) {
+        Some((integer_type, seconds_per_unit)) => (cast(array, &integer_type).and_then(|values| cast(&values, &DataType::Float64)), seconds_per_unit),
+        None => (cast(array, &DataType::Float64), 1.0),
+    };
+    match values {
+        Ok(values) => serie.extend(values.as_primitive::<Float64Type>().iter().map(|value| value.map(|v| v * seconds_per_unit).unwrap_or(f64::NAN))),
+        // checked at the creation
+        Err(_) => serie.extend((0..array.len()).map(|_| f64::NAN)),
+    }
+}
@@ -1,3 +1,5 @@
+use crate::error::PlotHelperError;
+
 use super::key::SerieKey;
 
 
@@ -16,6 +18,13 @@ pub trait Sample<Key>
     /// Get the value of data (as f32), associated to the given key
     fn get_numeric_value(&self, key : &Key) -> f32;
 
+    /// Get the value of data (as f64), associated to the given key
+    /// NOTE : the value used by the stats and the plots, the f32 value by default. A f32 is exact only up to 2^24 :
+    /// the samples storing larger values (nanoseconds, bytes...) override it, and give the rounded value in get_numeric_value
+    fn get_numeric_value_f64(&self, key : &Key) -> f64 {
+        self.get_numeric_value(key) as f64
+    }
+
     /// Get the value of data (as string), associated to the given key
     fn get_string_value(&self, key : &Key) -> String;
 
@@ -29,7 +38,7 @@ where
     KeyType : SerieKey
 {
     /// Load a sample from a file path
-    fn new_from_file_path(file_path : &str) -> Result<Self, Box<dyn std::error::Error>>;
+    fn new_from_file_path(file_path : &str) -> Result<Self, PlotHelperError>;
 }
 
 /// Define a constructeur for a multiple sample per file
@@ -39,5 +48,5 @@ where
     KeyType : SerieKey
 {
     /// Load a sample from a file path
-    fn new_from_file_path(file_path : &str) -> Result<Vec<Self>, Box<dyn std::error::Error>>;
+    fn new_from_file_path(file_path : &str) -> Result<Vec<Self>, PlotHelperError>;
 }
\ No newline at end of file
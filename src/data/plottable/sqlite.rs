@@ -0,0 +1,335 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use rusqlite::types::{Value, ValueRef};
+use rusqlite::{params_from_iter, Connection, OpenFlags};
+
+use crate::data::datetime::Timestamp;
+use crate::data::filtering::{Filter, FilterCondition};
+use crate::error::PlotHelperError;
+
+use super::key::SerieKey;
+use super::Plottable;
+
+/// the rows read from a sqlite database
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SqlSource {
+    /// all the rows of a table
+    Table(String),
+    /// the rows returned by a select query
+    Query(String),
+}
+
+impl SqlSource {
+    /// get the source as written after the FROM of a query
+    fn to_sql(&self) -> String {
+        match self {
+            SqlSource::Table(table) => quote_identifier(table),
+            SqlSource::Query(query) => format!("({})", query.trim().trim_end_matches(';')),
+        }
+    }
+}
+
+/// plottable data read from a sqlite table or query, each key is served by a column
+/// NOTE : the columns are read once, at the creation
+/// NOTE : a null value is NaN in a numeric serie, and empty in a string serie.
+/// A text value of a numeric key is parsed (as a datetime for a datetime key, see Timestamp)
+#[derive(Debug, Clone)]
+pub struct SqlitePlottable<Key>
+where
+    Key : SerieKey,
+{
+    numeric_series : HashMap<Key, Vec<f64>>,
+    string_series : HashMap<Key, Vec<String>>,
+    nb_of_samples : usize,
+}
+
+impl<Key> SqlitePlottable<Key>
+where
+    Key : SerieKey,
+{
+    /// read the rows of a table of the database file, columns gives the name of the column of each key
+    /// NOTE : see from_connection for the filters
+    pub fn from_table(path : &str, table : &str, columns : &[(Key, &str)], filters : &Option<Vec<&Filter<Key>>>) -> Result<Self, PlotHelperError> {
+        let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        Self::from_connection(&connection, &SqlSource::Table(table.to_string()), columns, filters)
+    }
+
+    /// read the rows returned by a select query on the database file, columns gives the name of the column of each key
+    /// NOTE : see from_connection for the filters
+    pub fn from_query(path : &str, query : &str, columns : &[(Key, &str)], filters : &Option<Vec<&Filter<Key>>>) -> Result<Self, PlotHelperError> {
+        let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        Self::from_connection(&connection, &SqlSource::Query(query.to_string()), columns, filters)
+    }
+
+    /// read the rows of the source, columns gives the name of the column of each key
+    /// NOTE : the filters with a condition (see Filter::new_range, Filter::new_one_of) are pushed down in the WHERE clause
+    /// if the declared type of their column gives the same values as the ones read (see can_push_down),
+    /// the other ones are evaluated on the rows read. The key of each filter must have a column
+    pub fn from_connection(
+        connection : &Connection,
+        source : &SqlSource,
+        columns : &[(Key, &str)],
+        filters : &Option<Vec<&Filter<Key>>>,
+    ) -> Result<Self, PlotHelperError> {
+        let filters = filters.clone().unwrap_or_default();
+        let affinities = get_column_affinities(connection, source, columns)?;
+        let SelectQuery { query, parameters, closure_filters } = get_select_query(source, columns, &affinities, &filters)?;
+
+        let mut series : Vec<Serie> = columns.iter().map(|(key, _)| if key.is_numeric() { Serie::Numeric(Vec::new()) } else { Serie::String(Vec::new()) }).collect();
+        let mut statement = connection.prepare(&query)?;
+        let mut rows = statement.query(params_from_iter(parameters))?;
+        while let Some(row) = rows.next()? {
+            for (index, ((key, _), serie)) in columns.iter().zip(series.iter_mut()).enumerate() {
+                serie.push(key, row.get_ref(index)?)?;
+            }
+        }
+
+        let mut plottable = Self {
+            numeric_series : HashMap::new(),
+            string_series : HashMap::new(),
+            nb_of_samples : 0,
+        };
+        for ((key, _), serie) in columns.iter().zip(series) {
+            plottable.nb_of_samples = serie.len();
+            match serie {
+                Serie::Numeric(values) => { plottable.numeric_series.insert(*key, values); },
+                Serie::String(values) => { plottable.string_series.insert(*key, values); },
+            }
+        }
+
+        // the filters not pushed down
+        if !closure_filters.is_empty() {
+            let mask = plottable.combine_filter(&Some(closure_filters), &None);
+            plottable.nb_of_samples = mask.count();
+            plottable.numeric_series.values_mut().for_each(|serie| *serie = mask.apply(serie));
+            plottable.string_series.values_mut().for_each(|serie| *serie = mask.apply(serie));
+        }
+        Ok(plottable)
+    }
+}
+
+impl<Key> Plottable<Key> for SqlitePlottable<Key>
+where
+    Key : SerieKey,
+{
+    fn get_number_of_samples(&self) -> usize {
+        self.nb_of_samples
+    }
+
+    /// NOTE : panic if the key has no column (as a sample without the value)
+    fn get_numeric_series(&self, key : &Key) -> Vec<f64> {
+        self.numeric_series.get(key)
+            .unwrap_or_else(|| panic!("the key {} has no numeric column in the sqlite data", key.get_display_name()))
+            .clone()
+    }
+
+    /// NOTE : panic if the key has no column (as a sample without the value)
+    fn get_string_series(&self, key : &Key) -> Vec<String> {
+        self.string_series.get(key)
+            .unwrap_or_else(|| panic!("the key {} has no string column in the sqlite data", key.get_display_name()))
+            .clone()
+    }
+}
+
+/// the values read from a column
+enum Serie {
+    Numeric(Vec<f64>),
+    String(Vec<String>),
+}
+
+impl Serie {
+    fn len(&self) -> usize {
+        match self {
+            Serie::Numeric(values) => values.len(),
+            Serie::String(values) => values.len(),
+        }
+    }
+
+    /// add the value of a row
+    fn push<Key>(&mut self, key : &Key, value : ValueRef) -> Result<(), PlotHelperError>
+    where
+        Key : SerieKey,
+    {
+        match (self, value) {
+            (Serie::Numeric(values), ValueRef::Null) => values.push(f64::NAN),
+            (Serie::Numeric(values), ValueRef::Integer(value)) => values.push(value as f64),
+            (Serie::Numeric(values), ValueRef::Real(value)) => values.push(value),
+            (Serie::Numeric(values), ValueRef::Text(text)) => {
+                let text = String::from_utf8_lossy(text);
+                let value = if key.is_datetime() {
+                    Timestamp::from_str(&text)?.to_value()
+                } else {
+                    text.trim().parse::<f64>()
+                        .map_err(|_| PlotHelperError::Parse(format!("the value {} of the key {} is not a number", text, key.get_display_name())))?
+                };
+                values.push(value);
+            },
+            (Serie::String(values), ValueRef::Null) => values.push(String::new()),
+            (Serie::String(values), ValueRef::Integer(value)) => values.push(value.to_string()),
+            (Serie::String(values), ValueRef::Real(value)) => values.push(value.to_string()),
+            (Serie::String(values), ValueRef::Text(text)) => values.push(String::from_utf8_lossy(text).to_string()),
+            (Serie::Numeric(_), ValueRef::Blob(_)) => return Err(PlotHelperError::expected_numeric(format!("{} (a blob column)", key.get_display_name()))),
+            (Serie::String(_), ValueRef::Blob(_)) => return Err(PlotHelperError::expected_string(format!("{} (a blob column)", key.get_display_name()))),
+        }
+        Ok(())
+    }
+}
+
+/// quote a table or a column name
+fn quote_identifier(identifier : &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+/// the type affinity of a column, given by its declared type (see the affinity rules of sqlite)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnAffinity {
+    Integer,
+    Text,
+    Blob,
+    Real,
+    Numeric,
+}
+
+impl ColumnAffinity {
+    fn from_declared_type(declared_type : &str) -> Self {
+        let declared_type = declared_type.to_uppercase();
+        if declared_type.contains("INT") {
+            ColumnAffinity::Integer
+        } else if ["CHAR", "CLOB", "TEXT"].iter().any(|name| declared_type.contains(name)) {
+            ColumnAffinity::Text
+        } else if declared_type.is_empty() || declared_type.contains("BLOB") {
+            ColumnAffinity::Blob
+        } else if ["REAL", "FLOA", "DOUB"].iter().any(|name| declared_type.contains(name)) {
+            ColumnAffinity::Real
+        } else {
+            ColumnAffinity::Numeric
+        }
+    }
+}
+
+/// get the affinity of the column of each key (None if the column has no declared type, e.g. an expression of a query)
+fn get_column_affinities<Key>(connection : &Connection, source : &SqlSource, columns : &[(Key, &str)]) -> Result<Vec<Option<ColumnAffinity>>, PlotHelperError>
+where
+    Key : SerieKey,
+{
+    let selected_columns = columns.iter().map(|(_, column)| quote_identifier(column)).collect::<Vec<_>>().join(", ");
+    let statement = connection.prepare(&format!("SELECT {} FROM {}", selected_columns, source.to_sql()))?;
+    Ok(statement.columns().iter().map(|column| column.decl_type().map(ColumnAffinity::from_declared_type)).collect())
+}
+
+/// true if the condition gives the same rows in the WHERE clause as on the values read (see Serie::push)
+/// NOTE : a range is compared to the numbers of an integer or real column (a text column would be compared as text, e.g. a datetime),
+/// the values are compared to the texts of a text column (an integer read as a string is not equal to its text in sqlite).
+/// An empty value is a null read as a string, it is not pushed down
+fn can_push_down(condition : &FilterCondition, affinity : Option<ColumnAffinity>) -> bool {
+    match (condition, affinity) {
+        (FilterCondition::Range(_, _), Some(ColumnAffinity::Integer | ColumnAffinity::Real)) => true,
+        (FilterCondition::OneOf(values), Some(ColumnAffinity::Text)) => values.iter().all(|value| !value.is_empty()),
+        _ => false,
+    }
+}
+
+/// a query selecting the columns of the keys from a source
+/// closure_filters : the filters not pushed down in the query, to evaluate on the rows read
+struct SelectQuery<'a, Key>
+where
+    Key : SerieKey,
+{
+    query : String,
+    parameters : Vec<Value>,
+    closure_filters : Vec<&'a Filter<Key>>,
+}
+
+/// get the query selecting the columns of the keys from the source
+/// affinities : the affinity of the column of each key (see get_column_affinities)
+/// NOTE : the filters with a condition are added in the WHERE clause if they can be pushed down (see can_push_down),
+/// an error if the key of a filter has no column
+fn get_select_query<'a, Key>(
+    source : &SqlSource,
+    columns : &[(Key, &str)],
+    affinities : &[Option<ColumnAffinity>],
+    filters : &[&'a Filter<Key>],
+) -> Result<SelectQuery<'a, Key>, PlotHelperError>
+where
+    Key : SerieKey,
+{
+    let selected_columns = columns.iter().map(|(_, column)| quote_identifier(column)).collect::<Vec<_>>().join(", ");
+    let mut query = format!("SELECT {} FROM {}", selected_columns, source.to_sql());
+
+    let mut conditions = Vec::new();
+    let mut parameters = Vec::new();
+    let mut closure_filters = Vec::new();
+    for filter in filters.iter() {
+        let Some(index) = columns.iter().position(|(key, _)| key == filter.get_key()) else {
+            return Err(PlotHelperError::InvalidParameter(format!("the key {} of the filter has no column", filter.get_key().get_display_name())));
+        };
+        let column = quote_identifier(columns[index].1);
+        match filter.get_condition() {
+            Some(condition) if can_push_down(condition, affinities.get(index).copied().flatten()) => match condition {
+                FilterCondition::Range(min, max) => {
+                    conditions.push(format!("{} BETWEEN ? AND ?", column));
+                    parameters.extend([Value::Real(*min), Value::Real(*max)]);
+                },
+                FilterCondition::OneOf(values) => {
+                    conditions.push(format!("{} IN ({})", column, vec!["?"; values.len()].join(", ")));
+                    parameters.extend(values.iter().map(|value| Value::Text(value.clone())));
+                },
+            },
+            _ => closure_filters.push(*filter),
+        }
+    }
+    if !conditions.is_empty() {
+        query = format!("{} WHERE {}", query, conditions.join(" AND "));
+    }
+    Ok(SelectQuery { query, parameters, closure_filters })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_derive::{Deserialize, Serialize};
+    use std::fmt::{Display, Formatter};
+    use crate::generate_plot_key;
+
+    generate_plot_key!(TestKey[
+        Tool { "tool", String },
+        Time { "time", Numeric }
+    ]);
+
+    #[test]
+    fn test_select_query() {
+        let columns = [(TestKey::Tool, "tool \"name\""), (TestKey::Time, "time")];
+        let range = Filter::new_range(TestKey::Time, 1.0, f64::INFINITY).unwrap();
+        let one_of = Filter::new_one_of(TestKey::Tool, vec!["a".to_string(), "b".to_string()]).unwrap();
+        let closure = Filter::new_number(TestKey::Time, |time| time > 2.0).unwrap();
+
+        let affinities = [Some(ColumnAffinity::Text), Some(ColumnAffinity::Real)];
+        let SelectQuery { query, parameters, closure_filters } = get_select_query(&SqlSource::Table("runs".to_string()), &columns, &affinities, &[&range, &one_of, &closure]).unwrap();
+        assert_eq!(query, "SELECT \"tool \"\"name\"\"\", \"time\" FROM \"runs\" WHERE \"time\" BETWEEN ? AND ? AND \"tool \"\"name\"\"\" IN (?, ?)");
+        assert_eq!(parameters.len(), 4);
+        assert_eq!(closure_filters.len(), 1);
+
+        // the conditions on a column of another type are evaluated on the rows read
+        let affinities = [Some(ColumnAffinity::Integer), Some(ColumnAffinity::Text)];
+        let SelectQuery { query, parameters, closure_filters } = get_select_query(&SqlSource::Table("runs".to_string()), &columns, &affinities, &[&range, &one_of]).unwrap();
+        assert_eq!(query, "SELECT \"tool \"\"name\"\"\", \"time\" FROM \"runs\"");
+        assert!(parameters.is_empty());
+        assert_eq!(closure_filters.len(), 2);
+
+        let query = get_select_query(&SqlSource::Query("SELECT * FROM runs;".to_string()), &columns[1..], &[None], &[]).unwrap().query;
+        assert_eq!(query, "SELECT \"time\" FROM (SELECT * FROM runs)");
+        assert!(get_select_query(&SqlSource::Query("SELECT * FROM runs".to_string()), &columns[1..], &[None], &[&one_of]).is_err());
+    }
+
+    #[test]
+    fn test_column_affinity() {
+        let affinities = ["INTEGER", "BIGINT", "VARCHAR(10)", "text", "", "BLOB", "DOUBLE PRECISION", "REAL", "DATETIME", "NUMERIC"]
+            .map(ColumnAffinity::from_declared_type);
+        assert_eq!(affinities, [
+            ColumnAffinity::Integer, ColumnAffinity::Integer, ColumnAffinity::Text, ColumnAffinity::Text, ColumnAffinity::Blob,
+            ColumnAffinity::Blob, ColumnAffinity::Real, ColumnAffinity::Real, ColumnAffinity::Numeric, ColumnAffinity::Numeric,
+        ]);
+    }
+}
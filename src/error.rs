@@ -0,0 +1,154 @@
+use std::fmt::{Display, Formatter};
+
+use plotters::drawing::DrawingAreaErrorKind;
+
+/// the errors returned by the crate
+#[derive(Debug)]
+pub enum PlotHelperError {
+    /// an error while reading or writing a file
+    Io(std::io::Error),
+    /// a content which can't be parsed (csv, json, path...)
+    Parse(String),
+    /// a key used where a key of another type is expected (a string key to compute a metric for example)
+    KeyTypeMismatch {
+        key : String,
+        expected : &'static str,
+    },
+    /// the figure doesn't fit the layout (more subplots than cells for example), or the sizes of a matrix don't match
+    LayoutMismatch(String),
+    /// there is not enough data to compute or to draw the result
+    EmptyData(String),
+    /// a parameter out of its domain (a confidence level greater than 1 for example)
+    InvalidParameter(String),
+    /// an error of the drawing backend while rendering a figure
+    Rendering(String),
+}
+
+impl PlotHelperError {
+    /// the error of a key which should be numeric
+    pub fn expected_numeric(key : String) -> Self {
+        PlotHelperError::KeyTypeMismatch {
+            key,
+            expected : "numeric",
+        }
+    }
+
+    /// the error of a key which should be a string
+    pub fn expected_string(key : String) -> Self {
+        PlotHelperError::KeyTypeMismatch {
+            key,
+            expected : "string",
+        }
+    }
+}
+
+impl Display for PlotHelperError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlotHelperError::Io(error) => write!(f, "io error : {}", error),
+            PlotHelperError::Parse(message) => write!(f, "parse error : {}", message),
+            PlotHelperError::KeyTypeMismatch { key, expected } => write!(f, "the key {} must be {}", key, expected),
+            PlotHelperError::LayoutMismatch(message) => write!(f, "layout mismatch : {}", message),
+            PlotHelperError::EmptyData(message) => write!(f, "empty data : {}", message),
+            PlotHelperError::InvalidParameter(message) => write!(f, "invalid parameter : {}", message),
+            PlotHelperError::Rendering(message) => write!(f, "rendering error : {}", message),
+        }
+    }
+}
+
+impl std::error::Error for PlotHelperError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PlotHelperError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for PlotHelperError {
+    fn from(error : std::io::Error) -> Self {
+        PlotHelperError::Io(error)
+    }
+}
+
+impl From<csv::Error> for PlotHelperError {
+    fn from(error : csv::Error) -> Self {
+        if error.is_io_error() {
+            match error.into_kind() {
+                csv::ErrorKind::Io(error) => PlotHelperError::Io(error),
+                kind => PlotHelperError::Parse(format!("{:?}", kind)),
+            }
+        } else {
+            PlotHelperError::Parse(error.to_string())
+        }
+    }
+}
+
+impl From<serde_json::Error> for PlotHelperError {
+    fn from(error : serde_json::Error) -> Self {
+        match error.classify() {
+            serde_json::error::Category::Io => PlotHelperError::Io(error.into()),
+            _ => PlotHelperError::Parse(error.to_string()),
+        }
+    }
+}
+
+impl From<std::num::ParseFloatError> for PlotHelperError {
+    fn from(error : std::num::ParseFloatError) -> Self {
+        PlotHelperError::Parse(error.to_string())
+    }
+}
+
+#[cfg(feature = "arrow")]
+impl From<arrow::error::ArrowError> for PlotHelperError {
+    fn from(error : arrow::error::ArrowError) -> Self {
+        match error {
+            arrow::error::ArrowError::IoError(_, error) => PlotHelperError::Io(error),
+            error => PlotHelperError::Parse(error.to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "arrow")]
+impl From<parquet::errors::ParquetError> for PlotHelperError {
+    fn from(error : parquet::errors::ParquetError) -> Self {
+        PlotHelperError::Parse(error.to_string())
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for PlotHelperError {
+    fn from(error : rusqlite::Error) -> Self {
+        PlotHelperError::Parse(error.to_string())
+    }
+}
+
+impl<E> From<DrawingAreaErrorKind<E>> for PlotHelperError
+where
+    E : std::error::Error + Send + Sync,
+{
+    fn from(error : DrawingAreaErrorKind<E>) -> Self {
+        PlotHelperError::Rendering(error.to_string())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_conversions() {
+        let error : PlotHelperError = std::io::Error::new(std::io::ErrorKind::NotFound, "missing").into();
+        assert!(matches!(error, PlotHelperError::Io(_)));
+        assert!(std::error::Error::source(&error).is_some());
+
+        let error : PlotHelperError = serde_json::from_str::<Vec<u8>>("[1,").unwrap_err().into();
+        assert!(matches!(error, PlotHelperError::Parse(_)));
+
+        let error : PlotHelperError = "1.5s".parse::<f64>().unwrap_err().into();
+        assert!(matches!(error, PlotHelperError::Parse(_)));
+
+        assert_eq!(PlotHelperError::expected_numeric("tool".to_string()).to_string(), "the key tool must be numeric");
+    }
+}
@@ -8,4 +8,5 @@ pub mod plotter;
 pub mod params;
 pub mod static_html;
 pub mod wrapper;
+pub mod error;
 mod utils;
\ No newline at end of file
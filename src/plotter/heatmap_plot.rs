@@ -0,0 +1,116 @@
+use std::path::Path;
+
+use plotters::backend::BitMapBackend;
+use plotters::chart::ChartBuilder;
+use plotters::drawing::IntoDrawingArea;
+use plotters::element::{Rectangle, Text};
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use plotters::style::{Color, IntoFont, RGBColor, BLACK, WHITE};
+
+use crate::error::PlotHelperError;
+use crate::data::matrix::LabelledMatrix;
+
+use super::style::FigureStyle;
+use super::utils::{draw_color_bar, gradient_color_normalized};
+
+
+/// plot the matrix as a heatmap : one colored cell per value, annotated with the value (2 decimals)
+/// the first row of the matrix is drawn at the top, and the color scale is drawn at the right
+/// NOTE : the NaN values are drawn in grey, and an empty matrix is an error (EmptyData) since there is no cell to lay out
+/// NOTE : the look of the figure is given by the style (FigureStyle::default() for the default one)
+pub fn heatmap_plot(
+    save_path : &str,
+    matrix : &LabelledMatrix,
+    title : Option<&str>,
+    style : &FigureStyle,
+) -> Result<(), PlotHelperError> {
+    let (nb_of_rows, nb_of_columns) = (matrix.get_row_labels().len(), matrix.get_column_labels().len());
+    if nb_of_rows == 0 || nb_of_columns == 0 {
+        return Err(PlotHelperError::EmptyData("the matrix to plot is empty".to_string()));
+    }
+    let range = matrix.get_range();
+
+    // the color scale always take the place of the legend
+    let global_size = style.get_figure_size((1, 1), title.is_some());
+    let global_size = (global_size.0.max(style.scaled(style.subplot_size.0 + style.legend_width)), global_size.1);
+    let root_drawing_area = BitMapBackend::new(Path::new(save_path), global_size).into_drawing_area();
+    root_drawing_area.fill(&style.background)?;
+    let figure_drawing_area = match title {
+        Some(title) => {
+            let (title_drawing_area, figure_drawing_area) = root_drawing_area.split_vertically(style.get_title_height());
+            let title_font = (style.font_family.as_str(), style.scaled(style.title_font_size)).into_font()
+                .color(&style.foreground)
+                .pos(Pos::new(HPos::Center, VPos::Center));
+            let (width, height) = title_drawing_area.dim_in_pixel();
+            title_drawing_area.draw(&Text::new(title.to_string(), (width as i32 / 2, height as i32 / 2), title_font))?;
+            figure_drawing_area
+        },
+        None => root_drawing_area.clone(),
+    };
+    let (chart_drawing_area, color_bar_drawing_area) =
+        figure_drawing_area.split_horizontally(global_size.0 - style.scaled(style.legend_width));
+
+    // one unit per cell
+    let mut chart = ChartBuilder::on(&chart_drawing_area)
+        .margin(style.scaled(style.margin))
+        .x_label_area_size(style.scaled(style.x_label_area_size))
+        .y_label_area_size(style.scaled(style.y_label_area_size))
+        .build_cartesian_2d(0.0..nb_of_columns as f64, 0.0..nb_of_rows as f64)?;
+    chart.configure_mesh()
+        .disable_mesh()
+        .x_labels(0)
+        .y_labels(0)
+        .axis_style(style.foreground)
+        .draw()?;
+
+    // the labels are drawn at the center of the cells (the mesh can't place them there), the rows are drawn from the top
+    let label_font = (style.font_family.as_str(), style.scaled(style.tick_font_size)).into_font().color(&style.foreground);
+    let label_offset = style.scaled(style.margin) as i32;
+    for (column, label) in matrix.get_column_labels().iter().enumerate() {
+        let (x, y) = chart.backend_coord(&(column as f64 + 0.5, 0.0));
+        root_drawing_area.draw(&Text::new(label.clone(), (x, y + label_offset), label_font.pos(Pos::new(HPos::Center, VPos::Top))))?;
+    }
+    for (row, label) in matrix.get_row_labels().iter().enumerate() {
+        let (x, y) = chart.backend_coord(&(0.0, (nb_of_rows - row) as f64 - 0.5));
+        root_drawing_area.draw(&Text::new(label.clone(), (x - label_offset, y), label_font.pos(Pos::new(HPos::Right, VPos::Center))))?;
+    }
+
+    let annotation_font = (style.font_family.as_str(), style.scaled(style.tick_font_size)).into_font();
+    for (row, values) in matrix.get_values().iter().enumerate() {
+        let y = (nb_of_rows - 1 - row) as f64;
+        for (column, value) in values.iter().enumerate() {
+            let x = column as f64;
+            let color = gradient_color_normalized(*value, range.start, range.end);
+            chart.draw_series(std::iter::once(Rectangle::new([(x, y), (x + 1.0, y + 1.0)], color.filled())))?;
+            chart.draw_series(std::iter::once(Text::new(
+                format!("{:.2}", value),
+                (x + 0.5, y + 0.5),
+                annotation_font.clone().color(&get_annotation_color(&color)).pos(Pos::new(HPos::Center, VPos::Center)),
+            )))?;
+        }
+    }
+
+    let bar_left = color_bar_drawing_area.dim_in_pixel().0 as i32 / 8;
+    draw_color_bar(&color_bar_drawing_area, matrix.get_name().clone(), bar_left, |ratio| range.start + ratio * (range.end - range.start), style)?;
+
+    root_drawing_area.present()?;
+    Ok(())
+}
+
+/// get a readable color for a text on the given background (black on light colors, white on dark ones)
+fn get_annotation_color(background : &RGBColor) -> RGBColor {
+    let luminance = 0.299 * background.0 as f64 + 0.587 * background.1 as f64 + 0.114 * background.2 as f64;
+    if luminance > 140.0 { BLACK } else { WHITE }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_annotation_color() {
+        assert_eq!(get_annotation_color(&RGBColor(253, 231, 37)), BLACK);
+        assert_eq!(get_annotation_color(&RGBColor(68, 1, 84)), WHITE);
+    }
+}
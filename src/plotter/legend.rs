@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+
+use plotters::style::RGBColor;
+
+use crate::data::legend_values::{ColorScale, LegendKeys};
+use crate::data::plot_data::{parse_legend_number, LegendOrder};
+use crate::data::plottable::key::SerieKey;
+
+use super::style::FigureStyle;
+use super::utils::{gradient_color_normalized, palette_color};
+
+
+/// a fixed association between a legend and a color
+/// NOTE : share the same instance between the figures of a report to always get the same color for a legend
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LegendColors {
+    colors : HashMap<String, RGBColor>,
+}
+
+impl LegendColors {
+    pub fn new() -> Self {
+        Self {
+            colors : HashMap::new(),
+        }
+    }
+
+    /// associate each legend to a color of the palette, in the given order
+    pub fn from_legends(legends : &[String]) -> Self {
+        let mut colors = Self::new();
+        for (index, legend) in legends.iter().enumerate() {
+            colors.set(legend.clone(), palette_color(index));
+        }
+        colors
+    }
+
+    pub fn set(&mut self, legend : String, color : RGBColor) -> &mut Self {
+        self.colors.insert(legend, color);
+        self
+    }
+
+    pub fn get(&self, legend : &str) -> Option<&RGBColor> {
+        self.colors.get(legend)
+    }
+
+    /// true if a legend is fixed to this color
+    pub fn uses_color(&self, color : &RGBColor) -> bool {
+        self.colors.values().any(|fixed_color| fixed_color == color)
+    }
+
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+}
+
+
+/// the resolved drawing style of a legend
+/// label : the full (composite) legend, as given to the PlotSeries
+/// color_label : the part of the label giving the color
+/// style_label : the part of the label giving the style (marker shape or line dash), if styled
+#[derive(Debug, Clone, PartialEq)]
+pub struct LegendEntry {
+    pub label : String,
+    pub color_label : String,
+    pub color : RGBColor,
+    pub style_label : Option<String>,
+    pub style_index : usize,
+}
+
+/// define how the legend of a figure is built
+/// legend_keys : the keys separating the data in series, their order, style and color scale (see LegendKeys)
+/// colors : the fixed colors, the legends without a fixed color take the next colors of the palette not fixed to a legend
+#[derive(Debug, Clone, PartialEq)]
+pub struct LegendConfig<Key>
+where
+    Key : SerieKey,
+{
+    pub legend_keys : LegendKeys<Key>,
+    pub colors : LegendColors,
+}
+
+impl<Key> LegendConfig<Key>
+where
+    Key : SerieKey,
+{
+    /// create a legend config, with the numeric order for a single numeric key and the alphabetical order otherwise
+    pub fn new(keys : Vec<Key>) -> Self {
+        Self {
+            legend_keys : LegendKeys::new(keys),
+            colors : LegendColors::new(),
+        }
+    }
+
+    pub fn with_order(mut self, order : LegendOrder) -> Self {
+        self.legend_keys = self.legend_keys.with_order(order);
+        self
+    }
+
+    pub fn with_colors(mut self, colors : LegendColors) -> Self {
+        self.colors = colors;
+        self
+    }
+
+    pub fn with_second_key_as_style(mut self, second_key_as_style : bool) -> Self {
+        self.legend_keys = self.legend_keys.with_second_key_as_style(second_key_as_style);
+        self
+    }
+
+    pub fn with_color_scale(mut self, color_scale : ColorScale) -> Self {
+        self.legend_keys = self.legend_keys.with_color_scale(color_scale);
+        self
+    }
+
+    /// get the keys of the legend (the part used by the data and the stats)
+    pub fn get_legend_keys(&self) -> &LegendKeys<Key> {
+        &self.legend_keys
+    }
+
+    /// sort the unique given legends, and associate each of them to a color (and a style)
+    /// legends : the values of the keys of each legend (see get_legend_key_values)
+    /// NOTE : the colors are taken in the default palette (see get_legend_entries_with_style)
+    pub fn get_legend_entries(&self, legends : &[Vec<String>]) -> Vec<LegendEntry> {
+        self.get_legend_entries_with_style(legends, &FigureStyle::default())
+    }
+
+    /// same as get_legend_entries, with the colors taken in the palette of the given style
+    pub fn get_legend_entries_with_style(&self, legends : &[Vec<String>], style : &FigureStyle) -> Vec<LegendEntry> {
+        let mut unique_legends : Vec<&Vec<String>> = Vec::new();
+        for legend in legends.iter() {
+            if !unique_legends.contains(&legend) {
+                unique_legends.push(legend);
+            }
+        }
+        let splitted = unique_legends.iter().map(|legend| {
+            let (label, color_label, style_label) = self.legend_keys.split_key_values(legend);
+            (label, (color_label, style_label))
+        }).collect::<Vec<_>>();
+
+        // the color dimension, in the legend order
+        let mut color_labels : Vec<String> = Vec::new();
+        for (_, (color_label, _)) in splitted.iter() {
+            if !color_labels.contains(color_label) {
+                color_labels.push(color_label.clone());
+            }
+        }
+        self.legend_keys.order.sort(&mut color_labels);
+        let gradient_range = if self.legend_keys.is_gradient() { get_gradient_range(&color_labels) } else { None };
+        // the colors of the palette which are not fixed to a legend (the whole palette if they are all fixed)
+        let mut free_colors = style.palette.iter().filter(|color| !self.colors.uses_color(color)).copied().collect::<Vec<RGBColor>>();
+        if free_colors.is_empty() {
+            free_colors = (0..style.palette.len().max(1)).map(|index| style.get_palette_color(index)).collect();
+        }
+        let mut palette_index = 0;
+        let colors = color_labels.iter().map(|color_label| {
+            match (self.colors.get(color_label), gradient_range, parse_legend_number(color_label)) {
+                (Some(color), _, _) => *color,
+                (None, Some((min, max)), Some(value)) => gradient_color_normalized(value, min, max),
+                _ => {
+                    palette_index += 1;
+                    free_colors[(palette_index - 1) % free_colors.len()]
+                }
+            }
+        }).collect::<Vec<RGBColor>>();
+
+        // the style dimension, in the natural order of the second key
+        let mut style_labels : Vec<String> = Vec::new();
+        for (_, (_, style_label)) in splitted.iter() {
+            if let Some(style_label) = style_label {
+                if !style_labels.contains(style_label) {
+                    style_labels.push(style_label.clone());
+                }
+            }
+        }
+        let keys = &self.legend_keys.keys;
+        if keys.len() == 2 && keys[1].is_numeric() {
+            LegendOrder::Numeric.sort(&mut style_labels);
+        } else {
+            LegendOrder::Lexicographic.sort(&mut style_labels);
+        }
+
+        let mut entries = splitted.into_iter().map(|(label, (color_label, style_label))| {
+            let color_index = color_labels.iter().position(|l| *l == color_label).unwrap();
+            let style_index = style_label.as_ref()
+                .map(|style_label| style_labels.iter().position(|l| l == style_label).unwrap())
+                .unwrap_or(0);
+            (color_index, LegendEntry {
+                label,
+                color_label,
+                color : colors[color_index],
+                style_label,
+                style_index,
+            })
+        }).collect::<Vec<_>>();
+        entries.sort_by_key(|(color_index, entry)| (*color_index, entry.style_index));
+        entries.into_iter().map(|(_, entry)| entry).collect()
+    }
+}
+
+/// get the (min, max) of the numeric legends, None if there is no numeric legend
+pub(crate) fn get_gradient_range(legends : &[String]) -> Option<(f64, f64)> {
+    let values = legends.iter().filter_map(|legend| parse_legend_number(legend)).filter(|v| v.is_finite()).collect::<Vec<f64>>();
+    if values.is_empty() {
+        return None;
+    }
+    let min = values.iter().fold(f64::MAX, |min, v| min.min(*v));
+    let max = values.iter().fold(f64::MIN, |max, v| max.max(*v));
+    Some((min, max))
+}
+
+impl<Key> Default for LegendConfig<Key>
+where
+    Key : SerieKey,
+{
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_derive::{Deserialize, Serialize};
+    use std::fmt::{Display, Formatter};
+    use crate::generate_plot_key;
+
+    generate_plot_key!(TestKey[
+        Number { "number", Numeric },
+        Name { "name", String }
+    ]);
+
+    fn to_strings(legends : &[&str]) -> Vec<String> {
+        legends.iter().map(|l| l.to_string()).collect()
+    }
+
+    /// the values of the keys of each legend, for a single key
+    fn to_key_values(legends : &[&str]) -> Vec<Vec<String>> {
+        legends.iter().map(|l| vec![l.to_string()]).collect()
+    }
+
+    fn get_labels_and_colors(entries : &[LegendEntry]) -> Vec<(String, RGBColor)> {
+        entries.iter().map(|entry| (entry.label.clone(), entry.color)).collect()
+    }
+
+    #[test]
+    fn test_legend_colors_order() {
+        let legends = to_key_values(&["10", "9", "10", "1.5"]);
+        let entries = LegendConfig::new(vec![TestKey::Number]).get_legend_entries(&legends);
+        assert_eq!(get_labels_and_colors(&entries), vec![
+            ("1.5".to_string(), palette_color(0)),
+            ("9".to_string(), palette_color(1)),
+            ("10".to_string(), palette_color(2)),
+        ]);
+
+        let entries = LegendConfig::new(vec![TestKey::Name]).get_legend_entries(&legends);
+        assert_eq!(entries.iter().map(|e| e.label.as_str()).collect::<Vec<_>>(), vec!["1.5", "10", "9"]);
+    }
+
+    #[test]
+    fn test_legend_fixed_colors() {
+        let fixed = LegendColors::from_legends(&to_strings(&["b", "a"]));
+        let config = LegendConfig::new(vec![TestKey::Name])
+            .with_order(LegendOrder::Custom(to_strings(&["c"])))
+            .with_colors(fixed);
+        let entries = config.get_legend_entries(&to_key_values(&["a", "b", "c", "d"]));
+        // the free legends skip the fixed colors
+        assert_eq!(get_labels_and_colors(&entries), vec![
+            ("c".to_string(), palette_color(2)),
+            ("a".to_string(), palette_color(1)),
+            ("b".to_string(), palette_color(0)),
+            ("d".to_string(), palette_color(3)),
+        ]);
+        let colors = entries.iter().map(|entry| entry.color).collect::<Vec<_>>();
+        assert!(colors.iter().enumerate().all(|(index, color)| !colors[..index].contains(color)));
+    }
+
+    #[test]
+    fn test_legend_multiple_keys() {
+        let legends = [["y", "10"], ["x", "9"], ["x", "10"], ["y", "9"]].iter().map(|values| to_strings(values)).collect::<Vec<_>>();
+
+        // composite labels only
+        let config = LegendConfig::new(vec![TestKey::Name, TestKey::Number]);
+        assert_eq!(config.legend_keys.get_title(), Some("name / number".to_string()));
+        let entries = config.get_legend_entries(&legends);
+        assert_eq!(entries.iter().map(|e| e.label.as_str()).collect::<Vec<_>>(), vec!["x / 10", "x / 9", "y / 10", "y / 9"]);
+        assert_eq!(entries.iter().map(|e| e.color).collect::<Vec<_>>(), (0..4).map(palette_color).collect::<Vec<_>>());
+
+        // the color by the first key, the style by the second
+        let config = config.with_second_key_as_style(true);
+        assert_eq!(config.legend_keys.get_dimension_titles(), (Some("name".to_string()), Some("number".to_string())));
+        let entries = config.get_legend_entries(&legends);
+        let resolved = entries.iter()
+            .map(|e| (e.label.as_str(), e.color, e.style_label.clone().unwrap(), e.style_index))
+            .collect::<Vec<_>>();
+        assert_eq!(resolved, vec![
+            ("x / 9", palette_color(0), "9".to_string(), 0),
+            ("x / 10", palette_color(0), "10".to_string(), 1),
+            ("y / 9", palette_color(1), "9".to_string(), 0),
+            ("y / 10", palette_color(1), "10".to_string(), 1),
+        ]);
+
+        // the values containing the separator are not split
+        let legends = vec![to_strings(&["a / b", "1"]), to_strings(&["c", "2 / 3"])];
+        let entries = config.get_legend_entries(&legends);
+        let resolved = entries.iter().map(|e| (e.color_label.as_str(), e.style_label.clone().unwrap())).collect::<Vec<_>>();
+        assert_eq!(resolved, vec![("a / b", "1".to_string()), ("c", "2 / 3".to_string())]);
+    }
+
+    #[test]
+    fn test_legend_gradient() {
+        let legends = to_key_values(&["10", "0", "5"]);
+        let config = LegendConfig::new(vec![TestKey::Number]).with_color_scale(ColorScale::Gradient);
+        assert!(config.legend_keys.is_gradient());
+        let entries = config.get_legend_entries(&legends);
+        assert_eq!(get_labels_and_colors(&entries), vec![
+            ("0".to_string(), gradient_color_normalized(0.0, 0.0, 10.0)),
+            ("5".to_string(), gradient_color_normalized(5.0, 0.0, 10.0)),
+            ("10".to_string(), gradient_color_normalized(10.0, 0.0, 10.0)),
+        ]);
+
+        // not numeric : fallback on the palette
+        let config = LegendConfig::new(vec![TestKey::Name]).with_color_scale(ColorScale::Gradient);
+        assert!(!config.legend_keys.is_gradient());
+        assert_eq!(config.get_legend_entries(&legends)[0].color, palette_color(0));
+    }
+}
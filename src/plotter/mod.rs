@@ -1,8 +1,13 @@
 
 
 
+pub mod plot;
+pub mod subplot;
 pub mod scatter_plot;
 pub mod line_plot;
+pub mod heatmap_plot;
+pub mod legend;
+pub mod style;
 
 
-mod utils;
\ No newline at end of file
+pub(crate) mod utils;
\ No newline at end of file
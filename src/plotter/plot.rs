@@ -0,0 +1,460 @@
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::Path;
+
+use plotters::backend::BitMapBackend;
+use plotters::chart::ChartBuilder;
+use plotters::drawing::IntoDrawingArea;
+use plotters::element::{Circle, Rectangle, Text};
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use plotters::style::{Color, IntoFont};
+
+use crate::data::axis::AxesConfig;
+use crate::data::datetime::format_datetime_value;
+use crate::data::export::{ExportTable, ExportValue};
+use crate::data::legend_values::{get_legend_key_values, join_legend_key_values};
+use crate::data::plot_data::{DensityGrid, Layout, PlotSeries};
+use crate::data::plottable::key::SerieKey;
+use crate::data::plottable::Plottable;
+use crate::error::PlotHelperError;
+use crate::utils::axe_number_formater;
+use crate::stats::fitting::{fit_series, FitResult};
+use crate::stats::stats_serie::MetricName;
+
+use super::legend::{LegendConfig, LegendEntry};
+use super::style::{FigureStyle, LegendPosition};
+use super::subplot::{PlotKind, Subplot};
+use super::utils::{draw_color_bar, draw_line_serie, draw_marker_serie, gradient_color, write_legend, LegendSymbol};
+
+
+/// the data of a subplot, ready to be drawn
+struct SubplotData {
+    /// the data in the plot space of the axes (the bins of a histogram)
+    data : PlotSeries,
+    range_x : Range<f64>,
+    range_y : Range<f64>,
+    /// the width of the bins, for a histogram
+    bin_width : Option<f64>,
+    /// the fitted models of each legend (in the data space)
+    fits : Vec<(String, FitResult)>,
+    /// the number of points in each cell, for a density (the data is then empty)
+    density : Option<DensityGrid>,
+    /// the categories of a categorical x axis, the category i is drawn at x = i
+    x_categories : Option<Vec<String>>,
+    /// the data in the data space, not downsampled (the bins of a histogram), if it is exported
+    export_data : Option<PlotSeries>,
+}
+
+impl SubplotData {
+    /// true if there is nothing to draw (no point, only empty bins or empty cells)
+    fn is_empty(&self) -> bool {
+        match (&self.density, self.bin_width) {
+            (Some(density), _) => density.get_max_count() == 0,
+            (None, Some(_)) => self.data.iter().all(|(_, bins)| bins.iter().all(|(_, count)| *count == 0.0)),
+            (None, None) => self.data.is_empty(),
+        }
+    }
+}
+
+/// plot the given subplots in a figure, following the layout (row by row)
+/// NOTE : the legend (colors and styles) is shared by all the subplots, its symbol is the one of the first subplot kind
+/// NOTE : the cells of the layout without subplot are left blank, and the subplots without data (all filtered out for example)
+/// are drawn with their axes and a "no data" message
+/// NOTE : return an error if there are more subplots than cells in the layout
+/// NOTE : the look of the figure (sizes, fonts, colors...) is given by the style (FigureStyle::default() for the default one)
+/// NOTE : if the layout has a data export, the data of the subplots is saved next to the image (the bins for a histogram, the points
+/// before the downsampling otherwise), as a tidy table with the columns subplot (index), title, legend, x and y
+pub fn plot<Key>(
+    data : &dyn Plottable<Key>,
+    legend : &LegendConfig<Key>,
+    save_path : &str,
+    layout : &Layout,
+    subplots : &[Subplot<'_, Key>],
+    style : &FigureStyle,
+) -> Result<(), PlotHelperError>
+where
+    Key : SerieKey,
+{
+    if subplots.len() > layout.get_nb_of_subplots() {
+        return Err(PlotHelperError::LayoutMismatch(format!("the number of subplots ({}) is greater than the number of cells in the layout ({})", subplots.len(), layout.get_nb_of_subplots())));
+    }
+
+    // initialise the plotter
+    let image_path_o = Path::new(save_path);
+    // (w, h)
+    let global_size = style.get_figure_size((layout.width, layout.height), layout.title.is_some());
+
+    // global drawing
+    let root_drawing_area = BitMapBackend::new(image_path_o, global_size).into_drawing_area();
+    root_drawing_area.fill(&style.background)?;
+    // isolate the title area (if there is a title)
+    let figure_drawing_area = match &layout.title {
+        Some(title) => {
+            let (title_drawing_area, figure_drawing_area) = root_drawing_area.split_vertically(style.get_title_height());
+            let title_font = (style.font_family.as_str(), style.scaled(style.title_font_size)).into_font()
+                .color(&style.foreground)
+                .pos(Pos::new(HPos::Center, VPos::Center));
+            let (width, height) = title_drawing_area.dim_in_pixel();
+            title_drawing_area.draw(&Text::new(title.clone(), (width as i32 / 2, height as i32 / 2), title_font))?;
+            figure_drawing_area
+        },
+        None => root_drawing_area.clone(),
+    };
+    // isolate the label area (if the legend is outside)
+    let label_width = if style.legend_position == LegendPosition::Outside { style.scaled(style.legend_width) } else { 0 };
+    let (chart_drawing_area, label_drawing_area) =
+        figure_drawing_area.split_horizontally(global_size.0 - label_width);
+
+    // get the drawing area for each subplot (row, col)
+    let child_drawing_areas = chart_drawing_area.split_evenly(layout.get_plotter_layout());
+
+    // get the legends, and associate each legend to a color
+    let legend_key_values = get_legend_key_values(data, &legend.legend_keys);
+    let legend_entries = legend.get_legend_entries_with_style(&legend_key_values, style);
+    check_nb_of_styles(&legend_entries, legend, subplots)?;
+    let legends = legend_key_values.iter().map(|key_values| join_legend_key_values(key_values)).collect::<Vec<String>>();
+    // the legends drawn in at least one subplot
+    let mut drawn_legends : HashSet<String> = HashSet::new();
+
+    let subplots_data = get_subplots_data(data, &legends, layout, subplots)?;
+    let export_table = layout.data_export.map(|_| get_export_table(subplots, &subplots_data));
+
+    // plot each subplot
+    for ((subplot, subplot_data), root) in subplots.iter().zip(subplots_data).zip(child_drawing_areas.iter()) {
+        let axes = subplot.get_used_axes();
+        let is_empty = subplot_data.is_empty();
+
+        // a density has the color bar of its counts at its right
+        let (root, color_bar_area) = match &subplot_data.density {
+            Some(_) => {
+                let width = root.dim_in_pixel().0;
+                let color_bar_width = style.scaled(DENSITY_COLOR_BAR_WIDTH).min(width / 4);
+                let (chart_area, color_bar_area) = root.split_horizontally(width - color_bar_width);
+                (chart_area, Some(color_bar_area))
+            },
+            None => (root.clone(), None),
+        };
+
+        // define the chart
+        let caption_font = (style.font_family.as_str(), style.scaled(style.caption_font_size)).into_font().color(&style.foreground);
+        let mut chart = ChartBuilder::on(&root)
+            .caption(subplot.get_title().as_str(), caption_font)
+            .margin(style.scaled(style.margin))
+            .x_label_area_size(style.scaled(style.x_label_area_size))
+            .y_label_area_size(style.scaled(style.y_label_area_size))
+            .build_cartesian_2d(subplot_data.range_x.clone(), subplot_data.range_y.clone())?;
+        let (range_x, range_y) = (subplot_data.range_x, subplot_data.range_y);
+
+        // the labels show the values of the data, not of the plot space (the dates for a datetime serie)
+        let (x_datetime, y_datetime) = subplot.get_datetime_axes();
+        let x_span = axes.x.from_plot_value(range_x.end) - axes.x.from_plot_value(range_x.start);
+        let y_span = axes.y.from_plot_value(range_y.end) - axes.y.from_plot_value(range_y.start);
+        let x_formatter = |x : &f64| match &subplot_data.x_categories {
+            Some(categories) => format_category(categories, *x),
+            None => format_axis_value(axes.x.from_plot_value(*x), x_datetime, x_span),
+        };
+        let y_formatter = |y : &f64| format_axis_value(axes.y.from_plot_value(*y), y_datetime, y_span);
+        let mut mesh = chart.configure_mesh();
+        mesh.x_desc(subplot.get_x_label().as_str())
+            .y_desc(subplot.get_y_label().as_str())
+            .x_label_formatter(&x_formatter)
+            .y_label_formatter(&y_formatter)
+            .axis_style(style.foreground)
+            .bold_line_style(style.foreground.mix(0.2))
+            .light_line_style(style.foreground.mix(0.05))
+            .label_style((style.font_family.as_str(), style.scaled(style.tick_font_size)).into_font().color(&style.foreground))
+            .axis_desc_style((style.font_family.as_str(), style.scaled(style.label_font_size)).into_font().color(&style.foreground));
+        if let Some(categories) = &subplot_data.x_categories {
+            // a label per category
+            mesh.x_labels(categories.len().max(1));
+        }
+        if !style.show_grid {
+            mesh.disable_mesh();
+        }
+        mesh.draw()?;
+
+        if is_empty {
+            let plotting_area = chart.plotting_area().strip_coord_spec();
+            let (width, height) = plotting_area.dim_in_pixel();
+            let no_data_font = (style.font_family.as_str(), style.scaled(style.caption_font_size)).into_font()
+                .color(&style.foreground.mix(0.6))
+                .pos(Pos::new(HPos::Center, VPos::Center));
+            plotting_area.draw(&Text::new(NO_DATA_MESSAGE, (width as i32 / 2, height as i32 / 2), no_data_font))?;
+        }
+
+        // plot the density, the color intensity grows with the logarithm of the count to see the less dense cells
+        if let (Some(density), Some(color_bar_area)) = (&subplot_data.density, &color_bar_area) {
+            let (width, height) = density.get_cell_size();
+            let max_count = (density.get_max_count() as f64).ln_1p();
+            chart.draw_series(density.iter_cells().map(|((x, y), count)| {
+                let color = gradient_color((count as f64).ln_1p() / max_count);
+                Rectangle::new([(x, y), (x + width, y + height)], color.filled())
+            }))?;
+            // the count at each ratio of the gradient
+            draw_color_bar(color_bar_area, "count".to_string(), style.scaled(style.margin) as i32, |ratio| (ratio * max_count).exp_m1(), style)?;
+        }
+
+        // plot the data, in the legend order
+        for entry in legend_entries.iter() {
+            let Some(data_for_legend) = subplot_data.data.get_data().get(&entry.label) else {
+                continue;
+            };
+            drawn_legends.insert(entry.label.clone());
+            match (subplot.kind, subplot_data.bin_width) {
+                (PlotKind::Line, _) => draw_line_serie(&mut chart, data_for_legend, entry.style_index, style.scaled(style.line_width), entry.color)?,
+                (PlotKind::Scatter, _) => draw_marker_serie(&mut chart, data_for_legend, entry.style_index, style.scaled(style.marker_size) as i32, entry.color)?,
+                (PlotKind::Histogram(_), bin_width) => {
+                    let bin_width = bin_width.unwrap_or_default();
+                    let bars = data_for_legend.iter().filter(|(_, count)| *count > 0.0);
+                    chart.draw_series(bars.clone().map(|(start, count)| {
+                        Rectangle::new([(*start, 0.0), (*start + bin_width, *count)], entry.color.mix(0.4).filled())
+                    }))?;
+                    chart.draw_series(bars.map(|(start, count)| {
+                        Rectangle::new([(*start, 0.0), (*start + bin_width, *count)], entry.color.stroke_width(style.scaled(style.line_width)))
+                    }))?;
+                },
+                // already drawn, the data is empty
+                (PlotKind::Density(_), _) => (),
+            }
+
+            // draw the fitted models of the legend, with a different dash for each model
+            for (index, (_, fit)) in subplot_data.fits.iter().filter(|(legend, _)| *legend == entry.label).enumerate() {
+                let curve = get_fit_curve(fit, &axes, &range_x);
+                draw_line_serie(&mut chart, &curve, index, style.scaled(style.line_width + 1), entry.color)?;
+            }
+        }
+
+        // circle the highlighted points, at their position in the plot space
+        let highlight_style = style.highlight_color.stroke_width(style.scaled(style.line_width + 1));
+        let highlights = subplot.highlights.iter().filter_map(|(x, y)| {
+            let position = (axes.x.to_plot_value(*x)?, axes.y.to_plot_value(*y)?);
+            Some(Circle::new(position, style.scaled(3 * style.marker_size + 2), highlight_style))
+        });
+        chart.draw_series(highlights)?;
+
+        // write the annotations, at their position in the plot space
+        let annotation_font = (style.font_family.as_str(), style.scaled(style.label_font_size)).into_font().color(&style.foreground);
+        let annotations = subplot.annotations.iter().filter_map(|annotation| {
+            let position = (axes.x.to_plot_value(annotation.position.0)?, axes.y.to_plot_value(annotation.position.1)?);
+            Some(Text::new(annotation.text.clone(), position, annotation_font.clone()))
+        });
+        chart.draw_series(annotations)?;
+    }// end of for each subplot
+
+    let drawn_legend_entries : Vec<_> = legend_entries.into_iter()
+        .filter(|entry| drawn_legends.contains(&entry.label))
+        .collect();
+    let symbol = subplots.first().map(|subplot| subplot.kind.get_legend_symbol()).unwrap_or(LegendSymbol::Marker);
+    match (style.legend_position, child_drawing_areas.first()) {
+        (LegendPosition::Outside, _) => write_legend(&label_drawing_area, &drawn_legend_entries, legend, symbol, style)?,
+        // inside the plotting area of the first subplot
+        (_, Some(first_area)) => {
+            let plotting_area = first_area.margin(
+                style.scaled(style.margin + style.caption_font_size + style.margin),
+                style.scaled(style.margin + style.x_label_area_size),
+                style.scaled(style.margin + style.y_label_area_size),
+                style.scaled(style.margin),
+            );
+            write_legend(&plotting_area, &drawn_legend_entries, legend, symbol, style)?
+        },
+        (_, None) => (),
+    }
+
+    root_drawing_area.present()?;
+
+    if let (Some(format), Some(export_table)) = (layout.data_export, export_table) {
+        export_table.save_to_path(&image_path_o.with_extension(format.get_extension()), format)?;
+    }
+
+    Ok(())
+}
+
+/// check that each value of the style key has its own style (marker shape or line dash) in the line and scatter subplots
+/// NOTE : an error if there are more values than styles, the styles would be reused for different values
+fn check_nb_of_styles<Key>(legend_entries : &[LegendEntry], legend : &LegendConfig<Key>, subplots : &[Subplot<'_, Key>]) -> Result<(), PlotHelperError>
+where
+    Key : SerieKey,
+{
+    if !legend.legend_keys.is_styled() {
+        return Ok(());
+    }
+    let nb_of_style_values = legend_entries.iter().map(|entry| entry.style_index + 1).max().unwrap_or(0);
+    for subplot in subplots.iter().filter(|subplot| matches!(subplot.kind, PlotKind::Line | PlotKind::Scatter)) {
+        let symbol = subplot.kind.get_legend_symbol();
+        if let Some(nb_of_styles) = symbol.get_nb_of_styles().filter(|nb_of_styles| nb_of_style_values > *nb_of_styles) {
+            return Err(PlotHelperError::InvalidParameter(format!(
+                "the style key has {} values, but the subplot {} has only {} styles", nb_of_style_values, subplot.get_title(), nb_of_styles
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// get the data of all the subplots as a tidy table : a row (subplot index, title, legend, x, y) per point
+fn get_export_table<Key>(subplots : &[Subplot<'_, Key>], subplots_data : &[SubplotData]) -> ExportTable
+where
+    Key : SerieKey,
+{
+    let headers = ["subplot", "title", "legend", "x", "y"].iter().map(|header| header.to_string()).collect();
+    let mut table = ExportTable::new(headers);
+    for (index, (subplot, subplot_data)) in subplots.iter().zip(subplots_data.iter()).enumerate() {
+        let title = subplot.get_title();
+        let Some(export_data) = &subplot_data.export_data else {
+            continue;
+        };
+        for row in export_data.to_export_table().rows {
+            table.rows.push([ExportValue::from(index as f64), title.as_str().into()].into_iter().chain(row).collect());
+        }
+    }
+    table
+}
+
+/// format a value of the data for the label of an axis
+/// span : the span of the axis in the data space, to choose the precision of the dates
+fn format_axis_value(value : f64, is_datetime : bool, span : f64) -> String {
+    if is_datetime {
+        format_datetime_value(value, span)
+    } else {
+        axe_number_formater(&value)
+    }
+}
+
+/// get the label of a category at the position x (empty between the categories)
+fn format_category(categories : &[String], x : f64) -> String {
+    let index = x.round();
+    if (x - index).abs() > 1e-3 || index < 0.0 {
+        return String::new();
+    }
+    categories.get(index as usize).cloned().unwrap_or_default()
+}
+
+/// width of the area of the color bar at the right of a density subplot (at most a quarter of the subplot)
+const DENSITY_COLOR_BAR_WIDTH : u32 = 80;
+
+/// the message drawn in the subplots without data
+const NO_DATA_MESSAGE : &str = "no data";
+
+/// number of points used to draw a fitted model
+const FIT_CURVE_POINTS : usize = 200;
+
+/// get the points of the fitted model in the plot space, evenly spaced on the x axis of the plot
+fn get_fit_curve(fit : &FitResult, axes : &AxesConfig, range_x : &Range<f64>) -> Vec<(f64, f64)> {
+    (0..FIT_CURVE_POINTS).filter_map(|index| {
+        let plot_x = range_x.start + (range_x.end - range_x.start) * index as f64 / (FIT_CURVE_POINTS - 1) as f64;
+        let y = fit.predict(axes.x.from_plot_value(plot_x));
+        Some((plot_x, axes.y.to_plot_value(y)?))
+    }).collect()
+}
+
+/// get the data of each subplot in the plot space of its axes, with its range
+/// NOTE : the ranges are shared between the subplots if asked by the layout (the axes should then have the same scale)
+fn get_subplots_data<Key>(
+    data : &dyn Plottable<Key>,
+    legends : &Vec<String>,
+    layout : &Layout,
+    subplots : &[Subplot<'_, Key>],
+) -> Result<Vec<SubplotData>, PlotHelperError>
+where
+    Key : SerieKey,
+{
+    let mut subplots_data = Vec::with_capacity(subplots.len());
+    for subplot in subplots.iter() {
+        let axes = subplot.get_used_axes();
+        // group the data by legend, then put it in the plot space of the axes
+        // the time buckets are aggregated after the grouping, and the categories after being sorted (with all their values)
+        let categorical_x = subplot.is_categorical_x();
+        let aggregation_metric = if subplot.time_bucket.is_some() || categorical_x { None } else { subplot.aggregation_metric };
+        let grouped_data = data.aggregate(
+            &subplot.x_serie_key,
+            &subplot.y_serie_key,
+            &subplot.filters,
+            legends,
+            &subplot.remove_outliers,
+            aggregation_metric,
+        )?;
+        let grouped_data = match subplot.time_bucket {
+            Some(time_bucket) => grouped_data.apply_time_bucket_aggregator(subplot.aggregation_metric.unwrap_or(MetricName::Mean), time_bucket)?,
+            None if categorical_x => {
+                let sorted_data = grouped_data.sort_x_categories(&subplot.category_order);
+                match subplot.aggregation_metric {
+                    Some(metric) => sorted_data.apply_aggregator(metric)?,
+                    None => sorted_data,
+                }
+            },
+            None => grouped_data,
+        };
+        // a model can't be fitted on the indices of the categories
+        let fits = match subplot.kind {
+            PlotKind::Histogram(_) | PlotKind::Density(_) => Vec::new(),
+            _ if categorical_x => Vec::new(),
+            _ => subplot.fits.iter().flat_map(|model| fit_series(&grouped_data, *model)).collect(),
+        };
+        let x_categories = grouped_data.get_x_categories().cloned();
+        let export_data = layout.data_export.map(|_| grouped_data.clone());
+        let grouped_data = grouped_data.to_axes_space(&axes);
+        let (mut range_x, range_y) = grouped_data.get_range_in_axes(&axes);
+        // each category is in the middle of a segment of width 1
+        if let Some(categories) = &x_categories {
+            range_x = -0.5..categories.len().max(1) as f64 - 0.5;
+        }
+
+        subplots_data.push(match subplot.kind {
+            PlotKind::Histogram(nb_of_bins) => {
+                let histogram = grouped_data.to_histogram(&range_x, nb_of_bins);
+                // the bins start in the data space
+                let export_data = export_data.map(|_| {
+                    let mut bins = PlotSeries::new();
+                    for (legend, serie) in histogram.iter() {
+                        serie.iter().for_each(|(start, count)| bins.add(legend.clone(), (axes.x.from_plot_value(*start), *count)));
+                    }
+                    bins
+                });
+                let max_count = histogram.iter()
+                    .flat_map(|(_, bins)| bins.iter().map(|(_, count)| *count))
+                    .fold(1.0, f64::max);
+                SubplotData {
+                    data : histogram,
+                    range_y : 0.0..max_count * 1.05,
+                    bin_width : Some((range_x.end - range_x.start) / nb_of_bins.max(1) as f64),
+                    range_x,
+                    fits,
+                    density : None,
+                    x_categories,
+                    export_data,
+                }
+            },
+            _ => SubplotData {
+                data : grouped_data,
+                range_x,
+                range_y,
+                bin_width : None,
+                fits,
+                density : None,
+                x_categories,
+                export_data,
+            },
+        });
+    }
+
+    // share the ranges, then compress the points in them (or count them for a density)
+    let mut ranges : Vec<_> = subplots_data.iter()
+        .map(|subplot_data| (subplot_data.range_x.clone(), subplot_data.range_y.clone()))
+        .collect();
+    layout.share_ranges(&mut ranges);
+    for ((subplot, subplot_data), (range_x, range_y)) in subplots.iter().zip(subplots_data.iter_mut()).zip(ranges) {
+        match subplot.kind {
+            PlotKind::Histogram(_) => (),
+            PlotKind::Density(nb_of_bins) => {
+                subplot_data.density = Some(subplot_data.data.to_density(&range_x, &range_y, nb_of_bins));
+                subplot_data.data = PlotSeries::new();
+            },
+            _ => {
+                subplot_data.data.downsample_in(&range_x, &range_y, &subplot.downsampling);
+            },
+        }
+        subplot_data.range_x = range_x;
+        subplot_data.range_y = range_y;
+    }
+
+    Ok(subplots_data)
+}
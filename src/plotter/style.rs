@@ -0,0 +1,211 @@
+use plotters::style::{Palette, RGBColor, BLACK, RED, WHITE};
+
+use crate::params::{FIGURE_CAPTION_FONT_SIZE, LABEL_HORIZONTAL_SIZE, ONE_FIG_SIZE};
+
+use super::utils::CustomPalette;
+
+
+/// the palette of the dark theme : light colors, readable on a dark background (the default palette has black, navy and maroon)
+const DARK_PALETTE : [(u8, u8, u8); 16] = [
+    (255, 99, 132),
+    (75, 220, 110),
+    (255, 225, 25),
+    (80, 170, 255),
+    (255, 160, 70),
+    (190, 120, 255),
+    (70, 240, 240),
+    (240, 90, 230),
+    (210, 245, 60),
+    (250, 190, 190),
+    (0, 200, 180),
+    (230, 190, 255),
+    (220, 160, 90),
+    (255, 250, 200),
+    (170, 255, 195),
+    (200, 200, 200),
+];
+
+/// define where the legend is drawn
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LegendPosition {
+    /// in an area at the right of the figure
+    Outside,
+    /// in a corner of the first subplot
+    InsideUpperLeft,
+    InsideUpperRight,
+    InsideLowerLeft,
+    InsideLowerRight,
+}
+
+/// define the look of a figure
+/// NOTE : the sizes are in pixels, and are multiplied by the dpi_scale when drawing
+#[derive(Debug, Clone, PartialEq)]
+pub struct FigureStyle {
+    /// size of one subplot in the grid (width, height)
+    pub subplot_size : (u32, u32),
+    /// width of the legend area, when the legend is outside
+    pub legend_width : u32,
+    /// factor applied to all the sizes (2.0 to get a figure with twice the resolution)
+    pub dpi_scale : f32,
+    pub font_family : String,
+    /// font size of the figure title
+    pub title_font_size : u32,
+    /// font size of the subplot titles
+    pub caption_font_size : u32,
+    /// font size of the axis descriptions and of the legend
+    pub label_font_size : u32,
+    /// font size of the axis values
+    pub tick_font_size : u32,
+    /// margin around each subplot
+    pub margin : u32,
+    /// size of the area of the axis values and description (x axis at the bottom, y axis at the left)
+    pub x_label_area_size : u32,
+    pub y_label_area_size : u32,
+    /// the colors given to the legends (loop over the palette)
+    pub palette : Vec<RGBColor>,
+    pub line_width : u32,
+    pub marker_size : u32,
+    pub background : RGBColor,
+    /// color of the texts, axes and grid
+    pub foreground : RGBColor,
+    /// color of the circles around the highlighted points (see Subplot::with_highlights)
+    pub highlight_color : RGBColor,
+    pub show_grid : bool,
+    pub legend_position : LegendPosition,
+}
+
+impl Default for FigureStyle {
+    fn default() -> Self {
+        Self {
+            subplot_size : ONE_FIG_SIZE,
+            legend_width : LABEL_HORIZONTAL_SIZE,
+            dpi_scale : 1.0,
+            font_family : "sans-serif".to_string(),
+            title_font_size : FIGURE_CAPTION_FONT_SIZE + 10,
+            caption_font_size : FIGURE_CAPTION_FONT_SIZE,
+            label_font_size : 12,
+            tick_font_size : 11,
+            margin : 5,
+            x_label_area_size : 40,
+            y_label_area_size : 60,
+            palette : CustomPalette::COLORS.iter().map(|(r, g, b)| RGBColor(*r, *g, *b)).collect(),
+            line_width : 1,
+            marker_size : 2,
+            background : WHITE,
+            foreground : BLACK,
+            highlight_color : RED,
+            show_grid : true,
+            legend_position : LegendPosition::Outside,
+        }
+    }
+}
+
+impl FigureStyle {
+    /// a dark theme, with light texts and series on a dark background
+    pub fn dark() -> Self {
+        Self {
+            background : RGBColor(30, 30, 36),
+            foreground : RGBColor(220, 220, 220),
+            palette : DARK_PALETTE.iter().map(|(r, g, b)| RGBColor(*r, *g, *b)).collect(),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_dpi_scale(mut self, dpi_scale : f32) -> Self {
+        self.dpi_scale = dpi_scale;
+        self
+    }
+
+    pub fn with_palette(mut self, palette : Vec<RGBColor>) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    pub fn with_legend_position(mut self, legend_position : LegendPosition) -> Self {
+        self.legend_position = legend_position;
+        self
+    }
+
+    pub fn with_grid(mut self, show_grid : bool) -> Self {
+        self.show_grid = show_grid;
+        self
+    }
+
+    pub fn with_highlight_color(mut self, highlight_color : RGBColor) -> Self {
+        self.highlight_color = highlight_color;
+        self
+    }
+
+    /// apply the dpi scale to a size
+    pub fn scaled(&self, size : u32) -> u32 {
+        (size as f32 * self.dpi_scale).round() as u32
+    }
+
+    /// get the height of the figure title area
+    pub fn get_title_height(&self) -> u32 {
+        self.scaled(self.title_font_size + 2 * self.margin)
+    }
+
+    /// get the size of the whole figure (width, height) for the given grid (width, height)
+    /// NOTE : the title area is added to the height if the figure has a title
+    pub fn get_figure_size(&self, grid : (usize, usize), has_title : bool) -> (u32, u32) {
+        let legend_width = if self.legend_position == LegendPosition::Outside { self.legend_width } else { 0 };
+        let title_height = if has_title { self.get_title_height() } else { 0 };
+        (
+            self.scaled(grid.0 as u32 * self.subplot_size.0 + legend_width),
+            self.scaled(grid.1 as u32 * self.subplot_size.1) + title_height,
+        )
+    }
+
+    /// get the color of the palette at the given index (loop over the palette, the foreground color if the palette is empty)
+    pub fn get_palette_color(&self, index : usize) -> RGBColor {
+        if self.palette.is_empty() {
+            return self.foreground;
+        }
+        self.palette[index % self.palette.len()]
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_figure_style_sizes() {
+        let style = FigureStyle::default();
+        assert_eq!(style.get_figure_size((2, 1), false), (2 * ONE_FIG_SIZE.0 + LABEL_HORIZONTAL_SIZE, ONE_FIG_SIZE.1));
+
+        let style = style.with_dpi_scale(2.0).with_legend_position(LegendPosition::InsideUpperRight);
+        assert_eq!(style.get_figure_size((1, 1), false), (2 * ONE_FIG_SIZE.0, 2 * ONE_FIG_SIZE.1));
+        assert_eq!(style.get_figure_size((1, 1), true), (2 * ONE_FIG_SIZE.0, 2 * ONE_FIG_SIZE.1 + 2 * (FIGURE_CAPTION_FONT_SIZE + 20)));
+        assert_eq!(style.scaled(style.caption_font_size), 2 * FIGURE_CAPTION_FONT_SIZE);
+    }
+
+    #[test]
+    fn test_figure_style_palette() {
+        let style = FigureStyle::default().with_palette(vec![RGBColor(1, 2, 3), RGBColor(4, 5, 6)]);
+        assert_eq!(style.get_palette_color(3), RGBColor(4, 5, 6));
+        assert_eq!(FigureStyle::dark().with_palette(Vec::new()).get_palette_color(0), RGBColor(220, 220, 220));
+        assert_eq!(FigureStyle::default().get_palette_color(0), RGBColor(230, 25, 75));
+    }
+
+    /// the contrast ratio between two colors (WCAG definition, from 1 to 21)
+    fn contrast_ratio(a : RGBColor, b : RGBColor) -> f64 {
+        let channel = |value : u8| {
+            let value = value as f64 / 255.0;
+            if value <= 0.04045 { value / 12.92 } else { ((value + 0.055) / 1.055).powf(2.4) }
+        };
+        let luminance = |color : RGBColor| 0.2126 * channel(color.0) + 0.7152 * channel(color.1) + 0.0722 * channel(color.2);
+        let (la, lb) = (luminance(a), luminance(b));
+        (la.max(lb) + 0.05) / (la.min(lb) + 0.05)
+    }
+
+    #[test]
+    fn test_dark_palette_contrast() {
+        let style = FigureStyle::dark();
+        assert!(style.palette.iter().all(|color| contrast_ratio(*color, style.background) >= 4.5));
+        // the default palette has colors hidden by the dark background
+        assert!(FigureStyle::default().palette.iter().any(|color| contrast_ratio(*color, style.background) < 2.0));
+    }
+}
@@ -0,0 +1,324 @@
+use crate::data::axis::{AxesConfig, AxisConfig};
+use crate::data::datetime::TimeBucket;
+use crate::data::downsampling::Downsampling;
+use crate::data::filtering::Filter;
+use crate::data::plot_data::CategoryOrder;
+use crate::data::plottable::key::SerieKey;
+use crate::stats::fitting::FitModel;
+use crate::stats::stats_serie::MetricName;
+
+use super::utils::LegendSymbol;
+
+
+/// the kind of plot drawn in a subplot
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlotKind {
+    /// the points are linked by a line
+    Line,
+    /// the points are drawn as markers
+    Scatter,
+    /// the distribution of the x values, cut in the given number of bins
+    Histogram(usize),
+    /// the number of points (of all the legends) in each cell of a grid of the given number of bins per axis, drawn as a color intensity
+    /// NOTE : to use instead of a scatter when there are too many points to see the density
+    Density(usize),
+}
+
+impl PlotKind {
+    /// get the symbol used in the legend for this kind of plot
+    pub(crate) fn get_legend_symbol(&self) -> LegendSymbol {
+        match self {
+            PlotKind::Line => LegendSymbol::Line,
+            PlotKind::Scatter | PlotKind::Density(_) => LegendSymbol::Marker,
+            PlotKind::Histogram(_) => LegendSymbol::Bar,
+        }
+    }
+}
+
+/// a text written on a subplot
+/// position : the position of the top left corner of the text, in the data space
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    pub text : String,
+    pub position : (f64, f64),
+}
+
+impl Annotation {
+    pub fn new(text : &str, position : (f64, f64)) -> Self {
+        Self {
+            text : text.to_string(),
+            position,
+        }
+    }
+}
+
+/// describe one subplot of a figure
+/// kind : the kind of plot to draw
+/// x_serie_key, y_serie_key : the series to plot (the y serie is ignored by a histogram, which count the x values)
+/// title, x_label, y_label : generated from the keys if None
+/// x_unit, y_unit : if Some, added to the axis label between parenthesis
+/// annotations : the texts written on the subplot (not drawn if their position is out of the axes)
+/// highlights : the points circled on the subplot, in the data space (a regression for example, not drawn if out of the axes)
+/// fits : the models fitted on each legend and drawn over the data (ignored by a histogram and a density)
+/// filters : if Some, the data is filtered by the given filters (true to keep the data)
+/// remove_outliers : if Some, the outliers of the given keys are removed from the data
+/// aggregation_metric : if Some, the y values with the same x value are combined with this metric
+/// time_bucket : if Some, the x values (a datetime serie) are replaced by the start of their time bucket,
+/// and the y values of a bucket are combined with the aggregation metric (the mean if None)
+/// category_order : the order of the categories on the x axis, if the x serie is a string serie (in order of appearance by default)
+/// axes : the scale, range and inversion of the axes (only the x axis is used by a histogram, its y axis is the count)
+/// downsampling : how the points are reduced before being drawn (ignored by a histogram and a density)
+#[derive(Clone)]
+pub struct Subplot<'a, Key>
+where
+    Key : SerieKey,
+{
+    pub kind : PlotKind,
+    pub x_serie_key : Key,
+    pub y_serie_key : Key,
+    pub title : Option<String>,
+    pub x_label : Option<String>,
+    pub y_label : Option<String>,
+    pub x_unit : Option<String>,
+    pub y_unit : Option<String>,
+    pub annotations : Vec<Annotation>,
+    pub highlights : Vec<(f64, f64)>,
+    pub fits : Vec<FitModel>,
+    pub filters : Option<Vec<&'a Filter<Key>>>,
+    pub remove_outliers : Option<Vec<Key>>,
+    pub aggregation_metric : Option<MetricName>,
+    pub time_bucket : Option<TimeBucket>,
+    pub category_order : CategoryOrder,
+    pub axes : AxesConfig,
+    pub downsampling : Downsampling,
+}
+
+impl<'a, Key> Subplot<'a, Key>
+where
+    Key : SerieKey,
+{
+    pub fn new(kind : PlotKind, x_serie_key : Key, y_serie_key : Key) -> Self {
+        Self {
+            kind,
+            x_serie_key,
+            y_serie_key,
+            title : None,
+            x_label : None,
+            y_label : None,
+            x_unit : None,
+            y_unit : None,
+            annotations : Vec::new(),
+            highlights : Vec::new(),
+            fits : Vec::new(),
+            filters : None,
+            remove_outliers : None,
+            aggregation_metric : None,
+            time_bucket : None,
+            category_order : CategoryOrder::default(),
+            axes : AxesConfig::default(),
+            downsampling : Downsampling::default(),
+        }
+    }
+
+    /// a line plot, the y values with the same x value are combined with the given metric
+    /// NOTE : the line keeps the extremes of each x bucket when downsampled (see Downsampling::line)
+    pub fn line(x_serie_key : Key, y_serie_key : Key, aggregation_metric : MetricName) -> Self {
+        Self::new(PlotKind::Line, x_serie_key, y_serie_key)
+            .with_aggregation_metric(aggregation_metric)
+            .with_downsampling(Downsampling::line())
+    }
+
+    pub fn scatter(x_serie_key : Key, y_serie_key : Key) -> Self {
+        Self::new(PlotKind::Scatter, x_serie_key, y_serie_key)
+    }
+
+    pub fn histogram(serie_key : Key, nb_of_bins : usize) -> Self {
+        Self::new(PlotKind::Histogram(nb_of_bins), serie_key, serie_key)
+    }
+
+    pub fn density(x_serie_key : Key, y_serie_key : Key, nb_of_bins : usize) -> Self {
+        Self::new(PlotKind::Density(nb_of_bins), x_serie_key, y_serie_key)
+    }
+
+    pub fn with_title(mut self, title : &str) -> Self {
+        self.title = Some(title.to_string());
+        self
+    }
+
+    pub fn with_x_label(mut self, x_label : &str) -> Self {
+        self.x_label = Some(x_label.to_string());
+        self
+    }
+
+    pub fn with_y_label(mut self, y_label : &str) -> Self {
+        self.y_label = Some(y_label.to_string());
+        self
+    }
+
+    pub fn with_x_unit(mut self, x_unit : &str) -> Self {
+        self.x_unit = Some(x_unit.to_string());
+        self
+    }
+
+    pub fn with_y_unit(mut self, y_unit : &str) -> Self {
+        self.y_unit = Some(y_unit.to_string());
+        self
+    }
+
+    pub fn with_annotation(mut self, annotation : Annotation) -> Self {
+        self.annotations.push(annotation);
+        self
+    }
+
+    pub fn with_highlights(mut self, points : Vec<(f64, f64)>) -> Self {
+        self.highlights.extend(points);
+        self
+    }
+
+    /// fit the model on the data of each legend (before the axes transformation), and draw it
+    pub fn with_fit(mut self, model : FitModel) -> Self {
+        self.fits.push(model);
+        self
+    }
+
+    pub fn with_filters(mut self, filters : Vec<&'a Filter<Key>>) -> Self {
+        self.filters = Some(filters);
+        self
+    }
+
+    pub fn with_remove_outliers(mut self, keys : Vec<Key>) -> Self {
+        self.remove_outliers = Some(keys);
+        self
+    }
+
+    pub fn with_aggregation_metric(mut self, aggregation_metric : MetricName) -> Self {
+        self.aggregation_metric = Some(aggregation_metric);
+        self
+    }
+
+    /// aggregate the data per time bucket (per day, per week...), the x serie must be a datetime
+    pub fn with_time_bucket(mut self, time_bucket : TimeBucket) -> Self {
+        self.time_bucket = Some(time_bucket);
+        self
+    }
+
+    /// sort the categories of a categorical x axis (the x serie must be a string serie)
+    pub fn with_category_order(mut self, category_order : CategoryOrder) -> Self {
+        self.category_order = category_order;
+        self
+    }
+
+    pub fn with_axes(mut self, axes : AxesConfig) -> Self {
+        self.axes = axes;
+        self
+    }
+
+    pub fn with_downsampling(mut self, downsampling : Downsampling) -> Self {
+        self.downsampling = downsampling;
+        self
+    }
+
+    /// get the title of the subplot
+    pub fn get_title(&self) -> String {
+        match (&self.title, self.kind) {
+            (Some(title), _) => title.clone(),
+            (None, PlotKind::Histogram(_)) => format!("distribution of {}", self.x_serie_key.get_display_name()),
+            (None, PlotKind::Density(_)) => format!("density of {} per {}", self.y_serie_key.get_display_name(), self.x_serie_key.get_display_name()),
+            (None, _) => format!("{} per {}", self.y_serie_key.get_display_name(), self.x_serie_key.get_display_name()),
+        }
+    }
+
+    pub fn get_x_label(&self) -> String {
+        let x_label = self.x_label.clone().unwrap_or_else(|| self.x_serie_key.get_display_name());
+        with_unit(x_label, &self.x_unit)
+    }
+
+    pub fn get_y_label(&self) -> String {
+        let y_label = match (&self.y_label, self.kind) {
+            (Some(y_label), _) => y_label.clone(),
+            (None, PlotKind::Histogram(_)) => "count".to_string(),
+            (None, _) => self.y_serie_key.get_display_name(),
+        };
+        with_unit(y_label, &self.y_unit)
+    }
+
+    /// get if the (x, y) axes show dates (the y axis of a histogram is the count)
+    pub(crate) fn get_datetime_axes(&self) -> (bool, bool) {
+        match self.kind {
+            PlotKind::Histogram(_) => (self.x_serie_key.is_datetime(), false),
+            _ => (self.x_serie_key.is_datetime(), self.y_serie_key.is_datetime()),
+        }
+    }
+
+    /// get if the x axis shows the categories of a string serie
+    pub(crate) fn is_categorical_x(&self) -> bool {
+        !self.x_serie_key.is_numeric()
+    }
+
+    /// get the axes really used by the subplot (the y axis of a histogram is the linear count,
+    /// the x axis of a categorical serie is the linear index of the categories)
+    pub(crate) fn get_used_axes(&self) -> AxesConfig {
+        let x_axis = if self.is_categorical_x() { AxisConfig::default() } else { self.axes.x.clone() };
+        match self.kind {
+            PlotKind::Histogram(_) => AxesConfig::new(x_axis, AxisConfig::default()),
+            _ => AxesConfig::new(x_axis, self.axes.y.clone()),
+        }
+    }
+}
+
+/// add the unit to a label, between parenthesis
+fn with_unit(label : String, unit : &Option<String>) -> String {
+    match unit {
+        Some(unit) => format!("{} ({})", label, unit),
+        None => label,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_derive::{Deserialize, Serialize};
+    use std::fmt::{Display, Formatter};
+    use crate::data::axis::AxisScale;
+    use crate::generate_plot_key;
+
+    generate_plot_key!(TestKey[
+        Size { "size", Numeric },
+        Time { "time", Numeric }
+    ]);
+
+    #[test]
+    fn test_subplot_labels() {
+        let line = Subplot::line(TestKey::Size, TestKey::Time, MetricName::Mean);
+        assert_eq!(line.get_title(), "time per size");
+        assert_eq!(line.get_y_label(), "time");
+        assert_eq!(line.aggregation_metric, Some(MetricName::Mean));
+        assert_eq!(line.downsampling, Downsampling::line());
+
+        let histogram = Subplot::histogram(TestKey::Time, 10).with_x_label("time (s)");
+        assert_eq!(histogram.get_title(), "distribution of time");
+        assert_eq!(histogram.get_x_label(), "time (s)");
+        assert_eq!(histogram.get_y_label(), "count");
+
+        let scatter = Subplot::scatter(TestKey::Size, TestKey::Time).with_title("custom")
+            .with_y_label("duration")
+            .with_y_unit("ms")
+            .with_x_unit("Mb");
+        assert_eq!(scatter.get_title(), "custom");
+        assert_eq!(scatter.get_x_label(), "size (Mb)");
+        assert_eq!(scatter.get_y_label(), "duration (ms)");
+
+        let density = Subplot::density(TestKey::Size, TestKey::Time, 50);
+        assert_eq!(density.get_title(), "density of time per size");
+        assert_eq!(density.get_y_label(), "time");
+    }
+
+    #[test]
+    fn test_subplot_histogram_axes() {
+        let axes = AxesConfig::new(AxisConfig::new(AxisScale::Log10), AxisConfig::new(AxisScale::Log2));
+        let histogram = Subplot::histogram(TestKey::Time, 10).with_axes(axes.clone());
+        assert_eq!(histogram.get_used_axes(), AxesConfig::new(AxisConfig::new(AxisScale::Log10), AxisConfig::default()));
+        assert_eq!(Subplot::scatter(TestKey::Size, TestKey::Time).with_axes(axes.clone()).get_used_axes(), axes);
+    }
+}
@@ -1,13 +1,19 @@
-use std::collections::HashMap;
-
 use plotters::backend::BitMapBackend;
-use plotters::chart::{ChartBuilder, SeriesLabelPosition};
+use plotters::chart::{ChartBuilder, ChartContext, SeriesAnno, SeriesLabelPosition};
+use plotters::coord::cartesian::Cartesian2d;
+use plotters::coord::types::RangedCoordf64;
 use plotters::coord::Shift;
 use plotters::drawing::DrawingArea;
-use plotters::element::Circle;
-use plotters::style::{Color, Palette, PaletteColor, RGBColor, BLACK, WHITE};
+use plotters::element::{Circle, Cross, DashedPathElement, EmptyElement, PathElement, Rectangle, Text, TriangleMarker};
+use plotters::series::{DashedLineSeries, LineSeries};
+use plotters::style::{Color, IntoFont, Palette, RGBColor, WHITE};
 
 use crate::data::plottable::key::SerieKey;
+use crate::error::PlotHelperError;
+use crate::utils::axe_number_formater;
+
+use super::legend::{get_gradient_range, LegendConfig, LegendEntry};
+use super::style::{FigureStyle, LegendPosition};
 
 
 
@@ -44,19 +50,134 @@ impl Palette for CustomPalette {
 
 
 
-/// draw the legend on the given drawing area
-pub fn write_legend<Key> (
+/// get the color of the palette at the given index (loop over the palette)
+pub(crate) fn palette_color(index : usize) -> RGBColor {
+    let (r, g, b) = CustomPalette::COLORS[index % CustomPalette::COLORS.len()];
+    RGBColor(r, g, b)
+}
+
+/// the kind of symbol drawn in the legend, and used to represent the style dimension
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum LegendSymbol {
+    /// a marker, its shape depend on the style index
+    Marker,
+    /// a line, its dash depend on the style index
+    Line,
+    /// a filled bar, the same for all the styles
+    Bar,
+}
+
+/// number of different marker shapes
+const NB_OF_MARKERS : usize = 6;
+
+/// draw a serie of markers on the chart, their shape is given by the style index (loop over the shapes)
+pub(crate) fn draw_marker_serie(
+    chart : &mut ChartContext<'_, BitMapBackend<'_>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+    points : &[(f64, f64)],
+    style_index : usize,
+    size : i32,
+    color : RGBColor,
+) -> Result<(), PlotHelperError> {
+    let points = points.iter().copied();
+    match style_index % NB_OF_MARKERS {
+        0 => chart.draw_series(points.map(|p| Circle::new(p, size, color.filled())))?,
+        1 => chart.draw_series(points.map(|p| TriangleMarker::new(p, size + 1, color.filled())))?,
+        2 => chart.draw_series(points.map(|p| EmptyElement::at(p) + Rectangle::new([(-size, -size), (size, size)], color.filled())))?,
+        3 => chart.draw_series(points.map(|p| Cross::new(p, size + 1, color.stroke_width(2))))?,
+        4 => chart.draw_series(points.map(|p| Circle::new(p, size, color.stroke_width(1))))?,
+        _ => chart.draw_series(points.map(|p| EmptyElement::at(p) + Rectangle::new([(-size, -size), (size, size)], color.stroke_width(1))))?,
+    };
+    Ok(())
+}
+
+/// number of different line dashes
+const NB_OF_DASHES : usize = 4;
+
+impl LegendSymbol {
+    /// get the number of different styles drawn with the symbol (None if the symbol has a single style)
+    pub(crate) fn get_nb_of_styles(&self) -> Option<usize> {
+        match self {
+            LegendSymbol::Marker => Some(NB_OF_MARKERS),
+            LegendSymbol::Line => Some(NB_OF_DASHES),
+            LegendSymbol::Bar => None,
+        }
+    }
+}
+
+/// get the (dash size, dash spacing) of the line with the given style index (None for a solid line)
+fn line_dash(style_index : usize) -> Option<(i32, i32)> {
+    match style_index % NB_OF_DASHES {
+        0 => None,
+        1 => Some((10, 6)),
+        2 => Some((3, 4)),
+        _ => Some((16, 4)),
+    }
+}
+
+/// draw a line serie on the chart, with the dash given by the style index
+pub(crate) fn draw_line_serie(
+    chart : &mut ChartContext<'_, BitMapBackend<'_>, Cartesian2d<RangedCoordf64, RangedCoordf64>>,
+    points : &[(f64, f64)],
+    style_index : usize,
+    width : u32,
+    color : RGBColor,
+) -> Result<(), PlotHelperError> {
+    let style = color.stroke_width(width);
+    match line_dash(style_index) {
+        None => {
+            chart.draw_series(LineSeries::new(points.iter().copied(), style))?;
+        },
+        Some((size, spacing)) => {
+            chart.draw_series(DashedLineSeries::new(points.iter().copied(), size, spacing, style))?;
+        },
+    }
+    Ok(())
+}
+
+/// set the symbol of a legend label (in the legend area)
+fn set_legend_symbol(
+    anno : &mut SeriesAnno<'_, BitMapBackend<'_>>,
+    symbol : Option<LegendSymbol>,
+    style_index : usize,
+    color : RGBColor,
+    style : &FigureStyle,
+) {
+    let size = style.scaled(5) as i32;
+    let length = style.scaled(8) as i32;
+    let width = style.scaled(2);
+    match (symbol, style_index % NB_OF_MARKERS, line_dash(style_index)) {
+        (None, _, _) => anno.legend(move |(x, y)| Circle::new((x, y), size, WHITE.mix(0.0).filled())),
+        (Some(LegendSymbol::Marker), 0, _) => anno.legend(move |(x, y)| Circle::new((x, y), size, color.filled())),
+        (Some(LegendSymbol::Marker), 1, _) => anno.legend(move |(x, y)| TriangleMarker::new((x, y), size + 1, color.filled())),
+        (Some(LegendSymbol::Marker), 2, _) => anno.legend(move |(x, y)| Rectangle::new([(x - size, y - size), (x + size, y + size)], color.filled())),
+        (Some(LegendSymbol::Marker), 3, _) => anno.legend(move |(x, y)| Cross::new((x, y), size + 1, color.stroke_width(width))),
+        (Some(LegendSymbol::Marker), 4, _) => anno.legend(move |(x, y)| Circle::new((x, y), size, color.stroke_width(1))),
+        (Some(LegendSymbol::Marker), _, _) => anno.legend(move |(x, y)| Rectangle::new([(x - size, y - size), (x + size, y + size)], color.stroke_width(1))),
+        (Some(LegendSymbol::Bar), _, _) => anno.legend(move |(x, y)| Rectangle::new([(x - length, y - size), (x + length, y + size)], color.mix(0.4).filled())),
+        (Some(LegendSymbol::Line), _, None) => anno.legend(move |(x, y)| PathElement::new(vec![(x - length, y), (x + length, y)], color.stroke_width(width))),
+        (Some(LegendSymbol::Line), _, Some((dash_size, spacing))) => anno.legend(move |(x, y)| {
+            DashedPathElement::new(vec![(x - length, y), (x + length, y)], dash_size / 2, spacing / 2, color.stroke_width(width))
+        }),
+    };
+}
+
+/// draw the legend on the given drawing area (the legend area, or the plotting area of a subplot if the legend is inside)
+/// legend_entries : the legends to draw, in order, with their color and style
+/// NOTE : if the legend is styled, the colors and the styles are drawn in two sections
+pub(crate) fn write_legend<Key> (
     label_drawing_area: &DrawingArea<BitMapBackend<'_>, Shift>,
-    legend_to_color : &HashMap<String, PaletteColor<CustomPalette>>,
-    legend_serie_key : &Option<Key>
-) -> Result<(), Box<dyn std::error::Error>>
+    legend_entries : &[LegendEntry],
+    legend : &LegendConfig<Key>,
+    symbol : LegendSymbol,
+    style : &FigureStyle,
+) -> Result<(), PlotHelperError>
 where 
     Key : SerieKey,
 {
     // draw the legend on a fantome chart
     let mut label_chart = 
     ChartBuilder::on(label_drawing_area)
-    .margin(5)
+    .margin(style.scaled(style.margin))
     .build_cartesian_2d(0..1, 0..1)?;
 
     label_chart
@@ -64,68 +185,167 @@ where
         .disable_mesh()
         .draw()?;
 
+    // draw phantome series to get the legend (no symbol if None)
+    let mut draw_label = |label : String, symbol : Option<LegendSymbol>, style_index : usize, color : RGBColor| -> Result<(), PlotHelperError> {
+        let dummy_data : Vec<(i32, i32)> = Vec::new();
+        let anno = label_chart
+            .draw_series(dummy_data.iter().map(|(x, y)| Circle::new((*x, *y), 2, color.filled())))?;
+        anno.label(label);
+        set_legend_symbol(anno, symbol, style_index, color, style);
+        Ok(())
+    };
 
+    let (color_title, style_title) = legend.legend_keys.get_dimension_titles();
+    let mut has_labels = false;
 
+    // the color dimension, as a color bar for a gradient
+    let gradient_range = if legend.legend_keys.is_gradient() {
+        get_gradient_range(&legend_entries.iter().map(|entry| entry.color_label.clone()).collect::<Vec<_>>())
+    } else {
+        None
+    };
+    if let Some((min, max)) = gradient_range {
+        // the bar is at the left of the legend area, or at the right of the area if the legend is inside
+        let bar_left = match style.legend_position {
+            LegendPosition::Outside => label_drawing_area.dim_in_pixel().0 as i32 / 8,
+            _ => label_drawing_area.dim_in_pixel().0 as i32 - style.scaled(style.legend_width) as i32 * 7 / 8,
+        };
+        draw_color_bar(label_drawing_area, color_title.unwrap_or_default(), bar_left, |ratio| min + ratio * (max - min), style)?;
+    } else if let Some(color_title) = color_title {
+        // begin by the one indicating the keys used
+        draw_label(color_title, None, 0, style.background)?;
+        has_labels = true;
+    }
 
-    let dummy_data : Vec<(i32, i32)> = Vec::new();
-
-    // draw phantome serie to get the legend
+    let mut drawn_color_labels : Vec<&String> = Vec::new();
+    for entry in legend_entries.iter().filter(|_| gradient_range.is_none()) {
+        // skip empty legend
+        if entry.color_label.is_empty() || drawn_color_labels.contains(&&entry.color_label) {
+            continue;
+        }
+        drawn_color_labels.push(&entry.color_label);
+        has_labels = true;
+        // the colors are drawn with the default style if the style is given by another dimension
+        let style_index = if legend.legend_keys.is_styled() { 0 } else { entry.style_index };
+        draw_label(entry.color_label.clone(), Some(symbol), style_index, entry.color)?;
+    }
 
-    // begin by the first one indicating the key used
-    if let Some(legend_serie_key) = legend_serie_key {
-        let color = RGBColor(255, 255, 255).mix(0.0);
-        
-        let serie_unlabellized = label_chart
-                .draw_series(
-                    dummy_data.iter()
-                        .map(|(x, y)| Circle::new((*x, *y), 2, color.filled())),
-            )?;
-        serie_unlabellized.label(legend_serie_key.get_display_name()).legend(move |(x, y)| {
-            Circle::new((x, y), 5, color.filled())
-        });
+    // then the style dimension
+    if let Some(style_title) = style_title {
+        draw_label(style_title, None, 0, style.background)?;
+        has_labels = true;
+        let mut drawn_style_indexes : Vec<usize> = Vec::new();
+        let mut style_entries = legend_entries.iter().collect::<Vec<_>>();
+        style_entries.sort_by_key(|entry| entry.style_index);
+        for entry in style_entries {
+            if drawn_style_indexes.contains(&entry.style_index) {
+                continue;
+            }
+            drawn_style_indexes.push(entry.style_index);
+            let style_label = entry.style_label.clone().unwrap_or_default();
+            draw_label(style_label, Some(symbol), entry.style_index, style.foreground)?;
+        }
     }
 
-    
-    let mut unique_legends = legend_to_color.keys().collect::<Vec<_>>();
-    if legend_serie_key.is_some() && legend_serie_key.unwrap().is_numeric(){
-        unique_legends.sort_by(|a, b| a.parse::<f32>().unwrap().partial_cmp(&b.parse::<f32>().unwrap()).unwrap());
+    if has_labels {
+        let position = match style.legend_position {
+            // below the color bar if there is one
+            LegendPosition::Outside if gradient_range.is_some() => SeriesLabelPosition::LowerMiddle,
+            LegendPosition::Outside => SeriesLabelPosition::MiddleMiddle,
+            LegendPosition::InsideUpperLeft => SeriesLabelPosition::UpperLeft,
+            LegendPosition::InsideUpperRight => SeriesLabelPosition::UpperRight,
+            LegendPosition::InsideLowerLeft => SeriesLabelPosition::LowerLeft,
+            LegendPosition::InsideLowerRight => SeriesLabelPosition::LowerRight,
+        };
+        let font = (style.font_family.as_str(), style.scaled(style.label_font_size)).into_font().color(&style.foreground);
+        label_chart
+            .configure_series_labels()
+            .background_style(style.background.mix(0.8))
+            .border_style(style.foreground)
+            .label_font(font)
+            .position(position)
+            .draw()?;
     }
 
-    for legend in unique_legends.iter() {
-        // skip empty legend
-        if legend == &"" {
-            continue;
-        }
-        let color = legend_to_color.get(*legend).unwrap();
-        let serie_unlabellized = label_chart
-                .draw_series(
-                    dummy_data.iter()
-                        .map(|(x, y)| Circle::new((*x, *y), 2, color.filled())),
-            )?;
-            serie_unlabellized.label(legend.to_string()).legend(move |(x, y)| {
-                Circle::new((x, y), 5, color.filled())
-            });
+    Ok(())
+}
+
+/// number of slices drawn to render the color bar
+const COLOR_BAR_SLICES : i32 = 100;
+/// number of values written next to the color bar
+const COLOR_BAR_TICKS : i32 = 5;
+
+/// draw a vertical color bar (the start of the gradient at the bottom, the end at the top) with its title in the given area
+/// bar_left : the position of the left side of the bar in the area
+/// value_at : the value written at a ratio of the gradient (between 0 and 1)
+pub(crate) fn draw_color_bar(
+    area : &DrawingArea<BitMapBackend<'_>, Shift>,
+    title : String,
+    bar_left : i32,
+    value_at : impl Fn(f64) -> f64,
+    style : &FigureStyle,
+) -> Result<(), PlotHelperError> {
+    let height = area.dim_in_pixel().1 as i32;
+    let bar_width = style.scaled(20) as i32;
+    let bar_right = bar_left + bar_width;
+    let (bar_top, bar_bottom) = (height / 4, height * 5 / 8);
+    let font = (style.font_family.as_str(), style.scaled(style.label_font_size + 1)).into_font().color(&style.foreground);
+
+    area.draw(&Text::new(title, (bar_left, bar_top - style.scaled(25) as i32), font.clone()))?;
+
+    let slice_height = (bar_bottom - bar_top) as f64 / COLOR_BAR_SLICES as f64;
+    for slice in 0..COLOR_BAR_SLICES {
+        let top = bar_top + (slice as f64 * slice_height) as i32;
+        let bottom = bar_top + ((slice + 1) as f64 * slice_height).ceil() as i32;
+        let ratio = 1.0 - (slice as f64 + 0.5) / COLOR_BAR_SLICES as f64;
+        area.draw(&Rectangle::new([(bar_left, top), (bar_right, bottom)], gradient_color(ratio).filled()))?;
     }
+    area.draw(&Rectangle::new([(bar_left, bar_top), (bar_right, bar_bottom)], style.foreground.stroke_width(1)))?;
 
-    label_chart
-        .configure_series_labels()
-        .background_style(&WHITE.mix(0.8))
-        .border_style(&BLACK)
-        .position(SeriesLabelPosition::MiddleMiddle)
-        .draw()?;
+    for tick in 0..COLOR_BAR_TICKS {
+        let ratio = tick as f64 / (COLOR_BAR_TICKS - 1) as f64;
+        let y = bar_bottom - (ratio * (bar_bottom - bar_top) as f64) as i32;
+        let value = value_at(ratio);
+        let tick_size = style.scaled(4) as i32;
+        area.draw(&PathElement::new(vec![(bar_right, y), (bar_right + tick_size, y)], style.foreground.stroke_width(1)))?;
+        area.draw(&Text::new(axe_number_formater(&value), (bar_right + 2 * tick_size, y - style.scaled(6) as i32), font.clone()))?;
+    }
 
     Ok(())
 }
 
-const TRESHOLD : f32 = 1000.0;
+/// the control colors of the gradient (viridis color map), evenly spaced
+const GRADIENT_COLORS : [(u8, u8, u8); 9] = [
+    (68, 1, 84),
+    (71, 44, 122),
+    (59, 81, 139),
+    (44, 113, 142),
+    (33, 144, 141),
+    (39, 173, 129),
+    (92, 200, 99),
+    (170, 220, 50),
+    (253, 231, 37),
+];
 
-/// Format a number to a string
-pub(crate) fn axe_number_formater(x: &f32) -> String {
-    let x = *x;
-    if x < -TRESHOLD || (x > -1.0/TRESHOLD && x < 1.0/TRESHOLD) || x > TRESHOLD {
-        format!("{:.0e}", x)
-    } else {
-        format!("{:.3}", x)
+/// get the color of the gradient at the given ratio (between 0 and 1, grey if NaN)
+pub(crate) fn gradient_color(ratio : f64) -> RGBColor {
+    if ratio.is_nan() {
+        return RGBColor(128, 128, 128);
+    }
+    let position = ratio.clamp(0.0, 1.0) * (GRADIENT_COLORS.len() - 1) as f64;
+    let index = (position.floor() as usize).min(GRADIENT_COLORS.len() - 2);
+    let t = position - index as f64;
+    let (from, to) = (GRADIENT_COLORS[index], GRADIENT_COLORS[index + 1]);
+    let interpolate = |a : u8, b : u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+    RGBColor(interpolate(from.0, to.0), interpolate(from.1, to.1), interpolate(from.2, to.2))
+}
+
+/// get the color of the gradient for a value between min and max (the middle color if min == max)
+pub(crate) fn gradient_color_normalized(value : f64, min : f64, max : f64) -> RGBColor {
+    if max <= min {
+        return gradient_color(0.5);
     }
+    gradient_color((value - min) / (max - min))
 }
 
+
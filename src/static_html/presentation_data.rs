@@ -6,6 +6,7 @@ use std::path::Path;
 use serde::Serialize as SerdeSerialize;
 use serde_derive::{Deserialize, Serialize};
 
+use crate::error::PlotHelperError;
 use crate::params::HTML_TEMPLATE;
 
 use super::to_html::{ToHtmlDepth, ToTableOfContent};
@@ -36,7 +37,7 @@ impl Default for Ir {
 
 impl Ir {
     /// convert the intermediate representation to html
-    pub fn to_html(&self) -> Result<String, Box<dyn std::error::Error>> {
+    pub fn to_html(&self) -> Result<String, PlotHelperError> {
         let mut html_template = HTML_TEMPLATE.to_string();
         let html_content = self.elements.to_html(1);
         let table_of_content = self.elements.get_table_of_content(1);
@@ -56,7 +57,7 @@ impl Ir {
     }
 
     /// Wrapper around the new_from_dir function of ListElement
-    pub fn new_from_file_system(path : &str) -> Result<Ir, Box<dyn std::error::Error>> {
+    pub fn new_from_file_system(path : &str) -> Result<Ir, PlotHelperError> {
         Ok(ListElement::new_from_dir(path)?.into())
     }
 
@@ -101,15 +102,15 @@ impl ListElement {
 
     /// Get a new list element from a directory
     /// WARN : return an error if the path is not a directory and if the directory does not contain only directories
-    pub fn new_from_dir(path : &str) -> Result<ListElement, Box<dyn std::error::Error>> {
+    pub fn new_from_dir(path : &str) -> Result<ListElement, PlotHelperError> {
         let mut elements = Vec::new();
         for entry in fs::read_dir(path)? {
             let entry = entry?;
             let path = entry.path();
             if !path.is_dir() {
-                return Err("the directory should only contain directories".into());
+                return Err(PlotHelperError::Parse(format!("the directory should only contain directories, path : {}", path.display())));
             }
-            elements.push(Element::new_from_dir(path.to_str().unwrap())?);
+            elements.push(Element::new_from_dir(path_to_str(&path)?)?);
         }
         Ok(elements.into())
     }
@@ -119,6 +120,11 @@ impl ListElement {
     }
 }
 
+/// get the path as a str (error if the path is not valid unicode)
+fn path_to_str(path : &Path) -> Result<&str, PlotHelperError> {
+    path.to_str().ok_or_else(|| PlotHelperError::Parse(format!("the path is not valid unicode, path : {}", path.display())))
+}
+
 // ------------------------------------- Element -------------------------------------
 
 /// represent an element (title associated with content)
@@ -139,15 +145,18 @@ impl Element {
     }
 
     /// Get a new element from a directory
-    pub fn new_from_dir(path : &str) -> Result<Element, Box<dyn std::error::Error>> {
+    pub fn new_from_dir(path : &str) -> Result<Element, PlotHelperError> {
         let path_o = Path::new(path);
-        let title = path_o.file_name().unwrap().to_str().unwrap().to_string();
+        let title = path_o.file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| PlotHelperError::Parse(format!("the directory has no valid name, path : {}", path)))?
+            .to_string();
         let mut content = Vec::new();
         let mut entries = fs::read_dir(path_o)?.collect::<Result<Vec<_>, _>>()?;
         entries.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
         for entry in entries {
             let path = entry.path();
-            content.push(ContentElement::new_from_path(path.to_str().unwrap())?);
+            content.push(ContentElement::new_from_path(path_to_str(&path)?)?);
         }
         Ok(Element::new(title, content))
     }
@@ -171,7 +180,7 @@ pub enum ContentElement {
 }
 
 impl ContentElement {
-    pub fn new_from_path(path : &str) -> Result<ContentElement, Box<dyn std::error::Error>> {
+    pub fn new_from_path(path : &str) -> Result<ContentElement, PlotHelperError> {
         let path_o = Path::new(path);
         if path_o.is_dir() {
             Ok(Element::new_from_dir(path)?.into())
@@ -214,25 +223,25 @@ impl Content {
     }
 
     /// Create a new content from a path
-    pub fn new_from_path(path : &str) -> Result<Content, Box<dyn std::error::Error>> {
+    pub fn new_from_path(path : &str) -> Result<Content, PlotHelperError> {
         let path_o = Path::new(path);
         if path_o.is_file() {
             let extension = path_o.extension();
             if let Some(extension) = extension {
-                let extension = extension.to_str().unwrap();
+                let extension = extension.to_str().unwrap_or_default();
                 match extension {
-                    "csv" =>                   Ok(Array::from_csv(path).into()),
+                    "csv" =>                   Ok(Array::from_csv(path)?.into()),
                     "png" | "jpg" | "jpeg" =>  Ok(Content::new_image(path)),
                     COLLAPSABLE_EXTENSION =>   Ok(Collapsable::load_from_file(path)?.into()),
                     TEXT_EXTENSION =>          Ok(Text::load_from_file(path)?.into()),
-                    _ =>                       Err(format!("the extension {} is not supported for the file {}", extension, path).into())
+                    _ =>                       Err(PlotHelperError::Parse(format!("the extension {} is not supported for the file {}", extension, path)))
                 }
             } else {
                 let content : Content = serde_json::from_str(&fs::read_to_string(path)?)?;
                 Ok(content)
             }
         } else {
-            Err("the path should be a file".into())
+            Err(PlotHelperError::Parse(format!("the path should be a file, path : {}", path)))
         }
     }
 }
@@ -289,19 +298,19 @@ where T : SerdeSerialize
 }
 
 impl Collapsable<Content>{
-    pub fn save_to_file(&self, dir_path : &str, file_name : &str) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn save_to_file(&self, dir_path : &str, file_name : &str) -> Result<(), PlotHelperError> {
         let path = Path::new(dir_path).join(format!("{}.{}", file_name, COLLAPSABLE_EXTENSION));
         fs::write(path, serde_json::to_string(self)?)?;
         Ok(())
     }
 
-    pub fn load_from_file(path : &str) -> Result<Collapsable<Content>, Box<dyn std::error::Error>> {
+    pub fn load_from_file(path : &str) -> Result<Collapsable<Content>, PlotHelperError> {
         let path = Path::new(path);
         if !path.is_file() {
-            return Err(format!("the path should be a file, path : {}", path.to_str().unwrap()).into());
+            return Err(PlotHelperError::Parse(format!("the path should be a file, path : {}", path.display())));
         }
-        if path.extension().unwrap().to_str().unwrap() != COLLAPSABLE_EXTENSION {
-            return Err(format!("the file should have the correct extension, path : {}", path.to_str().unwrap()).into());
+        if path.extension().and_then(|extension| extension.to_str()) != Some(COLLAPSABLE_EXTENSION) {
+            return Err(PlotHelperError::Parse(format!("the file should have the correct extension, path : {}", path.display())));
         }
         let content : Collapsable<Content> = serde_json::from_str(&fs::read_to_string(path)?)?;
         Ok(content)
@@ -320,19 +329,19 @@ impl From<Vec<TextContent>> for Text {
 }
 
 impl Text {
-    pub fn save_to_file(&self, dir_path : &str, file_name : &str) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn save_to_file(&self, dir_path : &str, file_name : &str) -> Result<(), PlotHelperError> {
         let path = Path::new(dir_path).join(format!("{}.{}", file_name, TEXT_EXTENSION));
         fs::write(path, serde_json::to_string(self)?)?;
         Ok(())
     }
 
-    pub fn load_from_file(path : &str) -> Result<Text, Box<dyn std::error::Error>> {
+    pub fn load_from_file(path : &str) -> Result<Text, PlotHelperError> {
         let path = Path::new(path);
         if !path.is_file() {
-            return Err(format!("the path should be a file, path : {}", path.to_str().unwrap()).into());
+            return Err(PlotHelperError::Parse(format!("the path should be a file, path : {}", path.display())));
         }
-        if path.extension().unwrap().to_str().unwrap() != TEXT_EXTENSION {
-            return Err(format!("the file should have the correct extension, path : {}", path.to_str().unwrap()).into());
+        if path.extension().and_then(|extension| extension.to_str()) != Some(TEXT_EXTENSION) {
+            return Err(PlotHelperError::Parse(format!("the file should have the correct extension, path : {}", path.display())));
         }
         let content : Text = serde_json::from_str(&fs::read_to_string(path)?)?;
         Ok(content)
@@ -417,22 +426,23 @@ impl Array {
         }
     }
 
-    pub fn from_csv(path : &str) -> Array {
-        let mut csv_reader = csv::ReaderBuilder::new().has_headers(false).from_path(path).unwrap();
+    /// load an array from a csv file, the first line is the header
+    pub fn from_csv(path : &str) -> Result<Array, PlotHelperError> {
+        let mut csv_reader = csv::ReaderBuilder::new().has_headers(false).from_path(path)?;
         let mut header = Vec::new();
         let mut data = Vec::new();
         for result in csv_reader.records() {
-            let record = result.unwrap();
+            let record = result?;
             if header.is_empty() {
                 header = record.iter().map(|s| s.to_string()).collect();
             } else {
                 data.push(record.iter().map(|s| s.to_string()).collect());
             }
         }
-        Array {
+        Ok(Array {
             header,
             data
-        }
+        })
     }
 
     pub fn get_header(&self) -> &Vec<String> {
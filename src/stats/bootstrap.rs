@@ -0,0 +1,143 @@
+use std::fmt::{Display, Formatter};
+
+#[cfg(feature = "parrallelize")]
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use crate::error::PlotHelperError;
+
+use super::random::SplitMix64;
+use super::stats_serie::{sorted_percentile, MetricName, StatsSerie};
+
+/// the bootstrap estimation of a metric
+/// estimate : the metric on the original serie
+/// lower, upper : the bounds of the confidence interval (percentile method)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BootstrapEstimate {
+    pub metric : MetricName,
+    pub estimate : f64,
+    pub lower : f64,
+    pub upper : f64,
+    pub confidence_level : f64,
+}
+
+impl Display for BootstrapEstimate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f, "{}: {:.2} ({:.0}% CI [{:.2}, {:.2}])",
+            self.metric, self.estimate, self.confidence_level * 100.0, self.lower, self.upper
+        )
+    }
+}
+
+/// estimate the confidence interval of the metric with a bootstrap (resampling with replacement)
+/// confidence_level : between 0 and 1 (0.95 for example)
+/// NOTE : the result is the same for a given seed, with or without the parallelization
+/// NOTE : the estimate and the bounds are NaN if the serie is empty
+pub fn bootstrap(
+    serie : &Vec<f64>,
+    metric : MetricName,
+    nb_of_resamples : usize,
+    confidence_level : f64,
+    seed : u64,
+) -> Result<BootstrapEstimate, PlotHelperError> {
+    if nb_of_resamples == 0 {
+        return Err(PlotHelperError::InvalidParameter("the number of resamples must be greater than 0".to_string()));
+    }
+    if !(confidence_level > 0.0 && confidence_level < 1.0) {
+        return Err(PlotHelperError::InvalidParameter(format!("the confidence level must be between 0 and 1 (got {})", confidence_level)));
+    }
+
+    let estimate = StatsSerie::new(serie).get_stats(metric).value;
+    if serie.is_empty() {
+        return Ok(BootstrapEstimate {
+            metric,
+            estimate,
+            lower : f64::NAN,
+            upper : f64::NAN,
+            confidence_level,
+        });
+    }
+
+    let mut resampled_metrics = resample_metrics(serie, metric, nb_of_resamples, seed);
+    resampled_metrics.sort_by(|a, b| a.total_cmp(b));
+    let alpha = (1.0 - confidence_level) / 2.0;
+
+    Ok(BootstrapEstimate {
+        metric,
+        estimate,
+        lower : sorted_percentile(&resampled_metrics, alpha * 100.0),
+        upper : sorted_percentile(&resampled_metrics, (1.0 - alpha) * 100.0),
+        confidence_level,
+    })
+}
+
+/// compute the metric on each resample
+/// NOTE : each resample has its own generator (derived from the seed and its index), so the order of execution doesn't matter
+#[cfg(not(feature = "parrallelize"))]
+fn resample_metrics(serie : &[f64], metric : MetricName, nb_of_resamples : usize, seed : u64) -> Vec<f64> {
+    (0..nb_of_resamples).map(|index| resample_metric(serie, metric, seed, index)).collect()
+}
+
+/// compute the metric on each resample
+/// NOTE : each resample has its own generator (derived from the seed and its index), so the order of execution doesn't matter
+#[cfg(feature = "parrallelize")]
+fn resample_metrics(serie : &[f64], metric : MetricName, nb_of_resamples : usize, seed : u64) -> Vec<f64> {
+    (0..nb_of_resamples).into_par_iter().map(|index| resample_metric(serie, metric, seed, index)).collect()
+}
+
+fn resample_metric(serie : &[f64], metric : MetricName, seed : u64, index : usize) -> f64 {
+    let mut rng = SplitMix64::from_stream(seed, index as u64);
+    let resample : Vec<f64> = (0..serie.len()).map(|_| serie[rng.next_index(serie.len())]).collect();
+    StatsSerie::new(&resample).get_stats(metric).value
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_serie() -> Vec<f64> {
+        (1..=101).map(|v| v as f64).collect()
+    }
+
+    #[test]
+    fn test_bootstrap_median() {
+        let result = bootstrap(&get_serie(), MetricName::Median, 500, 0.95, 3).unwrap();
+        assert_eq!(result.estimate, 51.0);
+        assert!(result.lower < 51.0 && result.lower > 35.0);
+        assert!(result.upper > 51.0 && result.upper < 67.0);
+        assert_eq!(result, bootstrap(&get_serie(), MetricName::Median, 500, 0.95, 3).unwrap());
+    }
+
+    #[test]
+    fn test_bootstrap_seeds() {
+        // a power of two resamples : each seed must have its own resamples
+        let results = [0, 1, 5, 200].map(|seed| bootstrap(&get_serie(), MetricName::Mean, 256, 0.95, seed).unwrap());
+        for (index, result) in results.iter().enumerate() {
+            assert!(results[..index].iter().all(|other| (other.lower, other.upper) != (result.lower, result.upper)));
+        }
+    }
+
+    #[test]
+    fn test_bootstrap_percentile() {
+        let result = bootstrap(&get_serie(), MetricName::Percentile(90), 200, 0.9, 0).unwrap();
+        assert_eq!(result.estimate, 91.0);
+        assert!(result.lower <= result.estimate && result.estimate <= result.upper);
+        assert_eq!(result.to_string(), format!("p90: 91.00 (90% CI [{:.2}, {:.2}])", result.lower, result.upper));
+    }
+
+    #[test]
+    fn test_bootstrap_constant_and_empty() {
+        let result = bootstrap(&vec![2.0; 10], MetricName::Mean, 100, 0.95, 0).unwrap();
+        assert_eq!((result.lower, result.estimate, result.upper), (2.0, 2.0, 2.0));
+
+        let result = bootstrap(&vec![], MetricName::Median, 100, 0.95, 0).unwrap();
+        assert!(result.estimate.is_nan() && result.lower.is_nan() && result.upper.is_nan());
+    }
+
+    #[test]
+    fn test_bootstrap_invalid_parameters() {
+        assert!(bootstrap(&get_serie(), MetricName::Mean, 0, 0.95, 0).is_err());
+        assert!(bootstrap(&get_serie(), MetricName::Mean, 10, 1.0, 0).is_err());
+    }
+}
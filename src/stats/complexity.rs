@@ -0,0 +1,218 @@
+use std::fmt::{Display, Formatter};
+
+use crate::data::plot_data::PlotSeries;
+use crate::static_html::presentation_data::Array;
+
+use super::fitting::{fit, r_squared, FitModel};
+
+/// number of decimals used when the table is converted into an html array
+const ARRAY_PRECISION : usize = 4;
+
+/// a candidate complexity class, the model is y = a + b * f(n)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ComplexityClass {
+    Constant,
+    Logarithmic,
+    Linear,
+    Linearithmic,
+    Quadratic,
+    Cubic,
+}
+
+impl ComplexityClass {
+    /// all the candidate classes, from the slowest growing to the fastest
+    pub const ALL : [ComplexityClass; 6] = [
+        ComplexityClass::Constant,
+        ComplexityClass::Logarithmic,
+        ComplexityClass::Linear,
+        ComplexityClass::Linearithmic,
+        ComplexityClass::Quadratic,
+        ComplexityClass::Cubic,
+    ];
+
+    /// the growth function f(n) of the class
+    pub fn growth(&self, n : f64) -> f64 {
+        match self {
+            ComplexityClass::Constant => 1.0,
+            ComplexityClass::Logarithmic => n.ln(),
+            ComplexityClass::Linear => n,
+            ComplexityClass::Linearithmic => n * n.ln(),
+            ComplexityClass::Quadratic => n * n,
+            ComplexityClass::Cubic => n * n * n,
+        }
+    }
+}
+
+impl Display for ComplexityClass {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ComplexityClass::Constant => "O(1)",
+            ComplexityClass::Logarithmic => "O(log n)",
+            ComplexityClass::Linear => "O(n)",
+            ComplexityClass::Linearithmic => "O(n log n)",
+            ComplexityClass::Quadratic => "O(n²)",
+            ComplexityClass::Cubic => "O(n³)",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// the fit of a complexity class on a serie
+/// intercept, slope : the a and b of y = a + b * f(n)
+/// rmse : the root mean square error of the fit (used to rank the classes)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComplexityFit {
+    pub class : ComplexityClass,
+    pub intercept : f64,
+    pub slope : f64,
+    pub r_squared : f64,
+    pub rmse : f64,
+}
+
+impl ComplexityFit {
+    /// get the predicted value for the input size n
+    pub fn predict(&self, n : f64) -> f64 {
+        self.intercept + self.slope * self.class.growth(n)
+    }
+}
+
+/// the complexity classes of each legend, ranked from the best fit to the worst
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComplexityTable {
+    rows : Vec<(String, Vec<ComplexityFit>)>,
+}
+
+impl ComplexityTable {
+    pub fn get_rows(&self) -> &Vec<(String, Vec<ComplexityFit>)> {
+        &self.rows
+    }
+
+    /// get the best complexity class of the legend (None if the legend is unknown or has not enough points)
+    pub fn get_best(&self, legend : &str) -> Option<&ComplexityFit> {
+        self.rows.iter().find(|(row_legend, _)| row_legend == legend).and_then(|(_, fits)| fits.first())
+    }
+
+    /// convert the table to an html array (one line per legend and class, with the rank), with the given number of decimals
+    pub fn to_array(&self, precision : usize) -> Array {
+        let header = ["legend", "rank", "complexity", "R²", "rmse", "intercept", "slope"]
+            .iter().map(|h| h.to_string()).collect();
+        let data = self.rows.iter().flat_map(|(legend, fits)| {
+            fits.iter().enumerate().map(move |(rank, fit)| vec![
+                legend.clone(),
+                (rank + 1).to_string(),
+                fit.class.to_string(),
+                format!("{:.*}", precision, fit.r_squared),
+                format!("{:.*e}", precision, fit.rmse),
+                format!("{:.*e}", precision, fit.intercept),
+                format!("{:.*e}", precision, fit.slope),
+            ])
+        }).collect();
+        Array::new(header, data)
+    }
+}
+
+impl From<&ComplexityTable> for Array {
+    fn from(table : &ComplexityTable) -> Array {
+        table.to_array(ARRAY_PRECISION)
+    }
+}
+
+impl From<ComplexityTable> for Array {
+    fn from(table : ComplexityTable) -> Array {
+        table.to_array(ARRAY_PRECISION)
+    }
+}
+
+/// fit every candidate complexity class on the points (x is the input size)
+/// return the fits sorted by rmse (the slowest growing class first in case of tie)
+/// NOTE : only the points with x > 0 are used, and the classes which can't be fitted are skipped
+pub fn fit_complexity_classes(points : &[(f64, f64)]) -> Vec<ComplexityFit> {
+    let points : Vec<(f64, f64)> = points.iter().copied()
+        .filter(|(x, y)| *x > 0.0 && x.is_finite() && y.is_finite())
+        .collect();
+    if points.is_empty() {
+        return Vec::new();
+    }
+    let mut fits : Vec<ComplexityFit> = ComplexityClass::ALL.iter().filter_map(|class| {
+        let (intercept, slope) = match class {
+            ComplexityClass::Constant => (points.iter().map(|(_, y)| y).sum::<f64>() / points.len() as f64, 0.0),
+            _ => {
+                let transformed : Vec<(f64, f64)> = points.iter().map(|(x, y)| (class.growth(*x), *y)).collect();
+                let result = fit(&transformed, FitModel::Linear).ok()?;
+                (result.get_coefficients()[0], result.get_coefficients()[1])
+            },
+        };
+        let mut complexity_fit = ComplexityFit {
+            class : *class,
+            intercept,
+            slope,
+            r_squared : f64::NAN,
+            rmse : f64::NAN,
+        };
+        complexity_fit.r_squared = r_squared(&points, |x| complexity_fit.predict(x));
+        let square_error : f64 = points.iter().map(|(x, y)| (y - complexity_fit.predict(*x)).powi(2)).sum();
+        complexity_fit.rmse = (square_error / points.len() as f64).sqrt();
+        Some(complexity_fit)
+    }).collect();
+    // stable sort : the classes are already from the slowest growing to the fastest
+    fits.sort_by(|a, b| a.rmse.total_cmp(&b.rmse));
+    fits
+}
+
+/// fit the candidate complexity classes on each serie (x is the input size), in the legend order
+/// NOTE : the series without usable points are skipped
+pub fn analyse_complexity(series : &PlotSeries) -> ComplexityTable {
+    let rows = series.iter().filter_map(|(legend, serie)| {
+        let fits = fit_complexity_classes(serie);
+        if fits.is_empty() { None } else { Some((legend.clone(), fits)) }
+    }).collect();
+    ComplexityTable {
+        rows,
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_series() -> PlotSeries {
+        let mut series = PlotSeries::new();
+        for n in [10.0_f64, 20.0, 50.0, 100.0, 200.0, 500.0, 1000.0] {
+            series.add("quadratic".to_string(), (n, 0.5 * n * n + 3.0));
+            series.add("linearithmic".to_string(), (n, 2.0 * n * n.ln()));
+            series.add("constant".to_string(), (n, 7.0));
+            series.add("logarithmic".to_string(), (n, 4.0 * n.ln() + 1.0));
+        }
+        series
+    }
+
+    #[test]
+    fn test_analyse_complexity_best_class() {
+        let table = analyse_complexity(&get_series());
+        assert_eq!(table.get_best("quadratic").unwrap().class, ComplexityClass::Quadratic);
+        assert_eq!(table.get_best("linearithmic").unwrap().class, ComplexityClass::Linearithmic);
+        assert_eq!(table.get_best("constant").unwrap().class, ComplexityClass::Constant);
+        assert_eq!(table.get_best("logarithmic").unwrap().class, ComplexityClass::Logarithmic);
+        assert!(table.get_best("unknown").is_none());
+
+        let quadratic = table.get_best("quadratic").unwrap();
+        assert!((quadratic.slope - 0.5).abs() < 1e-3);
+        assert!((quadratic.r_squared - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_complexity_table_to_array() {
+        let array : Array = analyse_complexity(&get_series()).into();
+        assert_eq!(array.get_header().len(), 7);
+        assert_eq!(array.get_data().len(), 4 * ComplexityClass::ALL.len());
+        assert_eq!(array.get_data()[0][0], "quadratic");
+        assert_eq!(array.get_data()[0][1], "1");
+        assert_eq!(array.get_data()[0][2], "O(n²)");
+    }
+
+    #[test]
+    fn test_fit_complexity_classes_without_points() {
+        assert!(fit_complexity_classes(&[(0.0, 1.0), (-1.0, 2.0)]).is_empty());
+    }
+}
@@ -0,0 +1,99 @@
+use std::fmt::{Display, Formatter};
+
+/// the correlation coefficient to compute
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CorrelationMethod {
+    /// the linear correlation
+    Pearson,
+    /// the correlation of the ranks (monotonic relation)
+    Spearman,
+}
+
+impl Display for CorrelationMethod {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CorrelationMethod::Pearson => write!(f, "pearson"),
+            CorrelationMethod::Spearman => write!(f, "spearman"),
+        }
+    }
+}
+
+impl CorrelationMethod {
+    /// get the correlation coefficient of the two series
+    /// NOTE : only the pairs of finite values are used, NaN if there are less than 2 pairs or a serie is constant
+    pub fn correlation(&self, x : &[f64], y : &[f64]) -> f64 {
+        let (x, y) : (Vec<f64>, Vec<f64>) = x.iter().zip(y.iter())
+            .filter(|(x, y)| x.is_finite() && y.is_finite())
+            .map(|(x, y)| (*x, *y))
+            .unzip();
+        match self {
+            CorrelationMethod::Pearson => pearson(&x, &y),
+            CorrelationMethod::Spearman => pearson(&average_ranks(&x), &average_ranks(&y)),
+        }
+    }
+}
+
+fn pearson(x : &[f64], y : &[f64]) -> f64 {
+    if x.len() < 2 {
+        return f64::NAN;
+    }
+    let n = x.len() as f64;
+    let (mean_x, mean_y) = (x.iter().sum::<f64>() / n, y.iter().sum::<f64>() / n);
+    let (mut covariance, mut variance_x, mut variance_y) = (0.0, 0.0, 0.0);
+    for (x, y) in x.iter().zip(y.iter()) {
+        covariance += (x - mean_x) * (y - mean_y);
+        variance_x += (x - mean_x).powi(2);
+        variance_y += (y - mean_y).powi(2);
+    }
+    if variance_x == 0.0 || variance_y == 0.0 {
+        return f64::NAN;
+    }
+    (covariance / (variance_x * variance_y).sqrt()).clamp(-1.0, 1.0)
+}
+
+/// get the rank of each value (starting at 1), the tied values get the average of their ranks
+pub(crate) fn average_ranks(values : &[f64]) -> Vec<f64> {
+    let mut indexes : Vec<usize> = (0..values.len()).collect();
+    indexes.sort_by(|a, b| values[*a].total_cmp(&values[*b]));
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+    while start < indexes.len() {
+        let mut end = start;
+        while end + 1 < indexes.len() && values[indexes[end + 1]] == values[indexes[start]] {
+            end += 1;
+        }
+        let average_rank = (start + end) as f64 / 2.0 + 1.0;
+        for index in indexes[start..=end].iter() {
+            ranks[*index] = average_rank;
+        }
+        start = end + 1;
+    }
+    ranks
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_pearson() {
+        let x = [1.0, 2.0, 3.0, 4.0];
+        assert_relative_eq!(CorrelationMethod::Pearson.correlation(&x, &[2.0, 4.0, 6.0, 8.0]), 1.0);
+        assert_relative_eq!(CorrelationMethod::Pearson.correlation(&x, &[8.0, 6.0, 4.0, 2.0]), -1.0);
+        assert_relative_eq!(CorrelationMethod::Pearson.correlation(&x, &[1.0, 3.0, 2.0, 4.0]), 0.8);
+        assert!(CorrelationMethod::Pearson.correlation(&x, &[1.0, 1.0, 1.0, 1.0]).is_nan());
+        // the NaN pairs are ignored
+        assert_relative_eq!(CorrelationMethod::Pearson.correlation(&[1.0, 2.0, f64::NAN, 3.0], &[1.0, 2.0, 5.0, 3.0]), 1.0);
+    }
+
+    #[test]
+    fn test_spearman() {
+        let x = [1.0, 2.0, 3.0, 4.0, 5.0];
+        // monotonic but not linear
+        assert_relative_eq!(CorrelationMethod::Spearman.correlation(&x, &[1.0, 4.0, 9.0, 16.0, 100.0]), 1.0);
+        assert!(CorrelationMethod::Pearson.correlation(&x, &[1.0, 4.0, 9.0, 16.0, 100.0]) < 1.0);
+        assert_eq!(average_ranks(&[3.0, 1.0, 3.0, 2.0]), vec![3.5, 1.0, 3.5, 2.0]);
+    }
+}
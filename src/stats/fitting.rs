@@ -0,0 +1,358 @@
+use std::fmt::{Display, Formatter};
+
+use crate::error::PlotHelperError;
+use crate::data::plot_data::PlotSeries;
+use crate::static_html::presentation_data::TextContent;
+
+
+/// the model fitted on a serie of points
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FitModel {
+    /// y = a + b * x
+    Linear,
+    /// y = c0 + c1 * x + ... + cn * x^n, with the given degree n
+    Polynomial(usize),
+    /// y = a * x^b (linear fit in the log-log space, only the points with x > 0 and y > 0 are used)
+    PowerLaw,
+    /// local weighted linear regression, with the given fraction of the points used for each local fit (between 0 and 1)
+    Loess(f64),
+}
+
+impl Display for FitModel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FitModel::Linear => write!(f, "linear"),
+            FitModel::Polynomial(degree) => write!(f, "polynomial (degree {})", degree),
+            FitModel::PowerLaw => write!(f, "power law"),
+            FitModel::Loess(span) => write!(f, "loess (span {})", span),
+        }
+    }
+}
+
+/// the result of a fit
+/// coefficients : the coefficients of the model (by increasing degree for a polynomial, [a, b] for a power law, empty for loess)
+/// r_squared : the coefficient of determination, computed on the original points
+/// smoothed : the smoothed points, sorted by x (loess only)
+#[derive(Debug, Clone, PartialEq)]
+pub struct FitResult {
+    model : FitModel,
+    coefficients : Vec<f64>,
+    r_squared : f64,
+    smoothed : Vec<(f64, f64)>,
+}
+
+impl FitResult {
+    pub fn get_model(&self) -> FitModel {
+        self.model
+    }
+
+    pub fn get_coefficients(&self) -> &Vec<f64> {
+        &self.coefficients
+    }
+
+    pub fn get_r_squared(&self) -> f64 {
+        self.r_squared
+    }
+
+    /// get the value of the fitted model at x
+    /// NOTE : the loess curve is linearly interpolated between the smoothed points (constant outside)
+    pub fn predict(&self, x : f64) -> f64 {
+        match self.model {
+            FitModel::Linear | FitModel::Polynomial(_) => {
+                self.coefficients.iter().rev().fold(0.0, |acc, coefficient| acc * x + coefficient)
+            },
+            FitModel::PowerLaw => self.coefficients[0] * x.powf(self.coefficients[1]),
+            FitModel::Loess(_) => interpolate(&self.smoothed, x),
+        }
+    }
+
+    /// get the equation of the fitted model, as text
+    pub fn get_equation(&self) -> String {
+        match self.model {
+            FitModel::Linear | FitModel::Polynomial(_) => {
+                let terms = self.coefficients.iter().enumerate().rev().map(|(degree, coefficient)| match degree {
+                    0 => format!("{:.4e}", coefficient),
+                    1 => format!("{:.4e} x", coefficient),
+                    _ => format!("{:.4e} x^{}", coefficient, degree),
+                }).collect::<Vec<_>>();
+                format!("y = {}", terms.join(" + "))
+            },
+            FitModel::PowerLaw => format!("y = {:.4e} x^{:.4}", self.coefficients[0], self.coefficients[1]),
+            FitModel::Loess(span) => format!("loess smoothing (span {})", span),
+        }
+    }
+}
+
+impl Display for FitResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} : {} (R² = {:.4})", self.model, self.get_equation(), self.r_squared)
+    }
+}
+
+/// NOTE : to insert the fit in an html report
+impl From<&FitResult> for TextContent {
+    fn from(fit : &FitResult) -> TextContent {
+        TextContent::Raw(fit.to_string())
+    }
+}
+
+/// fit the given model on the points
+/// NOTE : return an error if there are not enough (usable) points for the model
+pub fn fit(points : &[(f64, f64)], model : FitModel) -> Result<FitResult, PlotHelperError> {
+    let points : Vec<(f64, f64)> = points.iter().copied().filter(|(x, y)| x.is_finite() && y.is_finite()).collect();
+    let (coefficients, smoothed) = match model {
+        FitModel::Linear => (fit_polynomial(&points, 1)?, Vec::new()),
+        FitModel::Polynomial(degree) => (fit_polynomial(&points, degree)?, Vec::new()),
+        FitModel::PowerLaw => {
+            let log_points : Vec<(f64, f64)> = points.iter()
+                .filter(|(x, y)| *x > 0.0 && *y > 0.0)
+                .map(|(x, y)| (x.ln(), y.ln()))
+                .collect();
+            let coefficients = fit_polynomial(&log_points, 1)?;
+            (vec![coefficients[0].exp(), coefficients[1]], Vec::new())
+        },
+        FitModel::Loess(span) => (Vec::new(), fit_loess(&points, span)?),
+    };
+    let mut result = FitResult {
+        model,
+        coefficients,
+        r_squared : f64::NAN,
+        smoothed,
+    };
+    result.r_squared = r_squared(&points, |x| result.predict(x));
+    Ok(result)
+}
+
+/// fit the given model on each serie, in the legend order (the series without enough points are skipped)
+pub fn fit_series(series : &PlotSeries, model : FitModel) -> Vec<(String, FitResult)> {
+    series.iter().filter_map(|(legend, serie)| {
+        fit(serie, model).ok().map(|result| (legend.clone(), result))
+    }).collect()
+}
+
+/// least squares polynomial fit, return the coefficients by increasing degree
+/// NOTE : the x values are centred and scaled (x' = (x - mean) / std) to keep the normal equations well conditioned,
+/// the coefficients are transformed back to the x values
+fn fit_polynomial(points : &[(f64, f64)], degree : usize) -> Result<Vec<f64>, PlotHelperError> {
+    let nb_of_coefficients = degree + 1;
+    if points.len() < nb_of_coefficients {
+        return Err(PlotHelperError::EmptyData(format!("at least {} points are needed to fit a polynomial of degree {} ({} given)", nb_of_coefficients, degree, points.len())));
+    }
+    let singular_error = || PlotHelperError::InvalidParameter("the points can't be fitted (singular system)".to_string());
+    let mean = points.iter().map(|(x, _)| x).sum::<f64>() / points.len() as f64;
+    let std = (points.iter().map(|(x, _)| (x - mean).powi(2)).sum::<f64>() / points.len() as f64).sqrt();
+    let (mean, std) = match degree {
+        0 => (0.0, 1.0),
+        _ if std > 0.0 && std.is_finite() => (mean, std),
+        _ => return Err(singular_error()),
+    };
+
+    // normal equations : (X^T X) c = X^T y
+    let mut matrix = vec![vec![0.0; nb_of_coefficients + 1]; nb_of_coefficients];
+    for (x, y) in points.iter() {
+        let scaled_x = (x - mean) / std;
+        let powers : Vec<f64> = (0..nb_of_coefficients).map(|i| scaled_x.powi(i as i32)).collect();
+        for row in 0..nb_of_coefficients {
+            for col in 0..nb_of_coefficients {
+                matrix[row][col] += powers[row] * powers[col];
+            }
+            matrix[row][nb_of_coefficients] += powers[row] * y;
+        }
+    }
+    let scaled_coefficients = solve_linear_system(matrix).ok_or_else(singular_error)?;
+
+    // sum of b_k ((x - mean) / std)^k, expanded with the binomial theorem : a_j = sum over k >= j of b_k C(k, j) (-mean)^(k - j) / std^k
+    let mut coefficients = vec![0.0; nb_of_coefficients];
+    for (k, b) in scaled_coefficients.iter().enumerate() {
+        let mut binomial = 1.0;
+        for (j, coefficient) in coefficients.iter_mut().enumerate().take(k + 1) {
+            *coefficient += b * binomial * (-mean).powi((k - j) as i32) / std.powi(k as i32);
+            binomial = binomial * (k - j) as f64 / (j + 1) as f64;
+        }
+    }
+    Ok(coefficients)
+}
+
+/// solve the linear system given as an augmented matrix, with the gaussian elimination (None if singular)
+pub(crate) fn solve_linear_system(mut matrix : Vec<Vec<f64>>) -> Option<Vec<f64>> {
+    let size = matrix.len();
+    // the pivots are compared to the magnitude of the matrix to detect the singularity
+    let scale = matrix.iter().flatten().fold(0.0_f64, |max, value| max.max(value.abs()));
+    for col in 0..size {
+        // partial pivoting
+        let pivot = (col..size).max_by(|a, b| matrix[*a][col].abs().total_cmp(&matrix[*b][col].abs()))?;
+        if matrix[pivot][col].abs() <= scale * 1e-12 {
+            return None;
+        }
+        matrix.swap(col, pivot);
+        let pivot_row = matrix[col].clone();
+        for row in matrix.iter_mut().skip(col + 1) {
+            let factor = row[col] / pivot_row[col];
+            for (value, pivot_value) in row.iter_mut().zip(pivot_row.iter()).skip(col) {
+                *value -= factor * pivot_value;
+            }
+        }
+    }
+    let mut solution = vec![0.0; size];
+    for row in (0..size).rev() {
+        let sum : f64 = ((row + 1)..size).map(|k| matrix[row][k] * solution[k]).sum();
+        solution[row] = (matrix[row][size] - sum) / matrix[row][row];
+    }
+    Some(solution)
+}
+
+/// smooth the points with a local linear regression weighted by the tricube kernel
+/// return the smoothed value at each unique x, sorted by x
+fn fit_loess(points : &[(f64, f64)], span : f64) -> Result<Vec<(f64, f64)>, PlotHelperError> {
+    if points.len() < 2 {
+        return Err(PlotHelperError::EmptyData(format!("at least 2 points are needed for a loess smoothing ({} given)", points.len())));
+    }
+    let nb_of_neighbours = ((span.clamp(0.0, 1.0) * points.len() as f64).ceil() as usize).clamp(2, points.len());
+    let mut sorted_points = points.to_vec();
+    sorted_points.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut xs : Vec<f64> = sorted_points.iter().map(|(x, _)| *x).collect();
+    xs.dedup();
+
+    // the neighbours of x0 are the window [start, start + nb_of_neighbours) of the sorted points, it slides with x0
+    let mut start = 0;
+    Ok(xs.into_iter().map(|x0| {
+        while start + nb_of_neighbours < sorted_points.len() && x0 - sorted_points[start].0 > sorted_points[start + nb_of_neighbours].0 - x0 {
+            start += 1;
+        }
+        let neighbours = &sorted_points[start..start + nb_of_neighbours];
+        // the distance to the farthest neighbour
+        let max_distance = (x0 - neighbours[0].0).max(neighbours[nb_of_neighbours - 1].0 - x0).max(f64::EPSILON);
+
+        // weighted linear regression, centred on x0 (the intercept is the smoothed value)
+        let (mut sum_w, mut sum_wx, mut sum_wy, mut sum_wxx, mut sum_wxy) = (0.0, 0.0, 0.0, 0.0, 0.0);
+        for (x, y) in neighbours.iter() {
+            let dx = x - x0;
+            let ratio = dx.abs() / max_distance;
+            if ratio >= 1.0 {
+                continue;
+            }
+            let w = (1.0 - ratio.powi(3)).powi(3);
+            sum_w += w;
+            sum_wx += w * dx;
+            sum_wy += w * y;
+            sum_wxx += w * dx * dx;
+            sum_wxy += w * dx * y;
+        }
+        let denominator = sum_w * sum_wxx - sum_wx * sum_wx;
+        let y0 = if denominator.abs() < 1e-12 * (sum_w * sum_wxx).max(f64::MIN_POSITIVE) {
+            sum_wy / sum_w // all the neighbours have the same x : weighted mean
+        } else {
+            let slope = (sum_w * sum_wxy - sum_wx * sum_wy) / denominator;
+            (sum_wy - slope * sum_wx) / sum_w
+        };
+        (x0, y0)
+    }).collect())
+}
+
+/// linear interpolation between the given points (sorted by x), constant outside
+fn interpolate(points : &[(f64, f64)], x : f64) -> f64 {
+    match points.iter().position(|(px, _)| *px >= x) {
+        None => points.last().map(|(_, y)| *y).unwrap_or(f64::NAN),
+        Some(0) => points[0].1,
+        Some(index) => {
+            let ((x0, y0), (x1, y1)) = (points[index - 1], points[index]);
+            y0 + (y1 - y0) * (x - x0) / (x1 - x0)
+        },
+    }
+}
+
+/// the coefficient of determination of the prediction (NaN if the y values are constant)
+pub(crate) fn r_squared(points : &[(f64, f64)], predict : impl Fn(f64) -> f64) -> f64 {
+    let mean = points.iter().map(|(_, y)| y).sum::<f64>() / points.len() as f64;
+    let total : f64 = points.iter().map(|(_, y)| (y - mean).powi(2)).sum();
+    let residual : f64 = points.iter().map(|(x, y)| (y - predict(*x)).powi(2)).sum();
+    if total == 0.0 {
+        return f64::NAN;
+    }
+    1.0 - residual / total
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_fit_linear() {
+        let points : Vec<(f64, f64)> = (0..10).map(|x| (x as f64, 2.0 * x as f64 + 1.0)).collect();
+        let result = fit(&points, FitModel::Linear).unwrap();
+        assert_relative_eq!(result.get_coefficients()[0], 1.0, epsilon = 1e-9);
+        assert_relative_eq!(result.get_coefficients()[1], 2.0, epsilon = 1e-9);
+        assert_relative_eq!(result.get_r_squared(), 1.0, epsilon = 1e-9);
+        assert_relative_eq!(result.predict(20.0), 41.0, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_fit_polynomial() {
+        let points : Vec<(f64, f64)> = (-5..6).map(|x| (x as f64, 3.0 * (x * x) as f64 - x as f64 + 2.0)).collect();
+        let result = fit(&points, FitModel::Polynomial(2)).unwrap();
+        for (coefficient, expected) in result.get_coefficients().iter().zip([2.0, -1.0, 3.0]) {
+            assert_relative_eq!(*coefficient, expected, epsilon = 1e-8);
+        }
+        assert!(fit(&points[..2], FitModel::Polynomial(2)).is_err());
+    }
+
+    #[test]
+    fn test_fit_polynomial_large_x() {
+        // 50 points from 2e5 to 1e7 : the raw powers of x would give a singular system
+        let xs : Vec<f64> = (0..50).map(|i| 2e5 + i as f64 * (1e7 - 2e5) / 49.0).collect();
+        let polynomial = |x : f64| 1e-12 * x.powi(3) - 2e-5 * x.powi(2) + 3.0 * x + 7.0;
+        let points : Vec<(f64, f64)> = xs.iter().map(|x| (*x, polynomial(*x))).collect();
+        for degree in [2, 3] {
+            let result = fit(&points, FitModel::Polynomial(degree)).unwrap();
+            assert!(result.get_r_squared() > 0.99);
+        }
+        let result = fit(&points, FitModel::Polynomial(3)).unwrap();
+        for (x, y) in points.iter() {
+            assert_relative_eq!(result.predict(*x), *y, max_relative = 1e-6);
+        }
+        assert!(fit(&[(1.0, 1.0), (1.0, 2.0)], FitModel::Linear).is_err());
+    }
+
+    #[test]
+    fn test_fit_power_law() {
+        let points : Vec<(f64, f64)> = (1..20).map(|x| (x as f64, 0.5 * (x as f64).powf(1.5))).collect();
+        let result = fit(&points, FitModel::PowerLaw).unwrap();
+        assert_relative_eq!(result.get_coefficients()[0], 0.5, epsilon = 1e-9);
+        assert_relative_eq!(result.get_coefficients()[1], 1.5, epsilon = 1e-9);
+        assert_relative_eq!(result.get_r_squared(), 1.0, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn test_fit_loess() {
+        // a line is reproduced by the local linear regression
+        let points : Vec<(f64, f64)> = (0..20).map(|x| (x as f64, 3.0 * x as f64)).collect();
+        let result = fit(&points, FitModel::Loess(0.3)).unwrap();
+        assert_relative_eq!(result.predict(5.0), 15.0, epsilon = 1e-9);
+        assert_relative_eq!(result.predict(5.5), 16.5, epsilon = 1e-9);
+        assert_relative_eq!(result.get_r_squared(), 1.0, epsilon = 1e-9);
+        assert!(result.get_coefficients().is_empty());
+
+        // large and unevenly spaced x, with duplicated x : the window of neighbours slides with x
+        let points : Vec<(f64, f64)> = (0..200).map(|i| 1e6 + (i / 2) as f64 * (i / 2) as f64).map(|x| (x, 2.0 * x - 5.0)).collect();
+        let result = fit(&points, FitModel::Loess(0.1)).unwrap();
+        for (x, y) in points.iter() {
+            assert_relative_eq!(result.predict(*x), *y, max_relative = 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_fit_series_and_text() {
+        let mut series = PlotSeries::new();
+        for x in 1..5 {
+            series.add("b".to_string(), (x as f64, x as f64));
+            series.add("a".to_string(), (x as f64, 2.0 * x as f64 + 1.0));
+        }
+        series.add("c".to_string(), (1.0, 1.0));
+        let fits = fit_series(&series, FitModel::Linear);
+        assert_eq!(fits.iter().map(|(legend, _)| legend.as_str()).collect::<Vec<_>>(), vec!["b", "a"]);
+        let text : TextContent = (&fits[1].1).into();
+        assert_eq!(text, TextContent::Raw("linear : y = 2.0000e0 x + 1.0000e0 (R² = 1.0000)".to_string()));
+    }
+}
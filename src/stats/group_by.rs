@@ -0,0 +1,194 @@
+use std::cmp::Ordering;
+use std::path::Path;
+
+use crate::error::PlotHelperError;
+use crate::static_html::presentation_data::Array;
+
+/// number of decimals used when the table is converted into an html array
+const ARRAY_PRECISION : usize = 2;
+
+/// represent a row of a group by table
+/// groups : the value of each grouping key (in the order of the group headers)
+/// values : the aggregated values (in the order of the value headers)
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupByRow {
+    pub groups : Vec<String>,
+    pub values : Vec<f64>,
+}
+
+/// represent the result of a group by : one row per unique combination of the grouping keys,
+/// and one column per aggregation
+/// NOTE : the rows are sorted by the groups at the creation (numeric groups are sorted numerically)
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupByTable {
+    group_headers : Vec<String>,
+    /// if the group at the same index is numeric (sorted as a number)
+    numeric_groups : Vec<bool>,
+    value_headers : Vec<String>,
+    rows : Vec<GroupByRow>,
+}
+
+impl GroupByTable {
+    pub fn new(group_headers : Vec<String>, numeric_groups : Vec<bool>, value_headers : Vec<String>, rows : Vec<GroupByRow>) -> Self {
+        let mut table = Self {
+            group_headers,
+            numeric_groups,
+            value_headers,
+            rows,
+        };
+        table.sort_by_groups();
+        table
+    }
+
+    /// sort the rows by the groups, the first group is the most significant
+    pub fn sort_by_groups(&mut self) -> &mut Self {
+        let numeric_groups = &self.numeric_groups;
+        self.rows.sort_by(|a, b| {
+            for (i, (group_a, group_b)) in a.groups.iter().zip(b.groups.iter()).enumerate() {
+                let ordering = compare_group(group_a, group_b, numeric_groups.get(i).copied().unwrap_or(false));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            Ordering::Equal
+        });
+        self
+    }
+
+    /// sort the rows by the value of the given aggregation column (stable, NaN are put at the end)
+    /// NOTE : return an error if there is no aggregation column at this index
+    pub fn sort_by_value(&mut self, value_index : usize, descending : bool) -> Result<&mut Self, PlotHelperError> {
+        if value_index >= self.value_headers.len() {
+            return Err(PlotHelperError::InvalidParameter(format!("no aggregation column at the index {} (the table has {})", value_index, self.value_headers.len())));
+        }
+        self.rows.sort_by(|a, b| {
+            let (value_a, value_b) = (a.values[value_index], b.values[value_index]);
+            match (value_a.is_nan(), value_b.is_nan()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Greater,
+                (false, true) => Ordering::Less,
+                (false, false) => {
+                    let ordering = value_a.partial_cmp(&value_b).unwrap();
+                    if descending { ordering.reverse() } else { ordering }
+                }
+            }
+        });
+        Ok(self)
+    }
+
+    pub fn get_group_headers(&self) -> &Vec<String> {
+        &self.group_headers
+    }
+
+    pub fn get_value_headers(&self) -> &Vec<String> {
+        &self.value_headers
+    }
+
+    /// get all the headers (groups then values)
+    pub fn get_headers(&self) -> Vec<String> {
+        self.group_headers.iter().chain(self.value_headers.iter()).cloned().collect()
+    }
+
+    pub fn get_rows(&self) -> &Vec<GroupByRow> {
+        &self.rows
+    }
+
+    /// convert the table to an html array, with the given number of decimals
+    pub fn to_array(&self, precision : usize) -> Array {
+        let data = self.rows.iter().map(|row| {
+            row.groups.iter().cloned()
+                .chain(row.values.iter().map(|v| format!("{:.*}", precision, v)))
+                .collect()
+        }).collect();
+        Array::new(self.get_headers(), data)
+    }
+
+    /// save the table in a csv file (with the full precision), the first line is the header
+    /// NOTE : the file can be loaded back as an Array with Array::from_csv
+    pub fn save_to_csv(&self, dir_path : &str, file_name : &str) -> Result<(), PlotHelperError> {
+        let path = Path::new(dir_path).join(format!("{}.csv", file_name));
+        let mut writer = csv::Writer::from_path(path)?;
+        writer.write_record(self.get_headers())?;
+        for row in self.rows.iter() {
+            writer.write_record(
+                row.groups.iter().cloned().chain(row.values.iter().map(|v| v.to_string()))
+            )?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+impl From<&GroupByTable> for Array {
+    fn from(table : &GroupByTable) -> Array {
+        table.to_array(ARRAY_PRECISION)
+    }
+}
+
+impl From<GroupByTable> for Array {
+    fn from(table : GroupByTable) -> Array {
+        table.to_array(ARRAY_PRECISION)
+    }
+}
+
+/// compare two group values, as number if numeric (fallback on the string order if the parsing fail)
+fn compare_group(a : &str, b : &str, numeric : bool) -> Ordering {
+    if numeric {
+        if let (Ok(a_number), Ok(b_number)) = (a.parse::<f64>(), b.parse::<f64>()) {
+            if let Some(ordering) = a_number.partial_cmp(&b_number) {
+                return ordering;
+            }
+        }
+    }
+    a.cmp(b)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_table() -> GroupByTable {
+        GroupByTable::new(
+            vec!["tool".to_string(), "size".to_string()],
+            vec![false, true],
+            vec!["time (mean)".to_string()],
+            vec![
+                GroupByRow { groups : vec!["b".to_string(), "10".to_string()], values : vec![3.0] },
+                GroupByRow { groups : vec!["a".to_string(), "10".to_string()], values : vec![f64::NAN] },
+                GroupByRow { groups : vec!["a".to_string(), "9".to_string()], values : vec![1.0] },
+                GroupByRow { groups : vec!["b".to_string(), "2".to_string()], values : vec![2.0] },
+            ],
+        )
+    }
+
+    #[test]
+    fn test_group_by_table_sorted_by_groups() {
+        let table = get_table();
+        let groups = table.get_rows().iter().map(|r| r.groups.join("-")).collect::<Vec<_>>();
+        assert_eq!(groups, vec!["a-9", "a-10", "b-2", "b-10"]);
+    }
+
+    #[test]
+    fn test_group_by_table_sort_by_value() {
+        let mut table = get_table();
+        table.sort_by_value(0, true).unwrap();
+        let groups = table.get_rows().iter().map(|r| r.groups.join("-")).collect::<Vec<_>>();
+        assert_eq!(groups, vec!["b-10", "b-2", "a-9", "a-10"]);
+
+        table.sort_by_value(0, false).unwrap();
+        let groups = table.get_rows().iter().map(|r| r.groups.join("-")).collect::<Vec<_>>();
+        assert_eq!(groups, vec!["a-9", "b-2", "b-10", "a-10"]);
+
+        // no aggregation column at this index
+        assert!(matches!(table.sort_by_value(1, false), Err(PlotHelperError::InvalidParameter(_))));
+    }
+
+    #[test]
+    fn test_group_by_table_to_array() {
+        let array : Array = get_table().into();
+        assert_eq!(array.get_header(), &vec!["tool".to_string(), "size".to_string(), "time (mean)".to_string()]);
+        assert_eq!(array.get_data()[0], vec!["a".to_string(), "9".to_string(), "1.00".to_string()]);
+        assert_eq!(array.get_data()[1][2], "NaN");
+    }
+}
@@ -1,10 +1,20 @@
 
 pub mod stats_serie;
+pub mod group_by;
+pub mod fitting;
+pub mod complexity;
+pub mod random;
+pub mod bootstrap;
+pub mod correlation;
+pub mod significance;
+pub mod regression_detection;
+pub mod streaming;
 
 /// get the outliers of the given data (Mask)
 /// return the vector of bool, true if the corresponding data is an outlier
+/// NOTE : an empty serie has no outlier, and neither has a constant serie (or a single value) since its bounds are the value itself
 pub fn get_outliers(
-    data_to_filter: &Vec<f32>,
+    data_to_filter: &Vec<f64>,
 ) -> Vec<bool>
 {
 
@@ -21,17 +31,18 @@ pub fn get_outliers(
 }
 
 /// use the inverted_cdf method to get the q1 and q3
-pub fn calculate_q1_q3(data: &Vec<f32>) -> (f32, f32) {
+/// NOTE : the quartiles of an empty serie are NaN (so no value is out of the bounds)
+pub fn calculate_q1_q3(data: &Vec<f64>) -> (f64, f64) {
     if data.len() == 0 {
-        return (f32::NAN, f32::NAN);
+        return (f64::NAN, f64::NAN);
     }
 
     // Sort the data first
     let mut sorted_data = data.clone();
-    sorted_data.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    sorted_data.sort_by(|a, b| a.total_cmp(b));
 
-    let freq_q1 = (data.len() as f32) / 4.0;
-    let freq_q3 = (data.len() as f32) * 3.0 / 4.0;
+    let freq_q1 = (data.len() as f64) / 4.0;
+    let freq_q3 = (data.len() as f64) * 3.0 / 4.0;
 
     let q1_index = freq_q1.ceil() as usize - 1;
 
@@ -46,7 +57,7 @@ pub fn calculate_q1_q3(data: &Vec<f32>) -> (f32, f32) {
 }
 
 
-pub fn calculate_bounds(q1_q3: &(f32, f32)) -> (f32, f32)
+pub fn calculate_bounds(q1_q3: &(f64, f64)) -> (f64, f64)
 {
     // Calculate the lower and upper bounds for outliers
     let iqr = q1_q3.1.clone() - q1_q3.0.clone();
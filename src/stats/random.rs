@@ -0,0 +1,75 @@
+/// a small seeded pseudo random generator (SplitMix64), to get reproducible resamplings
+/// NOTE : not suitable for cryptography
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SplitMix64 {
+    state : u64,
+}
+
+impl SplitMix64 {
+    pub fn new(seed : u64) -> Self {
+        Self {
+            state : seed,
+        }
+    }
+
+    /// create the generator of the given stream of the seed, the streams of all the (seed, stream) are independent
+    /// NOTE : the seed is mixed before the stream is combined, so that two seeds never share their streams
+    pub fn from_stream(seed : u64, stream : u64) -> Self {
+        let mixed_seed = Self::new(seed).next_u64();
+        Self::new(Self::new(mixed_seed ^ stream).next_u64())
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// get a random float in [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64
+    }
+
+    /// get a random index in [0, len)
+    pub fn next_index(&mut self, len : usize) -> usize {
+        ((self.next_u64() as u128 * len as u128) >> 64) as usize
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_mix_64_reproducible() {
+        let mut a = SplitMix64::new(42);
+        let mut b = SplitMix64::new(42);
+        let values = (0..10).map(|_| a.next_u64()).collect::<Vec<_>>();
+        assert_eq!(values, (0..10).map(|_| b.next_u64()).collect::<Vec<_>>());
+        assert_ne!(SplitMix64::new(43).next_u64(), values[0]);
+        // reference value of the algorithm
+        assert_eq!(SplitMix64::new(0).next_u64(), 0xE220_A839_7B1D_CDAF);
+    }
+
+    #[test]
+    fn test_split_mix_64_streams() {
+        // the streams of two small seeds are different (the seed ^ stream would give the same set of generators)
+        let streams = |seed| (0..256).map(|stream| SplitMix64::from_stream(seed, stream).next_u64()).collect::<Vec<_>>();
+        let (a, b) = (streams(0), streams(1));
+        assert!(a.iter().all(|value| !b.contains(value)));
+        assert_eq!(SplitMix64::from_stream(3, 5), SplitMix64::from_stream(3, 5));
+    }
+
+    #[test]
+    fn test_split_mix_64_ranges() {
+        let mut rng = SplitMix64::new(7);
+        for _ in 0..1000 {
+            let value = rng.next_f64();
+            assert!((0.0..1.0).contains(&value));
+            assert!(rng.next_index(5) < 5);
+        }
+    }
+}
@@ -0,0 +1,378 @@
+use std::fmt::{Display, Formatter};
+
+use crate::data::datetime::format_datetime_value;
+use crate::data::filtering::Filter;
+use crate::data::plot_data::PlotSeries;
+use crate::data::plottable::key::SerieKey;
+use crate::data::plottable::Plottable;
+use crate::error::PlotHelperError;
+use crate::data::legend_values::{get_legend_values, LegendKeys};
+use crate::static_html::presentation_data::Array;
+
+use super::stats_serie::{MetricName, StatsSerie};
+
+const ARRAY_PRECISION : usize = 2;
+
+/// how the current values of a serie are separated from its baseline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegressionMethod {
+    /// the current values are the last `window` points, the baseline is the `baseline` points before them (all of them if None)
+    LatestWindow {
+        window : usize,
+        baseline : Option<usize>,
+    },
+    /// the serie is split at its most likely change of level (the split minimizing the squared errors to the mean of each segment,
+    /// with at least min_segment points per segment) : the baseline is before the split, the current values after
+    ChangePoint {
+        min_segment : usize,
+    },
+}
+
+/// the configuration of a regression detection
+/// metric : the metric computed on the baseline and on the current values
+/// threshold : the relative change (0.1 for 10%) of the metric above which the change is a regression (or an improvement)
+/// higher_is_worse : true if an increase of the values is a regression (a time), false otherwise (a throughput)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RegressionConfig {
+    pub method : RegressionMethod,
+    pub metric : MetricName,
+    pub threshold : f64,
+    pub higher_is_worse : bool,
+}
+
+impl RegressionConfig {
+    /// a detection with the median, a threshold of 10%, and the higher values as the worse
+    pub fn new(method : RegressionMethod) -> Self {
+        Self {
+            method,
+            metric : MetricName::Median,
+            threshold : 0.1,
+            higher_is_worse : true,
+        }
+    }
+
+    pub fn with_metric(mut self, metric : MetricName) -> Self {
+        self.metric = metric;
+        self
+    }
+
+    pub fn with_threshold(mut self, threshold : f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    pub fn with_higher_is_worse(mut self, higher_is_worse : bool) -> Self {
+        self.higher_is_worse = higher_is_worse;
+        self
+    }
+}
+
+/// the verdict of the comparison of the current values with the baseline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RegressionStatus {
+    Regression,
+    Improvement,
+    Stable,
+    /// the serie is too short for the method
+    NotEnoughData,
+}
+
+impl Display for RegressionStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RegressionStatus::Regression => write!(f, "regression"),
+            RegressionStatus::Improvement => write!(f, "improvement"),
+            RegressionStatus::Stable => write!(f, "stable"),
+            RegressionStatus::NotEnoughData => write!(f, "not enough data"),
+        }
+    }
+}
+
+/// the comparison of the current values of a legend with its baseline
+/// baseline, current : the metric of the baseline and of the current values (NaN if not enough data)
+/// relative_change : (current - baseline) / |baseline|
+/// current_points : the points of the current values, sorted by x
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegressionResult {
+    pub legend : String,
+    pub baseline : f64,
+    pub current : f64,
+    pub relative_change : f64,
+    pub status : RegressionStatus,
+    pub current_points : Vec<(f64, f64)>,
+}
+
+impl RegressionResult {
+    /// get the x of the first current point (the start of the window, or the change point)
+    pub fn get_since(&self) -> Option<f64> {
+        self.current_points.first().map(|(x, _)| *x)
+    }
+}
+
+/// the regressions of all the legends
+/// NOTE : the results are in the insertion order of the legends
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegressionReport {
+    results : Vec<RegressionResult>,
+    metric : MetricName,
+    /// if the x values are dates (formatted as dates in the array)
+    datetime_x : bool,
+    /// the span of the x values, for the precision of the dates
+    x_span : f64,
+}
+
+impl RegressionReport {
+    pub fn get_results(&self) -> &Vec<RegressionResult> {
+        &self.results
+    }
+
+    pub fn get_result(&self, legend : &str) -> Option<&RegressionResult> {
+        self.results.iter().find(|result| result.legend == legend)
+    }
+
+    /// get the results flagged as a regression
+    pub fn get_regressions(&self) -> Vec<&RegressionResult> {
+        self.results.iter().filter(|result| result.status == RegressionStatus::Regression).collect()
+    }
+
+    /// get the current points of the regressions, to highlight them on a plot (see Subplot::with_highlights)
+    pub fn get_highlights(&self) -> Vec<(f64, f64)> {
+        self.get_regressions().iter().flat_map(|result| result.current_points.iter().copied()).collect()
+    }
+
+    /// get the summary of the report : one line per legend
+    pub fn to_array(&self, precision : usize) -> Array {
+        let header = vec![
+            "legend".to_string(),
+            format!("baseline ({})", self.metric),
+            format!("current ({})", self.metric),
+            "change (%)".to_string(),
+            "status".to_string(),
+            "since".to_string(),
+        ];
+        let data = self.results.iter().map(|result| {
+            let since = match result.get_since() {
+                Some(x) if self.datetime_x => format_datetime_value(x, self.x_span),
+                Some(x) => format!("{}", x),
+                None => String::new(),
+            };
+            vec![
+                result.legend.clone(),
+                format!("{:.*}", precision, result.baseline),
+                format!("{:.*}", precision, result.current),
+                format!("{:+.*}", precision, result.relative_change * 100.0),
+                result.status.to_string(),
+                since,
+            ]
+        }).collect();
+        Array::new(header, data)
+    }
+}
+
+impl From<&RegressionReport> for Array {
+    fn from(report : &RegressionReport) -> Self {
+        report.to_array(ARRAY_PRECISION)
+    }
+}
+
+impl From<RegressionReport> for Array {
+    fn from(report : RegressionReport) -> Self {
+        Array::from(&report)
+    }
+}
+
+/// compare the current values of each serie with its baseline (the points are sorted by x, the time)
+/// NOTE : the NaN values are ignored
+pub fn detect_regressions(series : &PlotSeries, config : &RegressionConfig) -> RegressionReport {
+    let mut x_range = (f64::INFINITY, f64::NEG_INFINITY);
+    let results = series.iter().map(|(legend, serie)| {
+        let mut points : Vec<(f64, f64)> = serie.iter().copied().filter(|(x, y)| !x.is_nan() && !y.is_nan()).collect();
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        if let (Some(first), Some(last)) = (points.first(), points.last()) {
+            x_range = (x_range.0.min(first.0), x_range.1.max(last.0));
+        }
+        compare_with_baseline(legend.clone(), points, config)
+    }).collect();
+    RegressionReport {
+        results,
+        metric : config.metric,
+        datetime_x : false,
+        x_span : (x_range.1 - x_range.0).max(0.0),
+    }
+}
+
+/// detect the regressions of the y serie over the x serie (the time) for each legend
+/// NOTE : the y values with the same x value (the runs of a night) are first combined with the metric of the config,
+/// so the windows are counted in x values and the current points are on the line plot drawn with the same metric
+pub fn legend_regressions<Key>(
+    data : &dyn Plottable<Key>,
+    legend : &LegendKeys<Key>,
+    x_serie_key : &Key,
+    y_serie_key : &Key,
+    filters : &Option<Vec<&Filter<Key>>>,
+    config : &RegressionConfig,
+) -> Result<RegressionReport, PlotHelperError>
+where
+    Key : SerieKey,
+{
+    let legends = get_legend_values(data, legend);
+    let series = data.aggregate(x_serie_key, y_serie_key, filters, &legends, &None, Some(config.metric))?;
+    let mut report = detect_regressions(&series, config);
+    report.datetime_x = x_serie_key.is_datetime();
+    Ok(report)
+}
+
+fn compare_with_baseline(legend : String, points : Vec<(f64, f64)>, config : &RegressionConfig) -> RegressionResult {
+    let split = match config.method {
+        RegressionMethod::LatestWindow { window, baseline } => {
+            let window = window.max(1);
+            if points.len() <= window {
+                None
+            } else {
+                let end = points.len() - window;
+                let start = baseline.map(|baseline| end.saturating_sub(baseline.max(1))).unwrap_or(0);
+                Some((start, end))
+            }
+        },
+        RegressionMethod::ChangePoint { min_segment } => {
+            let values : Vec<f64> = points.iter().map(|(_, y)| *y).collect();
+            find_change_point(&values, min_segment.max(1)).map(|split| (0, split))
+        },
+    };
+    let Some((start, end)) = split else {
+        return RegressionResult {
+            legend,
+            baseline : f64::NAN,
+            current : f64::NAN,
+            relative_change : f64::NAN,
+            status : RegressionStatus::NotEnoughData,
+            current_points : Vec::new(),
+        };
+    };
+
+    let get_metric = |points : &[(f64, f64)]| {
+        StatsSerie::new(&points.iter().map(|(_, y)| *y).collect()).get_stats(config.metric).value
+    };
+    let baseline = get_metric(&points[start..end]);
+    let current = get_metric(&points[end..]);
+    let relative_change = get_relative_change(baseline, current);
+    let worsening = if config.higher_is_worse { relative_change } else { -relative_change };
+    let status = if worsening > config.threshold {
+        RegressionStatus::Regression
+    } else if worsening < -config.threshold {
+        RegressionStatus::Improvement
+    } else {
+        RegressionStatus::Stable
+    };
+    RegressionResult {
+        legend,
+        baseline,
+        current,
+        relative_change,
+        status,
+        current_points : points[end..].to_vec(),
+    }
+}
+
+/// get (current - baseline) / |baseline|, infinite if the baseline is 0 (and not the current value)
+fn get_relative_change(baseline : f64, current : f64) -> f64 {
+    if baseline == current {
+        0.0
+    } else if baseline == 0.0 {
+        (current - baseline).signum() * f64::INFINITY
+    } else {
+        (current - baseline) / baseline.abs()
+    }
+}
+
+/// get the index splitting the values in two segments with the minimum sum of squared errors to their mean
+/// NOTE : None if the values can't be split in two segments of min_segment values
+fn find_change_point(values : &[f64], min_segment : usize) -> Option<usize> {
+    if values.len() < 2 * min_segment {
+        return None;
+    }
+    // cumulative sums to get the squared errors of a segment in O(1)
+    let mut sums = vec![0.0; values.len() + 1];
+    let mut squared_sums = vec![0.0; values.len() + 1];
+    for (index, value) in values.iter().enumerate() {
+        sums[index + 1] = sums[index] + value;
+        squared_sums[index + 1] = squared_sums[index] + value * value;
+    }
+    let squared_errors = |start : usize, end : usize| {
+        let (sum, n) = (sums[end] - sums[start], (end - start) as f64);
+        squared_sums[end] - squared_sums[start] - sum * sum / n
+    };
+    (min_segment..=values.len() - min_segment)
+        .min_by(|a, b| {
+            let cost = |split : usize| squared_errors(0, split) + squared_errors(split, values.len());
+            cost(*a).total_cmp(&cost(*b))
+        })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_series() -> PlotSeries {
+        let mut series = PlotSeries::new();
+        // "slow" : 10 then 13 for the last 3 points, "fast" : always 5, "short" : 2 points
+        for x in 0..10 {
+            series.add("slow".to_string(), (x as f64, if x < 7 { 10.0 } else { 13.0 }));
+            series.add("fast".to_string(), (x as f64, 5.0));
+        }
+        series.add("short".to_string(), (0.0, 1.0));
+        series.add("short".to_string(), (1.0, 1.0));
+        series
+    }
+
+    #[test]
+    fn test_latest_window() {
+        let config = RegressionConfig::new(RegressionMethod::LatestWindow { window : 3, baseline : None });
+        let report = detect_regressions(&get_series(), &config);
+
+        let slow = report.get_result("slow").unwrap();
+        assert_eq!((slow.baseline, slow.current), (10.0, 13.0));
+        assert!((slow.relative_change - 0.3).abs() < 1e-9);
+        assert_eq!(slow.status, RegressionStatus::Regression);
+        assert_eq!(slow.get_since(), Some(7.0));
+        assert_eq!(report.get_result("fast").unwrap().status, RegressionStatus::Stable);
+        assert_eq!(report.get_result("short").unwrap().status, RegressionStatus::NotEnoughData);
+        assert_eq!(report.get_highlights(), vec![(7.0, 13.0), (8.0, 13.0), (9.0, 13.0)]);
+
+        // a higher value is better : an improvement
+        let report = detect_regressions(&get_series(), &config.with_higher_is_worse(false));
+        assert_eq!(report.get_result("slow").unwrap().status, RegressionStatus::Improvement);
+        // under the threshold
+        let report = detect_regressions(&get_series(), &config.with_threshold(0.5));
+        assert_eq!(report.get_result("slow").unwrap().status, RegressionStatus::Stable);
+        // a baseline of 2 points, the window starts before the change
+        let config = RegressionConfig::new(RegressionMethod::LatestWindow { window : 4, baseline : Some(2) });
+        let slow = detect_regressions(&get_series(), &config).get_result("slow").unwrap().clone();
+        assert_eq!((slow.baseline, slow.current), (10.0, 13.0));
+    }
+
+    #[test]
+    fn test_change_point() {
+        let config = RegressionConfig::new(RegressionMethod::ChangePoint { min_segment : 2 }).with_metric(MetricName::Mean);
+        let report = detect_regressions(&get_series(), &config);
+        let slow = report.get_result("slow").unwrap();
+        assert_eq!(slow.get_since(), Some(7.0));
+        assert_eq!(slow.status, RegressionStatus::Regression);
+        assert_eq!(report.get_result("short").unwrap().status, RegressionStatus::NotEnoughData);
+
+        assert_eq!(find_change_point(&[1.0, 1.0, 1.0, 5.0, 5.0], 1), Some(3));
+        assert_eq!(find_change_point(&[1.0, 5.0, 5.0, 5.0, 5.0], 2), Some(2));
+        assert_eq!(find_change_point(&[1.0, 5.0, 5.0], 2), None);
+    }
+
+    #[test]
+    fn test_regression_report_array() {
+        let config = RegressionConfig::new(RegressionMethod::LatestWindow { window : 3, baseline : None });
+        let array = detect_regressions(&get_series(), &config).to_array(1);
+        assert_eq!(array.get_header()[1], "baseline (median)");
+        assert_eq!(array.get_data()[0], vec!["slow", "10.0", "13.0", "+30.0", "regression", "7"]);
+        assert_eq!(array.get_data()[2][4], "not enough data");
+        assert_eq!(get_relative_change(0.0, 1.0), f64::INFINITY);
+    }
+}
@@ -0,0 +1,524 @@
+use std::collections::HashMap;
+use std::f64::consts::{PI, SQRT_2};
+
+use crate::error::PlotHelperError;
+use crate::data::filtering::Filter;
+use crate::data::plottable::key::SerieKey;
+use crate::data::plottable::Plottable;
+use crate::data::legend_values::{get_legend_values, LegendKeys};
+use crate::static_html::presentation_data::Array;
+
+use super::random::SplitMix64;
+
+/// number of decimals used when the table is converted into an html array
+const ARRAY_PRECISION : usize = 4;
+/// default number of resamples of the bootstrap test
+pub const DEFAULT_NB_OF_RESAMPLES : usize = 2000;
+
+/// the result of a statistical test
+/// statistic : the statistic of the test (t for Welch, U of the first group for Mann-Whitney, difference of the medians for the bootstrap)
+/// p_value : the two sided p-value (NaN if the test can't be done, e.g. not enough values)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TestResult {
+    pub statistic : f64,
+    pub p_value : f64,
+}
+
+impl TestResult {
+    fn undefined() -> Self {
+        Self {
+            statistic : f64::NAN,
+            p_value : f64::NAN,
+        }
+    }
+
+    /// if the p-value is lower than the given significance level (0.05 for example)
+    pub fn is_significant(&self, level : f64) -> bool {
+        self.p_value < level
+    }
+}
+
+/// the comparison of two groups of values
+/// cohen_d : the difference of the means divided by the pooled standard deviation (a - b)
+/// cliff_delta : P(a > b) - P(a < b), between -1 and 1
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupComparison {
+    pub group_a : String,
+    pub group_b : String,
+    pub welch_t_test : TestResult,
+    pub mann_whitney_u : TestResult,
+    pub bootstrap_median : TestResult,
+    pub cohen_d : f64,
+    pub cliff_delta : f64,
+}
+
+/// the comparisons of each pair of groups
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignificanceTable {
+    comparisons : Vec<GroupComparison>,
+}
+
+impl SignificanceTable {
+    pub fn get_comparisons(&self) -> &Vec<GroupComparison> {
+        &self.comparisons
+    }
+
+    /// get the comparison of the two groups (in any order, the effect sizes are then those of (group_a, group_b) as stored)
+    pub fn get_comparison(&self, group_a : &str, group_b : &str) -> Option<&GroupComparison> {
+        self.comparisons.iter().find(|comparison| {
+            (comparison.group_a == group_a && comparison.group_b == group_b)
+            || (comparison.group_a == group_b && comparison.group_b == group_a)
+        })
+    }
+
+    /// convert the table to an html array (one line per pair of groups), with the given number of decimals
+    pub fn to_array(&self, precision : usize) -> Array {
+        let header = [
+            "group a", "group b",
+            "welch t", "welch p-value",
+            "mann-whitney U", "mann-whitney p-value",
+            "median difference", "bootstrap p-value",
+            "cohen's d", "cliff's delta",
+        ].iter().map(|h| h.to_string()).collect();
+        let format = |value : f64| format!("{:.*}", precision, value);
+        let data = self.comparisons.iter().map(|comparison| vec![
+            comparison.group_a.clone(),
+            comparison.group_b.clone(),
+            format(comparison.welch_t_test.statistic),
+            format(comparison.welch_t_test.p_value),
+            format(comparison.mann_whitney_u.statistic),
+            format(comparison.mann_whitney_u.p_value),
+            format(comparison.bootstrap_median.statistic),
+            format(comparison.bootstrap_median.p_value),
+            format(comparison.cohen_d),
+            format(comparison.cliff_delta),
+        ]).collect();
+        Array::new(header, data)
+    }
+}
+
+impl From<&SignificanceTable> for Array {
+    fn from(table : &SignificanceTable) -> Array {
+        table.to_array(ARRAY_PRECISION)
+    }
+}
+
+impl From<SignificanceTable> for Array {
+    fn from(table : SignificanceTable) -> Array {
+        table.to_array(ARRAY_PRECISION)
+    }
+}
+
+/// compare each pair of groups (in the given order : (0, 1), (0, 2) ..., (1, 2) ...)
+/// nb_of_resamples, seed : the parameters of the bootstrap test (the result is reproducible with the same seed)
+pub fn pairwise_significance(groups : &[(String, Vec<f64>)], nb_of_resamples : usize, seed : u64) -> SignificanceTable {
+    let mut comparisons = Vec::new();
+    for (i, (name_a, values_a)) in groups.iter().enumerate() {
+        for (name_b, values_b) in groups.iter().skip(i + 1) {
+            comparisons.push(compare_groups(name_a, values_a, name_b, values_b, nb_of_resamples, seed));
+        }
+    }
+    SignificanceTable {
+        comparisons,
+    }
+}
+
+/// compare the values of the given key between each pair of legends (in the legend order)
+/// Apply also the filters and remove the outliers, like the plots
+/// nb_of_resamples, seed : the parameters of the bootstrap test (DEFAULT_NB_OF_RESAMPLES for example)
+pub fn legend_significance<Key>(
+    data : &dyn Plottable<Key>,
+    legend : &LegendKeys<Key>,
+    value_key : &Key,
+    filters : &Option<Vec<&Filter<Key>>>,
+    remove_outliers : &Option<Vec<Key>>,
+    nb_of_resamples : usize,
+    seed : u64,
+) -> Result<SignificanceTable, PlotHelperError>
+where
+    Key : SerieKey,
+{
+    if !value_key.is_numeric() {
+        return Err(PlotHelperError::expected_numeric(value_key.get_display_name()));
+    }
+    let filter_mask = data.combine_filter(filters, remove_outliers);
+    let values = filter_mask.apply(&data.get_numeric_series(value_key));
+    let legends = filter_mask.apply(&get_legend_values(data, legend));
+
+    let mut groups : HashMap<String, Vec<f64>> = HashMap::new();
+    for (value, legend) in values.iter().zip(legends.iter()) {
+        groups.entry(legend.clone()).or_default().push(*value);
+    }
+    let mut names : Vec<String> = groups.keys().cloned().collect();
+    legend.order.sort(&mut names);
+    let groups : Vec<(String, Vec<f64>)> = names.into_iter()
+        .map(|name| {
+            let values = groups.remove(&name).unwrap_or_default();
+            (name, values)
+        })
+        .collect();
+
+    Ok(pairwise_significance(&groups, nb_of_resamples, seed))
+}
+
+/// compare two groups of values with all the tests and effect sizes
+pub fn compare_groups(
+    name_a : &str,
+    values_a : &[f64],
+    name_b : &str,
+    values_b : &[f64],
+    nb_of_resamples : usize,
+    seed : u64
+) -> GroupComparison {
+    let a : Vec<f64> = values_a.iter().copied().filter(|v| v.is_finite()).collect();
+    let b : Vec<f64> = values_b.iter().copied().filter(|v| v.is_finite()).collect();
+    GroupComparison {
+        group_a : name_a.to_string(),
+        group_b : name_b.to_string(),
+        welch_t_test : welch_t_test(&a, &b),
+        mann_whitney_u : mann_whitney_u(&a, &b),
+        bootstrap_median : bootstrap_median_difference(&a, &b, nb_of_resamples, seed),
+        cohen_d : cohen_d(&a, &b),
+        cliff_delta : cliff_delta(&a, &b),
+    }
+}
+
+/// Welch's t-test (two sided), the variances of the groups can differ
+pub fn welch_t_test(a : &[f64], b : &[f64]) -> TestResult {
+    if a.len() < 2 || b.len() < 2 {
+        return TestResult::undefined();
+    }
+    let (mean_a, variance_a) = mean_variance(a);
+    let (mean_b, variance_b) = mean_variance(b);
+    let (se_a, se_b) = (variance_a / a.len() as f64, variance_b / b.len() as f64);
+    let standard_error = (se_a + se_b).sqrt();
+    if standard_error == 0.0 {
+        // no variance : the groups are either identical or always different
+        return if mean_a == mean_b {
+            TestResult { statistic : 0.0, p_value : 1.0 }
+        } else {
+            TestResult { statistic : (mean_a - mean_b).signum() * f64::INFINITY, p_value : 0.0 }
+        };
+    }
+    let t = (mean_a - mean_b) / standard_error;
+    let degrees_of_freedom = (se_a + se_b).powi(2) / (se_a.powi(2) / (a.len() - 1) as f64 + se_b.powi(2) / (b.len() - 1) as f64);
+    TestResult {
+        statistic : t,
+        p_value : student_t_two_sided_p_value(t, degrees_of_freedom),
+    }
+}
+
+/// Mann-Whitney U test (two sided), with the normal approximation (tie and continuity corrected)
+/// NOTE : the statistic is the U of the first group
+pub fn mann_whitney_u(a : &[f64], b : &[f64]) -> TestResult {
+    if a.is_empty() || b.is_empty() {
+        return TestResult::undefined();
+    }
+    let (n_a, n_b) = (a.len() as f64, b.len() as f64);
+    let n = n_a + n_b;
+
+    let (u_a, tie_correction) = u_statistic(a, b);
+    let mean_u = n_a * n_b / 2.0;
+    let sigma = (n_a * n_b / 12.0 * ((n + 1.0) - tie_correction / (n * (n - 1.0)))).sqrt();
+    if sigma == 0.0 || sigma.is_nan() {
+        return TestResult { statistic : u_a, p_value : 1.0 };
+    }
+    let difference = u_a - mean_u;
+    let z = (difference - 0.5 * difference.signum()) / sigma;
+    TestResult {
+        statistic : u_a,
+        p_value : normal_two_sided_p_value(z).min(1.0),
+    }
+}
+
+/// get the U of the first group (from the rank sum of its values) and the tie correction (sum of t^3 - t for each tie of size t)
+/// NOTE : the ties get the average of their ranks
+fn u_statistic(a : &[f64], b : &[f64]) -> (f64, f64) {
+    let mut values : Vec<(f64, bool)> = a.iter().map(|v| (*v, true)).chain(b.iter().map(|v| (*v, false))).collect();
+    values.sort_by(|x, y| x.0.total_cmp(&y.0));
+    let mut rank_sum_a = 0.0;
+    let mut tie_correction = 0.0;
+    let mut start = 0;
+    while start < values.len() {
+        let mut end = start;
+        while end + 1 < values.len() && values[end + 1].0 == values[start].0 {
+            end += 1;
+        }
+        let tie_size = (end - start + 1) as f64;
+        let average_rank = (start + end) as f64 / 2.0 + 1.0;
+        rank_sum_a += average_rank * values[start..=end].iter().filter(|(_, is_a)| *is_a).count() as f64;
+        tie_correction += tie_size.powi(3) - tie_size;
+        start = end + 1;
+    }
+    let n_a = a.len() as f64;
+    (rank_sum_a - n_a * (n_a + 1.0) / 2.0, tie_correction)
+}
+
+/// bootstrap test of the difference of the medians (a - b), two sided
+/// NOTE : the p-value is twice the proportion of the resampled differences on the other side of 0 (with a +1 correction)
+pub fn bootstrap_median_difference(a : &[f64], b : &[f64], nb_of_resamples : usize, seed : u64) -> TestResult {
+    if a.is_empty() || b.is_empty() || nb_of_resamples == 0 {
+        return TestResult::undefined();
+    }
+    let observed = median(a) - median(b);
+    let mut rng = SplitMix64::new(seed);
+    let mut resample = |values : &[f64]| -> Vec<f64> {
+        (0..values.len()).map(|_| values[rng.next_index(values.len())]).collect()
+    };
+    let (mut lower_or_equal, mut greater_or_equal) = (0, 0);
+    for _ in 0..nb_of_resamples {
+        let difference = median(&resample(a)) - median(&resample(b));
+        if difference <= 0.0 {
+            lower_or_equal += 1;
+        }
+        if difference >= 0.0 {
+            greater_or_equal += 1;
+        }
+    }
+    let tail = lower_or_equal.min(greater_or_equal) as f64;
+    TestResult {
+        statistic : observed,
+        p_value : (2.0 * (tail + 1.0) / (nb_of_resamples as f64 + 1.0)).min(1.0),
+    }
+}
+
+/// Cohen's d : (mean a - mean b) / pooled standard deviation (NaN if not enough values)
+pub fn cohen_d(a : &[f64], b : &[f64]) -> f64 {
+    if a.len() < 2 || b.len() < 2 {
+        return f64::NAN;
+    }
+    let (mean_a, variance_a) = mean_variance(a);
+    let (mean_b, variance_b) = mean_variance(b);
+    let (n_a, n_b) = (a.len() as f64, b.len() as f64);
+    let pooled = (((n_a - 1.0) * variance_a + (n_b - 1.0) * variance_b) / (n_a + n_b - 2.0)).sqrt();
+    (mean_a - mean_b) / pooled
+}
+
+/// Cliff's delta : P(a > b) - P(a < b) (NaN if a group is empty)
+/// NOTE : computed from the U of Mann-Whitney, delta = 2 U / (n_a n_b) - 1 (in O((n_a + n_b) log(n_a + n_b)))
+pub fn cliff_delta(a : &[f64], b : &[f64]) -> f64 {
+    if a.is_empty() || b.is_empty() {
+        return f64::NAN;
+    }
+    let (u_a, _) = u_statistic(a, b);
+    2.0 * u_a / (a.len() * b.len()) as f64 - 1.0
+}
+
+/// the mean and the sample variance (n - 1)
+fn mean_variance(values : &[f64]) -> (f64, f64) {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    (mean, variance)
+}
+
+pub(crate) fn median(values : &[f64]) -> f64 {
+    if values.is_empty() {
+        return f64::NAN;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let middle = sorted.len() / 2;
+    if sorted.len() % 2 == 1 {
+        sorted[middle]
+    } else {
+        (sorted[middle - 1] + sorted[middle]) / 2.0
+    }
+}
+
+/// P(|T| >= |t|) for a Student's t distribution with the given degrees of freedom
+fn student_t_two_sided_p_value(t : f64, degrees_of_freedom : f64) -> f64 {
+    regularized_incomplete_beta(degrees_of_freedom / 2.0, 0.5, degrees_of_freedom / (degrees_of_freedom + t * t))
+}
+
+/// P(|Z| >= |z|) for a standard normal distribution
+fn normal_two_sided_p_value(z : f64) -> f64 {
+    erfc(z.abs() / SQRT_2)
+}
+
+/// the complementary error function (fractional error lower than 1.2e-7)
+fn erfc(x : f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let polynomial = -z * z - 1.265_512_23 + t * (1.000_023_68 + t * (0.374_091_96 + t * (0.096_784_18
+        + t * (-0.186_288_06 + t * (0.278_868_07 + t * (-1.135_203_98 + t * (1.488_515_87
+        + t * (-0.822_152_23 + t * 0.170_872_77))))))));
+    let result = t * polynomial.exp();
+    if x >= 0.0 { result } else { 2.0 - result }
+}
+
+/// the logarithm of the gamma function (Lanczos approximation)
+fn ln_gamma(x : f64) -> f64 {
+    const COEFFICIENTS : [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        // reflection formula
+        return (PI / (PI * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let sum = COEFFICIENTS.iter().enumerate().skip(1)
+        .fold(COEFFICIENTS[0], |sum, (i, coefficient)| sum + coefficient / (x + i as f64));
+    0.5 * (2.0 * PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+/// the regularized incomplete beta function I_x(a, b)
+fn regularized_incomplete_beta(a : f64, b : f64, x : f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front = (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    // the continued fraction converges quickly in this domain, use the symmetry otherwise
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(a, b, x) / a
+    } else {
+        1.0 - front * beta_continued_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+/// the continued fraction of the incomplete beta function (modified Lentz's method)
+fn beta_continued_fraction(a : f64, b : f64, x : f64) -> f64 {
+    const MAX_ITERATIONS : usize = 300;
+    const EPSILON : f64 = 1e-15;
+    const TINY : f64 = 1e-300;
+    let (qab, qap, qam) = (a + b, a + 1.0, a - 1.0);
+    let mut c = 1.0;
+    let mut d = 1.0 - qab * x / qap;
+    if d.abs() < TINY {
+        d = TINY;
+    }
+    d = 1.0 / d;
+    let mut h = d;
+    for m in 1..=MAX_ITERATIONS {
+        let m = m as f64;
+        let m2 = 2.0 * m;
+        // even step
+        let aa = m * (b - m) * x / ((qam + m2) * (a + m2));
+        d = 1.0 + aa * d;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = 1.0 + aa / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1.0 / d;
+        h *= d * c;
+        // odd step
+        let aa = -(a + m) * (qab + m) * x / ((a + m2) * (qap + m2));
+        d = 1.0 + aa * d;
+        if d.abs() < TINY {
+            d = TINY;
+        }
+        c = 1.0 + aa / c;
+        if c.abs() < TINY {
+            c = TINY;
+        }
+        d = 1.0 / d;
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+    h
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn test_distributions() {
+        assert_relative_eq!(ln_gamma(5.0), 24.0_f64.ln(), epsilon = 1e-10);
+        assert_relative_eq!(ln_gamma(0.5), PI.sqrt().ln(), epsilon = 1e-10);
+        assert_relative_eq!(normal_two_sided_p_value(1.959964), 0.05, epsilon = 1e-6);
+        assert_relative_eq!(student_t_two_sided_p_value(2.228139, 10.0), 0.05, epsilon = 1e-6);
+        assert_relative_eq!(student_t_two_sided_p_value(0.0, 10.0), 1.0, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_welch_t_test() {
+        let a = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let b = [2.0, 4.0, 6.0, 8.0, 10.0];
+        let result = welch_t_test(&a, &b);
+        assert_relative_eq!(result.statistic, -3.0 / 2.5_f64.sqrt(), epsilon = 1e-12);
+        // df = 6.25 / 1.0625
+        assert_relative_eq!(result.p_value, student_t_two_sided_p_value(result.statistic, 6.25 / 1.0625), epsilon = 1e-12);
+        assert!(result.p_value > 0.05 && result.p_value < 0.2);
+        assert!(welch_t_test(&a, &[1.0]).p_value.is_nan());
+        assert_eq!(welch_t_test(&[1.0, 1.0], &[2.0, 2.0]).p_value, 0.0);
+    }
+
+    #[test]
+    fn test_mann_whitney_u() {
+        let result = mann_whitney_u(&[1.0, 2.0, 3.0], &[4.0, 5.0, 6.0]);
+        assert_eq!(result.statistic, 0.0);
+        assert_relative_eq!(result.p_value, 0.080_856, epsilon = 1e-5);
+        // ties
+        let result = mann_whitney_u(&[1.0, 1.0, 2.0], &[1.0, 2.0, 2.0]);
+        assert_eq!(result.statistic, 3.0);
+        assert_eq!(mann_whitney_u(&[1.0, 1.0], &[1.0]).p_value, 1.0);
+    }
+
+    #[test]
+    fn test_effect_sizes() {
+        assert_relative_eq!(cohen_d(&[1.0, 2.0, 3.0], &[4.0, 5.0, 6.0]), -3.0, epsilon = 1e-12);
+        assert_eq!(cliff_delta(&[1.0, 2.0, 3.0], &[4.0, 5.0, 6.0]), -1.0);
+        assert_eq!(cliff_delta(&[1.0, 5.0], &[3.0, 3.0]), 0.0);
+        assert_eq!(cliff_delta(&[3.0, 3.0], &[1.0, 5.0]), 0.0);
+
+        // the same as the definition, with ties
+        let a = [1.0, 2.0, 2.0, 4.0, 7.0, 7.0, 9.0];
+        let b = [2.0, 3.0, 7.0, 8.0, 8.0];
+        let dominance : i64 = a.iter().flat_map(|x| b.iter().map(move |y| (x > y) as i64 - (x < y) as i64)).sum();
+        assert_relative_eq!(cliff_delta(&a, &b), dominance as f64 / (a.len() * b.len()) as f64, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_bootstrap_median_difference() {
+        let a : Vec<f64> = (0..30).map(|v| v as f64).collect();
+        let b : Vec<f64> = (0..30).map(|v| v as f64 + 100.0).collect();
+        let result = bootstrap_median_difference(&a, &b, 500, 1);
+        assert_eq!(result.statistic, -100.0);
+        assert!(result.p_value < 0.01);
+        assert_eq!(result, bootstrap_median_difference(&a, &b, 500, 1));
+
+        let same = bootstrap_median_difference(&a, &a, 500, 1);
+        assert!(same.p_value > 0.5);
+    }
+
+    #[test]
+    fn test_pairwise_significance() {
+        let groups = vec![
+            ("a".to_string(), vec![1.0, 2.0, 3.0]),
+            ("b".to_string(), vec![4.0, 5.0, 6.0]),
+            ("c".to_string(), vec![1.0, 2.0, 3.5]),
+        ];
+        let table = pairwise_significance(&groups, 100, 0);
+        let pairs = table.get_comparisons().iter()
+            .map(|c| format!("{}-{}", c.group_a, c.group_b))
+            .collect::<Vec<_>>();
+        assert_eq!(pairs, vec!["a-b", "a-c", "b-c"]);
+        assert_eq!(table.get_comparison("b", "a").unwrap().cliff_delta, -1.0);
+
+        let array : Array = table.into();
+        assert_eq!(array.get_header().len(), 10);
+        assert_eq!(array.get_data().len(), 3);
+        assert_eq!(array.get_data()[0][9], "-1.0000");
+    }
+}
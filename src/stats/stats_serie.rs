@@ -7,12 +7,12 @@ use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 /// represent a serie with its stats
 #[derive(Debug, Clone)]
 pub struct StatsSerie {
-    pub serie : Vec<f32>,
+    pub serie : Vec<f64>,
     pub stats : HashMap<MetricName, MetricValue>,
 }
 
 impl StatsSerie {
-    pub fn new(serie : &Vec<f32>) -> Self {
+    pub fn new(serie : &Vec<f64>) -> Self {
         let mut stats = HashMap::new();
 
         if serie.len() == 0 {
@@ -28,23 +28,23 @@ impl StatsSerie {
         #[cfg(not(feature = "parrallelize"))]
         stats.insert(
             MetricName::Mean, 
-            MetricValue::mean(serie.iter().map(|f| *f as f64).sum::<f64>() / serie.len() as f64)
+            MetricValue::mean(serie.iter().sum::<f64>() / serie.len() as f64)
         );
         #[cfg(feature = "parrallelize")]
         stats.insert(
             MetricName::Mean, 
-            MetricValue::mean(serie.par_iter().map(|f| *f as f64).sum::<f64>() / serie.len() as f64)
+            MetricValue::mean(serie.par_iter().map(|f| *f).sum::<f64>() / serie.len() as f64)
         );
 
         let sorted_serie = {
             let mut sorted_serie = serie.clone();
-            sorted_serie.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            sorted_serie.sort_by(|a, b| a.total_cmp(b));
             sorted_serie
         };
         if serie.len() % 2 == 0 {
-            stats.insert(MetricName::Median, MetricValue::median((sorted_serie[serie.len() / 2] as f64 + sorted_serie[serie.len() / 2 - 1] as f64) / 2.0));
+            stats.insert(MetricName::Median, MetricValue::median((sorted_serie[serie.len() / 2] + sorted_serie[serie.len() / 2 - 1]) / 2.0));
         }else{
-            stats.insert(MetricName::Median, MetricValue::median(sorted_serie[serie.len() / 2] as f64));
+            stats.insert(MetricName::Median, MetricValue::median(sorted_serie[serie.len() / 2]));
         }
 
         Self {
@@ -53,8 +53,14 @@ impl StatsSerie {
         }
     }
 
+    /// get the value of the metric
+    /// NOTE : the percentiles are not precomputed, they are computed at each call
+    /// NOTE : a metric missing from the stats (removed by hand) is NaN
     pub fn get_stats(&self, metric : MetricName) -> MetricValue {
-        self.stats.get(&metric).unwrap().clone()
+        match metric {
+            MetricName::Percentile(percent) => MetricValue::percentile(percent, percentile(&self.serie, percent as f64)),
+            _ => self.stats.get(&metric).cloned().unwrap_or(MetricValue { name : metric, value : f64::NAN }),
+        }
     }
 }
 
@@ -98,6 +104,13 @@ impl MetricValue {
             value,
         }
     }
+
+    pub fn percentile(percent : u8, value : f64) -> Self {
+        Self {
+            name : MetricName::Percentile(percent),
+            value,
+        }
+    }
 }
 
 
@@ -105,6 +118,8 @@ impl MetricValue {
 pub enum MetricName {
     Mean,
     Median,
+    /// the given percentile, between 0 and 100 (linear interpolation between the closest ranks)
+    Percentile(u8),
 }
 
 impl Display for MetricName {
@@ -119,6 +134,33 @@ impl MetricName {
         match self {
             MetricName::Mean => "mean".to_string(),
             MetricName::Median => "median".to_string(),
+            MetricName::Percentile(percent) => format!("p{}", percent),
         }
     }
+}
+
+/// get the percentile (between 0 and 100) of the serie, with a linear interpolation between the closest ranks
+/// NOTE : NaN if the serie is empty
+pub fn percentile(serie : &[f64], percent : f64) -> f64 {
+    if serie.is_empty() {
+        return f64::NAN;
+    }
+    let mut sorted_serie = serie.to_vec();
+    sorted_serie.sort_by(|a, b| a.total_cmp(b));
+    sorted_percentile(&sorted_serie, percent)
+}
+
+/// same as percentile, but the serie must already be sorted
+pub(crate) fn sorted_percentile<T>(sorted_serie : &[T], percent : f64) -> f64
+where
+    T : Copy + Into<f64>,
+{
+    if sorted_serie.is_empty() {
+        return f64::NAN;
+    }
+    let rank = percent.clamp(0.0, 100.0) / 100.0 * (sorted_serie.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    let (lower_value, upper_value) = (sorted_serie[lower].into(), sorted_serie[upper].into());
+    lower_value + (upper_value - lower_value) * (rank - lower as f64)
 }
\ No newline at end of file
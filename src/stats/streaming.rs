@@ -0,0 +1,207 @@
+use std::fmt::{Display, Formatter};
+
+use super::stats_serie::{sorted_percentile, MetricName, MetricValue};
+
+/// the default number of values summarised by a StreamingStats (see StreamingStats::new)
+pub const STREAMING_CAPACITY : usize = 1024;
+
+/// stats computed value by value, with a bounded memory (to aggregate more values than the memory can hold)
+/// NOTE : the count, the mean, the min and the max are exact. The values are kept as they are until there are 2 * capacity of them,
+/// then they are summarised in capacity weighted centroids : the median and the percentiles are then approximated
+/// (the rank error is about count / capacity)
+/// NOTE : two stats can be merged, to compute them chunk by chunk
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamingStats {
+    count : usize,
+    mean : f64,
+    min : f64,
+    max : f64,
+    /// (value, weight), the weights are 1 while the stats are exact
+    centroids : Vec<(f64, f64)>,
+    capacity : usize,
+    exact : bool,
+}
+
+impl Default for StreamingStats {
+    fn default() -> Self {
+        Self::new(STREAMING_CAPACITY)
+    }
+}
+
+impl StreamingStats {
+    /// create empty stats, summarising the values in capacity centroids
+    pub fn new(capacity : usize) -> Self {
+        Self {
+            count : 0,
+            mean : f64::NAN,
+            min : f64::INFINITY,
+            max : f64::NEG_INFINITY,
+            centroids : Vec::new(),
+            capacity : capacity.max(2),
+            exact : true,
+        }
+    }
+
+    /// add a value (use welford's algorithm for the mean)
+    pub fn add(&mut self, value : f64) {
+        self.count += 1;
+        self.mean = if self.count == 1 { value } else { self.mean + (value - self.mean) / self.count as f64 };
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.centroids.push((value, 1.0));
+        if self.centroids.len() > 2 * self.capacity {
+            self.compress();
+        }
+    }
+
+    /// add the values of other stats
+    pub fn merge(&mut self, other : &StreamingStats) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 {
+            self.mean = other.mean;
+        } else {
+            self.mean += (other.mean - self.mean) * other.count as f64 / (self.count + other.count) as f64;
+        }
+        self.count += other.count;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.centroids.extend_from_slice(&other.centroids);
+        self.exact &= other.exact;
+        if self.centroids.len() > 2 * self.capacity {
+            self.compress();
+        }
+    }
+
+    /// get the number of values
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// true if the values are all kept (the percentiles are exact)
+    pub fn is_exact(&self) -> bool {
+        self.exact
+    }
+
+    /// get the value of the metric (NaN if there is no value)
+    /// NOTE : the same as StatsSerie::get_stats while the stats are exact
+    pub fn get_stats(&self, metric : MetricName) -> MetricValue {
+        match metric {
+            MetricName::Mean => MetricValue::mean(self.mean),
+            MetricName::Median => MetricValue::median(self.percentile(50.0)),
+            MetricName::Percentile(percent) => MetricValue::percentile(percent, self.percentile(percent as f64)),
+        }
+    }
+
+    /// get the min and the max of the values (NaN if there is no value)
+    pub fn get_min_max(&self) -> (f64, f64) {
+        if self.count == 0 {
+            return (f64::NAN, f64::NAN);
+        }
+        (self.min, self.max)
+    }
+
+    /// get the percentile (between 0 and 100), with a linear interpolation between the closest ranks (see stats_serie::percentile)
+    fn percentile(&self, percent : f64) -> f64 {
+        let mut centroids = self.centroids.clone();
+        centroids.sort_by(|a, b| a.0.total_cmp(&b.0));
+        if self.exact {
+            return sorted_percentile(&centroids.iter().map(|(value, _)| *value).collect::<Vec<f64>>(), percent);
+        }
+
+        // each centroid is at the middle rank of its values, the min and the max are at the first and the last rank
+        let rank = percent.clamp(0.0, 100.0) / 100.0 * (self.count - 1) as f64;
+        let mut previous = (0.0, self.min);
+        let mut cumulated_weight = 0.0;
+        for (value, weight) in centroids.iter() {
+            let centroid_rank = cumulated_weight + (weight - 1.0) / 2.0;
+            if rank <= centroid_rank {
+                return interpolate(previous, (centroid_rank, *value), rank);
+            }
+            previous = (centroid_rank, *value);
+            cumulated_weight += weight;
+        }
+        interpolate(previous, ((self.count - 1) as f64, self.max), rank)
+    }
+
+    /// summarise the values in capacity centroids : the sorted centroids are grouped by slice of count / capacity ranks
+    fn compress(&mut self) {
+        self.centroids.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let slice_weight = self.count as f64 / self.capacity as f64;
+        let mut compressed : Vec<(usize, f64, f64)> = Vec::with_capacity(self.capacity + 1);
+        let mut cumulated_weight = 0.0;
+        for (value, weight) in self.centroids.drain(..) {
+            let slice = (cumulated_weight / slice_weight) as usize;
+            cumulated_weight += weight;
+            match compressed.last_mut() {
+                Some((last_slice, mean, total_weight)) if *last_slice == slice => {
+                    *total_weight += weight;
+                    *mean += (value - *mean) * weight / *total_weight;
+                },
+                _ => compressed.push((slice, value, weight)),
+            }
+        }
+        self.centroids = compressed.into_iter().map(|(_, mean, weight)| (mean, weight)).collect();
+        self.exact = false;
+    }
+}
+
+/// interpolate linearly the value at the rank between two (rank, value)
+fn interpolate(start : (f64, f64), end : (f64, f64), rank : f64) -> f64 {
+    if end.0 <= start.0 {
+        return end.1;
+    }
+    start.1 + (end.1 - start.1) * (rank - start.0) / (end.0 - start.0)
+}
+
+impl Display for StreamingStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "count: {} {} {}", self.count, self.get_stats(MetricName::Mean), self.get_stats(MetricName::Median))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::stats_serie::StatsSerie;
+
+    #[test]
+    fn test_streaming_stats_exact() {
+        let values = vec![3.0, 1.0, 4.0, 1.0, 5.0, 9.0, 2.0, 6.0];
+        let mut stats = StreamingStats::default();
+        let mut other = StreamingStats::default();
+        values[..3].iter().for_each(|value| stats.add(*value));
+        values[3..].iter().for_each(|value| other.add(*value));
+        stats.merge(&other);
+
+        let expected = StatsSerie::new(&values);
+        assert!(stats.is_exact());
+        assert_eq!(stats.len(), values.len());
+        for metric in [MetricName::Mean, MetricName::Median, MetricName::Percentile(90)] {
+            assert!((stats.get_stats(metric).value - expected.get_stats(metric).value).abs() < 1e-12);
+        }
+        assert_eq!(stats.get_min_max(), (1.0, 9.0));
+        assert!(StreamingStats::default().get_stats(MetricName::Median).value.is_nan());
+    }
+
+    #[test]
+    fn test_streaming_stats_compressed() {
+        let mut stats = StreamingStats::new(50);
+        // 0..10000 in a shuffled order
+        (0..10000).for_each(|i| stats.add(((i * 7919) % 10000) as f64));
+        assert!(!stats.is_exact());
+        assert!(stats.centroids.len() <= 100);
+        assert!((stats.get_stats(MetricName::Mean).value - 4999.5).abs() < 1e-6);
+        // the rank error is about count / capacity
+        assert!((stats.get_stats(MetricName::Median).value - 4999.5).abs() < 200.0);
+        assert!((stats.get_stats(MetricName::Percentile(90)).value - 8999.1).abs() < 200.0);
+        assert_eq!(stats.get_stats(MetricName::Percentile(0)).value, 0.0);
+        assert_eq!(stats.get_stats(MetricName::Percentile(100)).value, 9999.0);
+    }
+}
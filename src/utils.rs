@@ -1,90 +1,137 @@
-#[cfg(feature = "parrallelize")]
-use std::sync::Mutex;
+use std::collections::HashMap;
 use std::ops::Range;
 #[cfg(feature = "parrallelize")]
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
 
-#[cfg(not(feature = "parrallelize"))]
-use std::collections::HashMap;
+use crate::data::linspace::Linspace;
 
 
-use crate::data::linspace::Linspace;
+/// the default number of divisions of each axis
+pub(crate) const SERIE_DIVISION : usize = 500;
 
+const TRESHOLD : f64 = 1000.0;
 
-const SERIE_DIVISION : usize = 500;
+/// Format a number to a string
+pub(crate) fn axe_number_formater(x: &f64) -> String {
+    let x = *x;
+    if x < -TRESHOLD || (x > -1.0/TRESHOLD && x < 1.0/TRESHOLD) || x > TRESHOLD {
+        format!("{:.0e}", x)
+    } else {
+        format!("{:.3}", x)
+    }
+}
 
-/// The goal of the function is to compress a dataserie to accelerate the plotting
-/// it will cut the graph into piece en make the average of each piece
-/// NOTE : The function take the borow of the data to avoid the memory allocation
-#[cfg(not(feature = "parrallelize"))]
-pub(crate) fn compress_data_serie(to_compress : Vec<(f32, f32)>, range_x : &Range<f32>, range_y : &Range<f32>) -> Vec<(f32, f32)> {
-    // cut the range into SERIE_DIVISION pieces
-    let x_linspace = Linspace::new(range_x.start as f64, range_x.end as f64, SERIE_DIVISION);
-    let y_linspace = Linspace::new(range_y.start as f64, range_y.end as f64, SERIE_DIVISION);
-
-    // create the hashmap to store the data
-    // the key is the index of the piece in the x and y linspace
-    // the value is a tuple with the sum of the x and y and the number of points
-    // use welfort's algorithm
-    let mut result_map : HashMap<(usize, usize), (f32, f32, usize)> = HashMap::new();
-    for (x, y) in to_compress.into_iter() {
-        let x_index = x_linspace.index_of(&(x as f64)).unwrap();
-        let y_index = y_linspace.index_of(&(y as f64)).unwrap();
-        let entry = result_map.entry((x_index, y_index)).or_insert((0.0, 0.0, 0));
-        entry.2 += 1;
-        entry.0 += (x - entry.0) / entry.2 as f32;
-        entry.1 += (y - entry.1) / entry.2 as f32;
-        
-    }
-
-    // create the result vector
-    let mut result : Vec<(f32, f32)> = Vec::new();
-    for (x, y, _) in result_map.values() {
-        result.push((*x, *y));
-    }
-    
-    result
+/// the mean of the points of a cell (welfort's algorithm), which can be merged with the mean of other points
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct CellMean {
+    x : f64,
+    y : f64,
+    count : usize,
 }
 
+impl CellMean {
+    pub(crate) fn add(&mut self, (x, y) : (f64, f64)) {
+        self.count += 1;
+        self.x += (x - self.x) / self.count as f64;
+        self.y += (y - self.y) / self.count as f64;
+    }
+
+    /// merge the mean of other points (as if they were added one by one)
+    pub(crate) fn merge(&mut self, other : &CellMean) {
+        if other.count == 0 {
+            return;
+        }
+        let count = self.count + other.count;
+        let weight = other.count as f64 / count as f64;
+        self.x += (other.x - self.x) * weight;
+        self.y += (other.y - self.y) * weight;
+        self.count = count;
+    }
+
+    pub(crate) fn get_point(&self) -> (f64, f64) {
+        (self.x, self.y)
+    }
+}
 
+/// the grid of compress_data_serie : the mean of the points of each of the resolution x resolution cells
+/// NOTE : the points can be added in several times (or in several grids of the same ranges, then merged)
+#[derive(Debug, Clone)]
+pub(crate) struct CompressionGrid {
+    x_linspace : Linspace,
+    y_linspace : Linspace,
+    /// the key is the index of the cell in the x and y linspaces
+    cells : HashMap<(usize, usize), CellMean>,
+}
 
-/// The goal of the function is to compress a dataserie to accelerate the plotting
-/// it will cut the graph into piece en make the average of each piece
-/// NOTE : The function take the borow of the data to avoid a double memory allocation
-#[cfg(feature = "parrallelize")]
-pub(crate) fn compress_data_serie(to_compress : Vec<(f32, f32)>, range_x : &Range<f32>, range_y : &Range<f32>) -> Vec<(f32, f32)> {
-    // cut the range into SERIE_DIVISION pieces
+impl CompressionGrid {
+    pub(crate) fn new(range_x : &Range<f64>, range_y : &Range<f64>, resolution : usize) -> Self {
+        // cut the range into resolution pieces
+        let resolution = resolution.max(2);
+        Self {
+            x_linspace : Linspace::new(range_x.start, range_x.end, resolution),
+            y_linspace : Linspace::new(range_y.start, range_y.end, resolution),
+            cells : HashMap::new(),
+        }
+    }
 
-   
+    /// add a point to its cell (the points out of the ranges, or NaN, are ignored)
+    pub(crate) fn add(&mut self, (x, y) : (f64, f64)) {
+        if let (Some(x_index), Some(y_index)) = (self.x_linspace.index_of(&x), self.y_linspace.index_of(&y)) {
+            self.cells.entry((x_index, y_index)).or_default().add((x, y));
+        }
+    }
 
-    
-    let x_linspace = Linspace::new(range_x.start as f64, range_x.end as f64, SERIE_DIVISION);
-    let y_linspace = Linspace::new(range_y.start as f64, range_y.end as f64, SERIE_DIVISION);
-
-    let mut discrete_points : Vec<Mutex<(f64, f64, u64)>> = Vec::with_capacity(SERIE_DIVISION*SERIE_DIVISION);
-    for _ in 0..SERIE_DIVISION*SERIE_DIVISION {
-        discrete_points.push(Mutex::new((0.0, 0.0, 0)));
-    }
-
-    // use the welfort's algorithm to compress the data
-    to_compress.into_par_iter().map(|(x, y)| {
-        let x_index = x_linspace.index_of(&(x as f64)).unwrap();
-        let y_index = y_linspace.index_of(&(y as f64)).unwrap();
-        let index = x_index * SERIE_DIVISION + y_index;
-        let mut value = discrete_points[index].lock().unwrap(); // block until the lock is available (in scope)
-        value.2 += 1;
-        value.0 += (x as f64 - value.0) / value.2 as f64;
-        value.1 += (y as f64 - value.1) / value.2 as f64;
-    }).collect::<Vec<_>>(); // need to collect to force the calcul of the iterator
-    
-    discrete_points.into_par_iter().filter_map(|mutex| {
-        let value = mutex.lock().unwrap();
-        if value.2 == 0 {
-            return None;
+    /// merge the cells of a grid of the same ranges and resolution
+    pub(crate) fn merge(&mut self, other : &CompressionGrid) {
+        for (index, cell) in other.cells.iter() {
+            self.cells.entry(*index).or_default().merge(cell);
         }
-        Some((value.0 as f32, value.1 as f32))
-    }).collect()
+    }
+
+    /// get the mean of each non empty cell, sorted by x (then by y)
+    pub(crate) fn into_points(self) -> Vec<(f64, f64)> {
+        let mut points : Vec<(f64, f64)> = self.cells.values().map(CellMean::get_point).collect();
+        sort_by_x(&mut points);
+        points
+    }
+}
+
+/// The goal of the function is to compress a dataserie to accelerate the plotting
+/// it will cut the graph into resolution x resolution pieces en make the average of each piece
+/// NOTE : The function take the borow of the data to avoid the memory allocation
+/// NOTE : the result is sorted by x (then by y), the points out of the ranges are removed
+pub(crate) fn compress_data_serie(to_compress : Vec<(f64, f64)>, range_x : &Range<f64>, range_y : &Range<f64>, resolution : usize) -> Vec<(f64, f64)> {
+    fill_compression_grid(to_compress, range_x, range_y, resolution).into_points()
+}
+
+/// sort the points by x, then by y
+pub(crate) fn sort_by_x(points : &mut [(f64, f64)]) {
+    points.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+}
+
+/// add the points to a new grid (see compress_data_serie)
+#[cfg(not(feature = "parrallelize"))]
+pub(crate) fn fill_compression_grid(points : Vec<(f64, f64)>, range_x : &Range<f64>, range_y : &Range<f64>, resolution : usize) -> CompressionGrid {
+    let mut grid = CompressionGrid::new(range_x, range_y, resolution);
+    points.into_iter().for_each(|point| grid.add(point));
+    grid
+}
+
+/// add the points to a new grid (see compress_data_serie)
+/// NOTE : each thread fills its own grid, the grids are then merged
+#[cfg(feature = "parrallelize")]
+pub(crate) fn fill_compression_grid(points : Vec<(f64, f64)>, range_x : &Range<f64>, range_y : &Range<f64>, resolution : usize) -> CompressionGrid {
+    let new_grid = || CompressionGrid::new(range_x, range_y, resolution);
+    points.into_par_iter()
+        .fold(new_grid, |mut grid, point| {
+            grid.add(point);
+            grid
+        })
+        .reduce(new_grid, |mut grid, other| {
+            grid.merge(&other);
+            grid
+        })
 }
 
 
@@ -94,11 +141,11 @@ mod tests {
 
     #[test]
     fn test_empty_dataset() {
-        let to_compress: Vec<(f32, f32)> = Vec::new();
+        let to_compress: Vec<(f64, f64)> = Vec::new();
         let range_x = Range { start: 0.0, end: 10.0 };
         let range_y = Range { start: 0.0, end: 10.0 };
 
-        let compressed = compress_data_serie(to_compress, &range_x, &range_y);
+        let compressed = compress_data_serie(to_compress, &range_x, &range_y, SERIE_DIVISION);
 
         assert_eq!(compressed.len(), 0);
     }
@@ -109,7 +156,7 @@ mod tests {
         let range_x = Range { start: 0.0, end: 10.0 };
         let range_y = Range { start: 0.0, end: 10.0 };
 
-        let compressed = compress_data_serie(to_compress, &range_x, &range_y);
+        let compressed = compress_data_serie(to_compress, &range_x, &range_y, SERIE_DIVISION);
 
         assert_eq!(compressed.len(), 1);
         assert_eq!(compressed[0], (5.0, 5.0));
@@ -121,7 +168,7 @@ mod tests {
         let range_x = Range { start: 0.0, end: 10.0 };
         let range_y = Range { start: 0.0, end: 10.0 };
 
-        let compressed = compress_data_serie(to_compress, &range_x, &range_y);
+        let compressed = compress_data_serie(to_compress, &range_x, &range_y, SERIE_DIVISION);
 
         assert!(compressed.len() == 2);
     }
@@ -132,21 +179,63 @@ mod tests {
         let range_x = Range { start: 0.0, end: 10.0 };
         let range_y = Range { start: 0.0, end: 10.0 };
 
-        let compressed = compress_data_serie(to_compress, &range_x, &range_y);
+        let compressed = compress_data_serie(to_compress, &range_x, &range_y, SERIE_DIVISION);
 
         assert!(compressed.len() == 2);
     }
 
+    #[test]
+    fn test_points_out_of_range_are_removed() {
+        let to_compress = vec![(1.0, 1.0), (11.0, 1.0), (1.0, f64::NAN)];
+        let range = Range { start: 0.0, end: 10.0 };
+
+        let compressed = compress_data_serie(to_compress, &range, &range, SERIE_DIVISION);
+
+        assert_eq!(compressed, vec![(1.0, 1.0)]);
+    }
+
    
     #[test]
     fn test_large_dataset() {
-        let to_compress = (0..(2 * SERIE_DIVISION * SERIE_DIVISION)).map(|x| (x as f32 % 10.0, x as f32 % 10.0)).collect();
+        let to_compress = (0..(2 * SERIE_DIVISION * SERIE_DIVISION)).map(|x| (x as f64 % 10.0, x as f64 % 10.0)).collect();
         let range_x = Range { start: 0.0, end: 10.0 };
         let range_y = Range { start: 0.0, end: 10.0 };
 
-        let compressed = compress_data_serie(to_compress, &range_x, &range_y);
+        let compressed = compress_data_serie(to_compress, &range_x, &range_y, SERIE_DIVISION);
 
         assert!(compressed.len() <= SERIE_DIVISION * SERIE_DIVISION);
         // Further checks can assess distribution and averaging correctness
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_compressed_sorted_by_x_with_resolution() {
+        let to_compress = vec![(9.0, 1.0), (1.0, 9.0), (5.0, 5.0), (1.2, 8.8)];
+        let range = Range { start: 0.0, end: 10.0 };
+
+        let compressed = compress_data_serie(to_compress.clone(), &range, &range, SERIE_DIVISION);
+        assert_eq!(compressed, vec![(1.0, 9.0), (1.2, 8.8), (5.0, 5.0), (9.0, 1.0)]);
+
+        // (1, 9) and (1.2, 8.8) are in the same piece
+        let compressed = compress_data_serie(to_compress, &range, &range, 3);
+        assert_eq!(compressed, vec![(1.1, 8.9), (5.0, 5.0), (9.0, 1.0)]);
+    }
+
+    #[test]
+    fn test_merged_grids() {
+        let points : Vec<(f64, f64)> = (0..100).map(|x| ((x % 7) as f64, (x % 3) as f64 + x as f64 / 100.0)).collect();
+        let range = Range { start: 0.0, end: 10.0 };
+
+        // the points added in two grids, then merged, give the same means as in one grid
+        let mut grid = CompressionGrid::new(&range, &range, 4);
+        let mut other = CompressionGrid::new(&range, &range, 4);
+        points[..30].iter().for_each(|point| grid.add(*point));
+        points[30..].iter().for_each(|point| other.add(*point));
+        grid.merge(&other);
+        let expected = compress_data_serie(points, &range, &range, 4);
+        let merged = grid.into_points();
+        assert_eq!(merged.len(), expected.len());
+        for (point, expected) in merged.iter().zip(expected.iter()) {
+            assert!((point.0 - expected.0).abs() < 1e-12 && (point.1 - expected.1).abs() < 1e-12);
+        }
+    }
+}
@@ -27,7 +27,7 @@ fn get_all_test_plot_series_from_dir() -> Result<(PlotSeries, HashMap<String, Ve
             let dir_test_series = common::stats_helper::TestSerie::new_from_dir(dir_path.to_str().unwrap());
             
             let mut legend : Option<String> = None;
-            let mut x_coordinate = 0.0_f32;
+            let mut x_coordinate = 0.0_f64;
             for test_serie in dir_test_series.iter() {
                 if legend.is_none() {
                     legend = Some(format!("{}", test_serie.data.len()));
@@ -69,7 +69,7 @@ fn apply_aggregator_test() -> Result<(), Box<dyn std::error::Error>> {
                 if test_serie.mean.is_none() {
                     unreachable!("The mean is not defined for the test serie {}, this doesn't mean to arrive....The vector mus be empty.", test_serie.name);
                 }
-                assert_relative_eq!(test_serie.mean.unwrap(), point.1 as f64, max_relative = common::ABSOLUTE_ERROR);
+                assert_relative_eq!(test_serie.mean.unwrap(), point.1, max_relative = common::ABSOLUTE_ERROR);
             }
         }
         
@@ -0,0 +1,51 @@
+use plot_helper::data::plot_data::{CategoryOrder, Layout, LegendOrder};
+use plot_helper::data::plottable::Plottable;
+use plot_helper::plotter::legend::LegendConfig;
+use plot_helper::plotter::plot::plot;
+use plot_helper::plotter::style::FigureStyle;
+use plot_helper::plotter::subplot::Subplot;
+use plot_helper::stats::stats_serie::MetricName;
+
+use common::bench_helper::{get_bench_plottable, BenchKey};
+
+mod common;
+
+#[macro_use]
+extern crate approx;
+
+#[test]
+fn categorical_aggregate_test() -> Result<(), Box<dyn std::error::Error>> {
+    let data = get_bench_plottable();
+    let legends = data.get_string_series(&BenchKey::Tool);
+    let series = data.aggregate(&BenchKey::Config, &BenchKey::Time, &None, &legends, &None, Some(MetricName::Mean))?;
+
+    // the categories in order of appearance, the x values are their indices
+    assert_eq!(series.get_x_categories().unwrap(), &vec!["b".to_string(), "a".to_string()]);
+    let fast = &series.get_data()["fast"];
+    assert_eq!(fast.iter().map(|(x, _)| *x).collect::<Vec<_>>(), vec![0.0, 1.0]);
+    assert_relative_eq!(fast[0].1, 13.0 / 3.0 + 1.1, epsilon = 1e-5);
+    assert_relative_eq!(fast[1].1, 13.0 / 3.0 + 0.1, epsilon = 1e-5);
+
+    let sorted = series.sort_x_categories(&CategoryOrder::Name(LegendOrder::Lexicographic));
+    assert_eq!(sorted.get_x_categories().unwrap(), &vec!["a".to_string(), "b".to_string()]);
+    assert_relative_eq!(sorted.get_data()["fast"][0].1, 13.0 / 3.0 + 0.1, epsilon = 1e-5);
+    Ok(())
+}
+
+#[test]
+fn categorical_plot_test() -> Result<(), Box<dyn std::error::Error>> {
+    let data = get_bench_plottable();
+    let legend = LegendConfig::new(vec![BenchKey::Tool]);
+
+    let subplots = vec![
+        Subplot::line(BenchKey::Config, BenchKey::Time, MetricName::Mean)
+            .with_category_order(CategoryOrder::Name(LegendOrder::Lexicographic)),
+        Subplot::scatter(BenchKey::Tool, BenchKey::Time)
+            .with_category_order(CategoryOrder::Metric { metric : MetricName::Median, descending : true }),
+        Subplot::line(BenchKey::Size, BenchKey::Time, MetricName::Mean),
+    ];
+    let save_path = std::env::temp_dir().join("plot_helper_categorical.png");
+    plot(&data, &legend, save_path.to_str().unwrap(), &Layout::new(3, 1), &subplots, &FigureStyle::default())?;
+    assert!(save_path.is_file());
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use plot_helper::data::downsampling::Downsampling;
+use plot_helper::data::filtering::Filter;
+use plot_helper::data::legend_values::LegendKeys;
+use plot_helper::data::plot_data::Layout;
+use plot_helper::data::plottable::chunked::ChunkedPlottable;
+use plot_helper::data::plottable::Plottable;
+use plot_helper::error::PlotHelperError;
+use plot_helper::plotter::legend::LegendConfig;
+use plot_helper::plotter::plot::plot;
+use plot_helper::plotter::style::FigureStyle;
+use plot_helper::plotter::subplot::Subplot;
+use plot_helper::stats::stats_serie::MetricName;
+
+use common::bench_helper::{assert_same_series, get_bench_plottable, get_bench_samples, write_bench_files, BenchKey, BenchSample};
+
+mod common;
+
+#[macro_use]
+extern crate approx;
+
+#[test]
+fn chunked_aggregate_test() -> Result<(), Box<dyn std::error::Error>> {
+    let paths = write_bench_files("plot_helper_chunked_aggregate")?;
+    // 2 files (18 samples) per chunk
+    let data : ChunkedPlottable<BenchSample, BenchKey> = ChunkedPlottable::from_multiple_sample_paths(&paths, 2);
+    let expected_data = get_bench_plottable();
+    let legend = LegendKeys::new(vec![BenchKey::Tool]);
+    let legends = expected_data.get_string_series(&BenchKey::Tool);
+
+    assert_eq!(data.get_number_of_filtered_samples(&None)?, get_bench_samples().len());
+    let fast_filter = Filter::new_str(BenchKey::Tool, |tool| tool == "fast")?;
+    assert_eq!(data.get_number_of_filtered_samples(&Some(vec![&fast_filter]))?, get_bench_samples().len() / 2);
+
+    // the aggregation is the same as in memory
+    for metric in [MetricName::Mean, MetricName::Median, MetricName::Percentile(90)] {
+        let series = data.aggregate_by_chunk(&BenchKey::Size, &BenchKey::Time, &None, &legend, Some(metric), &Downsampling::default())?;
+        let expected = expected_data.aggregate(&BenchKey::Size, &BenchKey::Time, &None, &legends, &None, Some(metric))?;
+        assert_same_series(&series, &expected, 1e-9);
+    }
+
+    // the categories are indexed in order of appearance
+    let series = data.aggregate_by_chunk(&BenchKey::Config, &BenchKey::Time, &Some(vec![&fast_filter]), &legend, Some(MetricName::Mean), &Downsampling::default())?;
+    let expected = expected_data.aggregate(&BenchKey::Config, &BenchKey::Time, &Some(vec![&fast_filter]), &legends, &None, None)?
+        .apply_aggregator(MetricName::Mean)?;
+    assert_same_series(&series, &expected, 1e-9);
+
+    // without aggregation, the points are downsampled
+    let expected = expected_data.aggregate(&BenchKey::Size, &BenchKey::Time, &None, &legends, &None, None)?;
+    let (range_x, range_y) = expected.get_range();
+    for downsampling in [Downsampling::default(), Downsampling::Grid(4), Downsampling::Lttb(4), Downsampling::MinMax(2), Downsampling::None] {
+        let series = data.aggregate_by_chunk(&BenchKey::Size, &BenchKey::Time, &None, &legend, None, &downsampling)?;
+        assert_same_series(&series, expected.clone().downsample_in(&range_x, &range_y, &downsampling), 1e-9);
+    }
+    Ok(())
+}
+
+#[test]
+fn chunked_categories_test() -> Result<(), Box<dyn std::error::Error>> {
+    // 5 configurations, more than the cells of a 2 x 2 grid
+    let path = std::env::temp_dir().join("plot_helper_chunked_categories.csv");
+    let mut writer = csv::Writer::from_path(&path)?;
+    writer.write_record(["tool", "configuration", "size", "time"])?;
+    for (index, config) in ["e", "d", "c", "b", "a"].iter().enumerate() {
+        for run in 0..3 {
+            writer.write_record(["fast", config, "1", &(index as f32 + run as f32 * 0.1).to_string()])?;
+        }
+    }
+    writer.flush()?;
+    let paths = vec![path.to_str().unwrap().to_string()];
+    let data : ChunkedPlottable<BenchSample, BenchKey> = ChunkedPlottable::from_multiple_sample_paths(&paths, 1);
+    let legend = LegendKeys::new(vec![BenchKey::Tool]);
+
+    // the points of different categories are not averaged together
+    let series = data.aggregate_by_chunk(&BenchKey::Config, &BenchKey::Time, &None, &legend, None, &Downsampling::Grid(2))?;
+    assert_eq!(series.get_x_categories(), Some(&vec!["e".to_string(), "d".to_string(), "c".to_string(), "b".to_string(), "a".to_string()]));
+    // the x values are the exact indices of the categories (a category can have several cells of y)
+    let mut xs : Vec<f64> = series.get_data()["fast"].iter().map(|(x, _)| *x).collect();
+    xs.dedup();
+    assert_eq!(xs, vec![0.0, 1.0, 2.0, 3.0, 4.0]);
+
+    let reduced = data.reduce(&BenchKey::Config, &BenchKey::Time, &None, &legend, None, &Downsampling::Grid(2))?;
+    let mut configs = reduced.get_string_series(&BenchKey::Config);
+    configs.dedup();
+    assert_eq!(configs, ["e", "d", "c", "b", "a"]);
+    Ok(())
+}
+
+#[test]
+fn chunked_stats_test() -> Result<(), Box<dyn std::error::Error>> {
+    let paths = write_bench_files("plot_helper_chunked_stats")?;
+    let data : ChunkedPlottable<BenchSample, BenchKey> = ChunkedPlottable::from_multiple_sample_paths(&paths, 1);
+    let keys = vec![BenchKey::Time, BenchKey::Size];
+
+    let stats = data.collect_streaming_stats(&keys, &None)?;
+    let expected = get_bench_plottable().get_numeric_series(&BenchKey::Time);
+    assert_eq!(stats[&BenchKey::Time].len(), expected.len());
+    assert_relative_eq!(stats[&BenchKey::Time].get_stats(MetricName::Mean).value, expected.iter().sum::<f64>() / expected.len() as f64, epsilon = 1e-9);
+
+    let sorted_stats = data.collect_streaming_stats_sorted_by_unique_values(&keys, &BenchKey::Tool, &None)?;
+    let expected = get_bench_plottable().collect_stats_sorted_by_unique_values(&keys, &BenchKey::Tool)?;
+    assert_eq!(sorted_stats.len(), expected.len());
+    for (tool, stats) in expected.iter() {
+        for (key, serie) in stats.iter() {
+            for metric in [MetricName::Mean, MetricName::Median, MetricName::Percentile(25)] {
+                assert_relative_eq!(sorted_stats[tool][key].get_stats(metric).value, serie.get_stats(metric).value, epsilon = 1e-9);
+            }
+        }
+    }
+    assert!(data.collect_streaming_stats(&[BenchKey::Tool], &None).is_err());
+    Ok(())
+}
+
+#[test]
+fn chunked_plot_test() -> Result<(), Box<dyn std::error::Error>> {
+    let paths = write_bench_files("plot_helper_chunked_plot")?;
+    let data : ChunkedPlottable<BenchSample, BenchKey> = ChunkedPlottable::from_multiple_sample_paths(&paths, 3);
+    let legend = LegendConfig::new(vec![BenchKey::Tool, BenchKey::Config]);
+
+    let reduced = data.reduce(&BenchKey::Size, &BenchKey::Time, &None, legend.get_legend_keys(), Some(MetricName::Mean), &Downsampling::default())?;
+    // 3 sizes per (tool, configuration)
+    assert_eq!(reduced.get_number_of_samples(), 3 * 4);
+    assert_eq!(reduced.get_string_series(&BenchKey::Config)[..4], ["b", "b", "b", "a"]);
+
+    let subplots = vec![Subplot::line(BenchKey::Size, BenchKey::Time, MetricName::Mean)];
+    let save_path = std::env::temp_dir().join("plot_helper_chunked_plot.png");
+    plot(&reduced, &legend, save_path.to_str().unwrap(), &Layout::new(1, 1), &subplots, &FigureStyle::default())?;
+    Ok(())
+}
+
+#[test]
+fn chunked_load_error_test() -> Result<(), Box<dyn std::error::Error>> {
+    let mut paths = write_bench_files("plot_helper_chunked_load_error")?;
+    // a truncated file (a time without value) in the last chunk
+    let truncated_path = std::env::temp_dir().join("plot_helper_chunked_truncated.csv");
+    std::fs::write(&truncated_path, "tool,configuration,size,time\nfast,a,1,\n")?;
+    paths.push(truncated_path.to_str().unwrap().to_string());
+    let data : ChunkedPlottable<BenchSample, BenchKey> = ChunkedPlottable::from_multiple_sample_paths(&paths, 2);
+    let legend = LegendKeys::new(vec![BenchKey::Tool]);
+
+    // the analyses return the error instead of ignoring the file
+    assert!(matches!(data.get_number_of_filtered_samples(&None), Err(PlotHelperError::Parse(_))));
+    assert!(data.collect_streaming_stats(&[BenchKey::Time], &None).is_err());
+    assert!(data.reduce(&BenchKey::Size, &BenchKey::Time, &None, &legend, Some(MetricName::Mean), &Downsampling::default()).is_err());
+
+    // a missing file too
+    paths.pop();
+    paths.push(std::env::temp_dir().join("plot_helper_chunked_missing.csv").to_str().unwrap().to_string());
+    let data : ChunkedPlottable<BenchSample, BenchKey> = ChunkedPlottable::from_multiple_sample_paths(&paths, 2);
+    assert!(matches!(data.get_number_of_filtered_samples(&None), Err(PlotHelperError::Io(_))));
+    Ok(())
+}
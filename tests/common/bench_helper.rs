@@ -0,0 +1,127 @@
+#![allow(dead_code)] // shared by several test binaries
+// ------------------------------ bench helper ------------------------------
+// small benchmark like dataset (tool x configuration x size -> time), to test the analyses
+
+use std::fmt::{Display, Formatter};
+
+use plot_helper::generate_plot_key;
+use plot_helper::data::plot_data::PlotSeries;
+use plot_helper::data::plottable::key::SerieKey;
+use plot_helper::data::plottable::sample::{MultipleSample, Sample};
+use plot_helper::data::plottable::{PlottableSamples, PlottableStruct};
+use plot_helper::error::PlotHelperError;
+use serde_derive::{Deserialize, Serialize};
+
+generate_plot_key!(
+    BenchKey[
+        Tool { "tool", String },
+        Config { "configuration", String },
+        Size { "size", Numeric },
+        Time { "time (s)", Numeric }
+    ]
+);
+
+/// a run of a tool
+#[derive(Debug, Clone)]
+pub struct BenchSample {
+    pub tool : String,
+    pub config : String,
+    pub size : f32,
+    pub time : f32,
+}
+
+impl Sample<BenchKey> for BenchSample {
+    fn get_numeric_value(&self, key : &BenchKey) -> f32 {
+        match key {
+            BenchKey::Size => self.size,
+            BenchKey::Time => self.time,
+            _ => panic!("Not a numeric serie"),
+        }
+    }
+
+    fn get_string_value(&self, key : &BenchKey) -> String {
+        match key {
+            BenchKey::Tool => self.tool.clone(),
+            BenchKey::Config => self.config.clone(),
+            _ => panic!("Not a string serie"),
+        }
+    }
+}
+
+impl MultipleSample<BenchKey> for BenchSample {
+    /// load the runs of a csv file (tool, configuration, size, time), see write_bench_files
+    fn new_from_file_path(file_path : &str) -> Result<Vec<Self>, PlotHelperError> {
+        let mut reader = csv::Reader::from_path(file_path)?;
+        let mut samples = Vec::new();
+        for record in reader.records() {
+            let record = record?;
+            samples.push(BenchSample {
+                tool : record[0].to_string(),
+                config : record[1].to_string(),
+                size : record[2].parse()?,
+                time : record[3].parse()?,
+            });
+        }
+        Ok(samples)
+    }
+}
+
+/// get the samples : for each tool ("fast" and "slow"), each configuration ("a" and "b"), and each size (1, 2, 10),
+/// 3 runs. "fast" time is the size, "slow" time is the double of the size (+ 1 for the configuration "b"),
+/// the runs add 0, 0.1 and 0.2
+pub fn get_bench_samples() -> Vec<BenchSample> {
+    let mut samples = Vec::new();
+    for tool in ["slow", "fast"] {
+        for config in ["b", "a"] {
+            for size in [10.0, 1.0, 2.0] {
+                for run in 0..3 {
+                    let base = if tool == "fast" { size } else { 2.0 * size };
+                    let config_cost = if config == "b" { 1.0 } else { 0.0 };
+                    samples.push(BenchSample {
+                        tool : tool.to_string(),
+                        config : config.to_string(),
+                        size,
+                        time : base + config_cost + run as f32 * 0.1,
+                    });
+                }
+            }
+        }
+    }
+    samples
+}
+
+pub fn get_bench_plottable() -> PlottableStruct<BenchSample, BenchKey> {
+    PlottableStruct::new(get_bench_samples())
+}
+
+/// write the samples in csv files of the temp dir, one per (tool, configuration), in the order of the samples,
+/// and return their paths
+pub fn write_bench_files(name : &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let samples = get_bench_samples();
+    let mut paths = Vec::new();
+    for (index, runs) in samples.chunks(9).enumerate() {
+        let path = std::env::temp_dir().join(format!("{}_{}.csv", name, index));
+        let mut writer = csv::Writer::from_path(&path)?;
+        writer.write_record(["tool", "configuration", "size", "time"])?;
+        for run in runs {
+            writer.write_record([run.tool.clone(), run.config.clone(), run.size.to_string(), run.time.to_string()])?;
+        }
+        writer.flush()?;
+        paths.push(path.to_str().unwrap().to_string());
+    }
+    Ok(paths)
+}
+
+/// check that the series have the same legends (in the same order), the same categories and the same points (up to epsilon)
+pub fn assert_same_series(series : &PlotSeries, expected : &PlotSeries, epsilon : f64) {
+    assert_eq!(series.get_legends(), expected.get_legends());
+    assert_eq!(series.get_x_categories(), expected.get_x_categories());
+    for (legend, points) in series.iter() {
+        let expected_points = &expected.get_data()[legend];
+        assert_eq!(points.len(), expected_points.len(), "the serie {} has not the expected number of points", legend);
+        for (point, expected_point) in points.iter().zip(expected_points.iter()) {
+            approx::assert_relative_eq!(point.0, expected_point.0, epsilon = epsilon);
+            approx::assert_relative_eq!(point.1, expected_point.1, epsilon = epsilon);
+        }
+    }
+}
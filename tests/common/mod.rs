@@ -1,5 +1,7 @@
 pub mod stats_helper;
 pub mod plottable_helper;
+pub mod bench_helper;
+pub mod nightly_helper;
 
 // ------------- tests params -------------
 pub const STATS_RESSOURCES_DIR_PATH : &'static str = "tests/ressources/stats_series/data/";
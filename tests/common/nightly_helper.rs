@@ -0,0 +1,64 @@
+#![allow(dead_code)] // shared by several test binaries
+// ------------------------------ nightly helper ------------------------------
+// nightly runs of two tools over 3 weeks (date x tool -> time), to test the datetime series
+
+use std::fmt::{Display, Formatter};
+
+use plot_helper::data::datetime::Timestamp;
+use plot_helper::data::plottable::key::SerieKey;
+use plot_helper::data::plottable::sample::Sample;
+use plot_helper::data::plottable::{PlottableSamples, PlottableStruct};
+use plot_helper::generate_plot_key;
+use serde_derive::{Deserialize, Serialize};
+
+generate_plot_key!(
+    NightlyKey[
+        Date { "date", Datetime },
+        Tool { "tool", String },
+        Time { "time (s)", Numeric }
+    ]
+);
+
+/// a nightly run of a tool
+pub struct NightlySample {
+    date : Timestamp,
+    tool : String,
+    time : f64,
+}
+
+impl Sample<NightlyKey> for NightlySample {
+    fn get_numeric_value(&self, key : &NightlyKey) -> f32 {
+        self.get_numeric_value_f64(key) as f32
+    }
+
+    fn get_numeric_value_f64(&self, key : &NightlyKey) -> f64 {
+        match key {
+            NightlyKey::Date => self.date.to_value(),
+            NightlyKey::Time => self.time,
+            _ => panic!("Not a numeric serie"),
+        }
+    }
+
+    fn get_string_value(&self, key : &NightlyKey) -> String {
+        match key {
+            NightlyKey::Tool => self.tool.clone(),
+            _ => panic!("Not a string serie"),
+        }
+    }
+}
+
+/// two runs per night (at 1h and 13h) during 3 weeks, the time of "slow" grows each day
+pub fn get_nightly_plottable() -> PlottableStruct<NightlySample, NightlyKey> {
+    let start = "2024-03-04T00:00:00Z".parse::<Timestamp>().unwrap().get_epoch_seconds();
+    let mut samples = Vec::new();
+    for day in 0..21 {
+        for (hour, tool) in [(1.0, "fast"), (13.0, "slow")] {
+            samples.push(NightlySample {
+                date : Timestamp::from_epoch_seconds(start + day as f64 * 86400.0 + hour * 3600.0).unwrap(),
+                tool : tool.to_string(),
+                time : if tool == "fast" { 1.0 } else { 1.0 + day as f64 },
+            });
+        }
+    }
+    PlottableStruct::new(samples)
+}
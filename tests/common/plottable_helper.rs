@@ -82,13 +82,17 @@ macro_rules! generate_plot_key {
             paste::item! {
                 pub struct [< Sample$key_name >] {
                     $(
-                        $struct_name : f32,
+                        $struct_name : f64,
                     )*
                 }
 
                 // all the keys are plottable, and numeric
                 impl Sample<$key_name> for [< Sample$key_name >] {
                     fn get_numeric_value(&self, key : &$key_name) -> f32 {
+                        self.get_numeric_value_f64(key) as f32
+                    }
+
+                    fn get_numeric_value_f64(&self, key : &$key_name) -> f64 {
                         match key {
                             $($key_name::$variant => {
                                 self.$struct_name.clone()
@@ -105,7 +109,7 @@ macro_rules! generate_plot_key {
                 #[derive(Clone, Serialize, Deserialize, Debug)]
                 pub struct [< AllStats$key_name >] {
                     $(
-                        $struct_name : Vec<f32>,
+                        $struct_name : Vec<f64>,
                     )*
                     length : usize,
                 }
@@ -12,11 +12,11 @@ use crate::common::ABSOLUTE_ERROR;
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TestSerie {
     pub name: String,
-    pub data: Vec<f32>,
+    pub data: Vec<f64>,
     pub mean: Option<f64>,
     pub median: Option<f64>,
-    pub q_1: Option<f32>,
-    pub q_3: Option<f32>,
+    pub q_1: Option<f64>,
+    pub q_3: Option<f64>,
 }
 
 impl TestSerie {
@@ -72,14 +72,14 @@ pub fn test_stats(instance : &TestSerie, verbose : bool) {
     if instance.q_1.is_none() {
         assert!(q1.is_nan(), "The q1 is not correct for the serie {} (Must be a NAN value).", instance_name);
     }else{
-        let result = relative_eq!(q1, instance.q_1.unwrap(), epsilon = ABSOLUTE_ERROR as f32);
+        let result = relative_eq!(q1, instance.q_1.unwrap(), epsilon = ABSOLUTE_ERROR);
         assert!(result, "The q1 is not correct for the serie {} with an obtained value of {} and an expected value of {}", instance_name, q1, instance.q_1.unwrap());
     }
 
     if instance.q_3.is_none() {
         assert!(q3.is_nan(), "The q3 is not correct for the serie {} (Must be a NAN value).", instance_name);
     }else{
-        let result = relative_eq!(q3, instance.q_3.unwrap(), epsilon = ABSOLUTE_ERROR as f32);
+        let result = relative_eq!(q3, instance.q_3.unwrap(), epsilon = ABSOLUTE_ERROR);
         assert!(result, "The q3 is not correct for the serie {} with an obtained value of {} and an expected value of {}", instance_name, q3, instance.q_3.unwrap());
     }
 }
@@ -0,0 +1,50 @@
+use plot_helper::data::filtering::Filter;
+use plot_helper::data::plottable::Plottable;
+use plot_helper::static_html::presentation_data::Array;
+use plot_helper::stats::correlation::CorrelationMethod;
+
+use common::bench_helper::{get_bench_plottable, BenchKey};
+
+mod common;
+
+#[macro_use]
+extern crate approx;
+
+#[test]
+fn correlation_matrix_test() -> Result<(), Box<dyn std::error::Error>> {
+    let data = get_bench_plottable();
+    let matrix = data.correlation_matrix(&[BenchKey::Size, BenchKey::Time], &None, CorrelationMethod::Pearson)?;
+
+    assert_eq!(matrix.get_name(), "pearson");
+    assert_eq!(matrix.get_row_labels(), &vec!["size".to_string(), "time (s)".to_string()]);
+    assert_eq!(matrix.get_column_labels(), matrix.get_row_labels());
+    assert_eq!(matrix.get_range(), -1.0..1.0);
+    assert_relative_eq!(matrix.get("size", "size").unwrap(), 1.0, epsilon = 1e-9);
+    assert_eq!(matrix.get("size", "time (s)"), matrix.get("time (s)", "size"));
+    assert_relative_eq!(matrix.get("size", "time (s)").unwrap(), 0.8955893, epsilon = 1e-6);
+
+    let array : Array = matrix.into();
+    assert_eq!(array.get_header(), &vec!["".to_string(), "size".to_string(), "time (s)".to_string()]);
+    assert_eq!(array.get_data()[0][1], "1.0000");
+
+    Ok(())
+}
+
+#[test]
+fn correlation_matrix_filtered_test() -> Result<(), Box<dyn std::error::Error>> {
+    let data = get_bench_plottable();
+    // "fast" with the configuration "a" : the time is the size (+ the run), so the ranks are almost the same
+    let tool_filter = Filter::new_str(BenchKey::Tool, |tool| tool == "fast")?;
+    let config_filter = Filter::new_str(BenchKey::Config, |config| config == "a")?;
+    let matrix = data.correlation_matrix(
+        &[BenchKey::Size, BenchKey::Time],
+        &Some(vec![&tool_filter, &config_filter]),
+        CorrelationMethod::Spearman,
+    )?;
+    assert_relative_eq!(matrix.get("size", "time (s)").unwrap(), 0.9486833, epsilon = 1e-6);
+
+    // not numeric key
+    assert!(data.correlation_matrix(&[BenchKey::Size, BenchKey::Tool], &None, CorrelationMethod::Pearson).is_err());
+
+    Ok(())
+}
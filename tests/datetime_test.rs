@@ -0,0 +1,60 @@
+use plot_helper::data::datetime::{TimeBucket, Timestamp};
+use plot_helper::data::plot_data::Layout;
+use plot_helper::data::plottable::key::SerieKey;
+use plot_helper::data::plottable::Plottable;
+use plot_helper::plotter::legend::LegendConfig;
+use plot_helper::plotter::line_plot::line_plot;
+use plot_helper::plotter::plot::plot;
+use plot_helper::plotter::style::FigureStyle;
+use plot_helper::plotter::subplot::Subplot;
+use plot_helper::stats::stats_serie::MetricName;
+
+use common::nightly_helper::{get_nightly_plottable, NightlyKey};
+
+mod common;
+
+#[macro_use]
+extern crate approx;
+
+#[test]
+fn datetime_series_test() -> Result<(), Box<dyn std::error::Error>> {
+    let data = get_nightly_plottable();
+    assert!(NightlyKey::Date.is_numeric() && NightlyKey::Date.is_datetime());
+    assert_eq!(data.get_series_as_string(&NightlyKey::Date)[0], "2024-03-04T01:00:00Z");
+
+    // the mean time of each tool per week
+    let per_week = data.aggregate(&NightlyKey::Date, &NightlyKey::Time, &None, &data.get_string_series(&NightlyKey::Tool), &None, None)?
+        .apply_time_bucket_aggregator(MetricName::Mean, TimeBucket::Week)?;
+    let monday = "2024-03-04T00:00:00Z".parse::<Timestamp>()?.to_value();
+    assert_eq!(per_week.get_data()["slow"][0], (monday, 4.0));
+    assert_eq!(per_week.get_data()["fast"].len(), 3);
+    Ok(())
+}
+
+#[test]
+fn datetime_plot_test() -> Result<(), Box<dyn std::error::Error>> {
+    let data = get_nightly_plottable();
+    let legend = LegendConfig::new(vec![NightlyKey::Tool]);
+
+    let save_path = std::env::temp_dir().join("plot_helper_datetime_line.png");
+    line_plot(
+        &data,
+        &legend,
+        save_path.to_str().unwrap(),
+        &Layout::new(1, 1),
+        &FigureStyle::default(),
+        vec![(NightlyKey::Date, NightlyKey::Time, None)],
+        None,
+        MetricName::Mean,
+    )?;
+    assert!(save_path.is_file());
+
+    let subplots = vec![
+        Subplot::scatter(NightlyKey::Date, NightlyKey::Time),
+        Subplot::line(NightlyKey::Date, NightlyKey::Time, MetricName::Median).with_time_bucket(TimeBucket::Week),
+    ];
+    let save_path = std::env::temp_dir().join("plot_helper_datetime_buckets.png");
+    plot(&data, &legend, save_path.to_str().unwrap(), &Layout::new(2, 1), &subplots, &FigureStyle::default())?;
+    assert!(save_path.is_file());
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use plot_helper::data::axis::{AxesConfig, AxisConfig, AxisScale};
+use plot_helper::data::filtering::Filter;
+use plot_helper::data::plot_data::{Layout, PlotSeries};
+use plot_helper::error::PlotHelperError;
+use plot_helper::plotter::legend::LegendConfig;
+use plot_helper::plotter::line_plot::line_plot;
+use plot_helper::plotter::plot::plot;
+use plot_helper::plotter::scatter_plot::scatter_plot;
+use plot_helper::plotter::style::FigureStyle;
+use plot_helper::plotter::subplot::Subplot;
+use plot_helper::stats::stats_serie::{MetricName, StatsSerie};
+use plot_helper::stats::{calculate_q1_q3, get_outliers};
+
+use common::bench_helper::{get_bench_plottable, BenchKey};
+
+mod common;
+
+#[macro_use]
+extern crate approx;
+
+#[test]
+fn degenerate_stats_test() {
+    // empty serie : every metric is NaN, there is no outlier
+    let empty = StatsSerie::new(&vec![]);
+    for metric in [MetricName::Mean, MetricName::Median, MetricName::Percentile(90)] {
+        assert!(empty.get_stats(metric).value.is_nan());
+    }
+    let (q1, q3) = calculate_q1_q3(&vec![]);
+    assert!(q1.is_nan() && q3.is_nan());
+    assert!(get_outliers(&vec![]).is_empty());
+
+    // single value and constant serie : every metric is the value, there is no outlier
+    for serie in [vec![3.0], vec![3.0; 5]] {
+        let stats = StatsSerie::new(&serie);
+        for metric in [MetricName::Mean, MetricName::Median, MetricName::Percentile(90)] {
+            assert_eq!(stats.get_stats(metric).value, 3.0);
+        }
+        assert_eq!(calculate_q1_q3(&serie), (3.0, 3.0));
+        assert!(get_outliers(&serie).iter().all(|outlier| !outlier));
+    }
+}
+
+#[test]
+fn degenerate_plot_series_test() {
+    // empty series
+    let series = PlotSeries::new();
+    assert!(series.is_empty());
+    assert_eq!(series.get_range(), (0.0..1.0, 0.0..1.0));
+    assert!(series.apply_aggregator(MetricName::Mean).unwrap().is_empty());
+
+    // single point : the ranges are centered on the point
+    let mut series = PlotSeries::new();
+    series.add("a".to_string(), (2.0, 5.0));
+    assert!(!series.is_empty());
+    assert_eq!(series.get_range(), (1.5..2.5, 4.5..5.5));
+
+    // constant serie, the NaN are ignored
+    let mut series = PlotSeries::new();
+    for x in [0.0, 1.0, 2.0] {
+        series.add("a".to_string(), (x, 1.0));
+    }
+    series.add("a".to_string(), (f64::NAN, f64::NAN));
+    let (range_x, range_y) = series.get_range();
+    assert_eq!((range_x.clone(), range_y.clone()), (0.0..2.0, 0.5..1.5));
+    series.compress_in(&range_x, &range_y);
+    assert_eq!(series.get_data()["a"], vec![(0.0, 1.0), (1.0, 1.0), (2.0, 1.0)]);
+}
+
+#[test]
+fn plot_without_data_test() -> Result<(), Box<dyn std::error::Error>> {
+    let data = get_bench_plottable();
+    // nothing is kept : each subplot is drawn with a "no data" message
+    let filter = Filter::new_str(BenchKey::Tool, |_| false)?;
+    let subplots = vec![
+        Subplot::scatter(BenchKey::Size, BenchKey::Time).with_filters(vec![&filter]),
+        Subplot::line(BenchKey::Size, BenchKey::Time, MetricName::Mean).with_filters(vec![&filter]),
+        Subplot::histogram(BenchKey::Time, 10).with_filters(vec![&filter]),
+        Subplot::density(BenchKey::Size, BenchKey::Time, 10).with_filters(vec![&filter]),
+    ];
+    let save_path = std::env::temp_dir().join("plot_helper_no_data.png");
+    plot(
+        &data,
+        &LegendConfig::new(vec![BenchKey::Tool]),
+        save_path.to_str().unwrap(),
+        &Layout::new(2, 2),
+        &subplots,
+        &FigureStyle::default(),
+    )?;
+    assert!(save_path.is_file());
+    Ok(())
+}
+
+#[test]
+fn plot_small_density_test() -> Result<(), Box<dyn std::error::Error>> {
+    let data = get_bench_plottable();
+    // a subplot narrower than the color bar of the density
+    let style = FigureStyle {
+        subplot_size : (60, 60),
+        ..FigureStyle::default()
+    };
+    let subplots = vec![Subplot::density(BenchKey::Size, BenchKey::Time, 10)];
+    let save_path = std::env::temp_dir().join("plot_helper_small_density.png");
+    plot(&data, &LegendConfig::new(vec![]), save_path.to_str().unwrap(), &Layout::new(1, 1), &subplots, &style)?;
+    assert!(save_path.is_file());
+    Ok(())
+}
+
+#[test]
+fn plot_too_many_styles_test() -> Result<(), Box<dyn std::error::Error>> {
+    let data = get_bench_plottable();
+    // more times than line dashes
+    let legend = LegendConfig::new(vec![BenchKey::Tool, BenchKey::Time]).with_second_key_as_style(true);
+    let save_path = std::env::temp_dir().join("plot_helper_too_many_styles.png");
+    let subplots = vec![Subplot::line(BenchKey::Size, BenchKey::Time, MetricName::Mean)];
+    let result = plot(&data, &legend, save_path.to_str().unwrap(), &Layout::new(1, 1), &subplots, &FigureStyle::default());
+    assert!(matches!(result, Err(PlotHelperError::InvalidParameter(_))));
+
+    // a histogram has no style
+    let subplots = vec![Subplot::histogram(BenchKey::Time, 10)];
+    plot(&data, &legend, save_path.to_str().unwrap(), &Layout::new(1, 1), &subplots, &FigureStyle::default())?;
+    Ok(())
+}
+
+#[test]
+fn legacy_plot_axes_test() -> Result<(), Box<dyn std::error::Error>> {
+    let data = get_bench_plottable();
+    let legend = LegendConfig::new(vec![BenchKey::Tool]);
+    let save_path = std::env::temp_dir().join("plot_helper_legacy_axes.png");
+    let series = || vec![(BenchKey::Size, BenchKey::Time, None)];
+    let log_axes = AxesConfig::new(AxisConfig::new(AxisScale::Log10), AxisConfig::new(AxisScale::Log2));
+
+    // the axes of the layout are used by the subplots of the legacy functions
+    let layout = Layout::new(1, 1).with_axes(vec![log_axes.clone()]);
+    scatter_plot(&data, &legend, save_path.to_str().unwrap(), &layout, &FigureStyle::default(), series(), None)?;
+    line_plot(&data, &legend, save_path.to_str().unwrap(), &layout, &FigureStyle::default(), series(), None, MetricName::Mean)?;
+
+    // an axes configuration per subplot
+    let layout = Layout::new(2, 1).with_axes(vec![log_axes]);
+    let result = scatter_plot(&data, &legend, save_path.to_str().unwrap(), &layout, &FigureStyle::default(), series(), None);
+    assert!(matches!(result, Err(PlotHelperError::InvalidParameter(_))));
+    Ok(())
+}
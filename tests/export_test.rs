@@ -0,0 +1,80 @@
+use plot_helper::data::export::{sorted_stats_table, DataFormat, ExportValue};
+use plot_helper::data::plot_data::Layout;
+use plot_helper::data::plottable::Plottable;
+use plot_helper::plotter::legend::LegendConfig;
+use plot_helper::plotter::plot::plot;
+use plot_helper::plotter::style::FigureStyle;
+use plot_helper::plotter::subplot::Subplot;
+use plot_helper::static_html::presentation_data::Array;
+use plot_helper::stats::stats_serie::MetricName;
+
+use common::bench_helper::{get_bench_plottable, BenchKey};
+
+mod common;
+
+#[macro_use]
+extern crate approx;
+
+#[test]
+fn plot_series_export_test() -> Result<(), Box<dyn std::error::Error>> {
+    let data = get_bench_plottable();
+    let legends = data.get_string_series(&BenchKey::Tool);
+    let series = data.aggregate(&BenchKey::Size, &BenchKey::Time, &None, &legends, &None, Some(MetricName::Median))?;
+
+    let dir = std::env::temp_dir();
+    series.save_to_csv(dir.to_str().unwrap(), "plot_helper_export_series")?;
+    let array = Array::from_csv(dir.join("plot_helper_export_series.csv").to_str().unwrap())?;
+    assert_eq!(array.get_header(), &vec!["legend", "x", "y"]);
+    assert_eq!(array.get_data().len(), 6);
+    assert_eq!(array.get_data()[0][0], "slow");
+    let y : f64 = array.get_data()[0][2].parse()?;
+    assert_relative_eq!(y, 2.6, epsilon = 1e-5);
+
+    // the categories are exported by name
+    let series = data.aggregate(&BenchKey::Config, &BenchKey::Time, &None, &legends, &None, Some(MetricName::Mean))?;
+    let table = series.to_export_table();
+    assert_eq!(table.get_rows()[0][1], ExportValue::Text("b".to_string()));
+    Ok(())
+}
+
+#[test]
+fn sorted_stats_export_test() -> Result<(), Box<dyn std::error::Error>> {
+    let data = get_bench_plottable();
+    let stats = data.collect_stats_sorted_by_unique_values(&vec![BenchKey::Time, BenchKey::Size], &BenchKey::Tool)?;
+    let table = sorted_stats_table(&stats, "tool", &[MetricName::Mean, MetricName::Median]);
+    assert_eq!(table.get_headers(), &vec!["tool", "serie", "count", "mean", "median"]);
+    // sorted by tool, then by serie
+    let rows = table.get_rows();
+    assert_eq!(rows.len(), 4);
+    assert_eq!(rows[0][..3], [ExportValue::from("fast"), ExportValue::from("size"), ExportValue::from(18.0)]);
+    assert_eq!(rows[1][1], ExportValue::from("time (s)"));
+    let ExportValue::Number(mean) = rows[1][3] else { panic!("the mean is a number") };
+    assert_relative_eq!(mean, 13.0 / 3.0 + 0.6, epsilon = 1e-5);
+
+    table.save_to_json(std::env::temp_dir().to_str().unwrap(), "plot_helper_export_stats")?;
+    Ok(())
+}
+
+#[test]
+fn plot_data_export_test() -> Result<(), Box<dyn std::error::Error>> {
+    let data = get_bench_plottable();
+    let legend = LegendConfig::new(vec![BenchKey::Tool]);
+    let subplots = vec![
+        Subplot::line(BenchKey::Size, BenchKey::Time, MetricName::Mean),
+        Subplot::histogram(BenchKey::Time, 4),
+    ];
+
+    let save_path = std::env::temp_dir().join("plot_helper_export_plot.png");
+    plot(&data, &legend, save_path.to_str().unwrap(), &Layout::new(2, 1).with_data_export(DataFormat::Csv), &subplots, &FigureStyle::default())?;
+    let array = Array::from_csv(save_path.with_extension("csv").to_str().unwrap())?;
+    assert_eq!(array.get_header(), &vec!["subplot", "title", "legend", "x", "y"]);
+    // 3 sizes per tool for the line, 4 bins per tool for the histogram
+    assert_eq!(array.get_data().len(), 2 * 3 + 2 * 4);
+    assert_eq!(array.get_data()[0][..4], ["0", "time (s) per size", "slow", "1"]);
+    assert_eq!(array.get_data()[6][..3], ["1", "distribution of time (s)", "slow"]);
+
+    plot(&data, &legend, save_path.to_str().unwrap(), &Layout::new(2, 1).with_data_export(DataFormat::Json), &subplots, &FigureStyle::default())?;
+    let json : serde_json::Value = serde_json::from_str(&std::fs::read_to_string(save_path.with_extension("json"))?)?;
+    assert_eq!(json.as_array().unwrap().len(), 14);
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use plot_helper::data::filtering::Filter;
+use plot_helper::data::plottable::Plottable;
+use plot_helper::static_html::presentation_data::Array;
+use plot_helper::stats::stats_serie::MetricName;
+
+use common::bench_helper::{get_bench_plottable, BenchKey};
+
+mod common;
+
+#[macro_use]
+extern crate approx;
+
+#[test]
+fn group_by_test() -> Result<(), Box<dyn std::error::Error>> {
+    let data = get_bench_plottable();
+    let table = data.group_by(
+        &[BenchKey::Tool, BenchKey::Size],
+        &[(BenchKey::Time, MetricName::Mean), (BenchKey::Time, MetricName::Median)],
+        &None,
+    )?;
+
+    assert_eq!(table.get_headers(), vec!["tool", "size", "time (s) (mean)", "time (s) (median)"]);
+    // sorted by tool, then numerically by size
+    let groups = table.get_rows().iter().map(|r| r.groups.join("-")).collect::<Vec<_>>();
+    assert_eq!(groups, vec!["fast-1", "fast-2", "fast-10", "slow-1", "slow-2", "slow-10"]);
+
+    // fast, size 1 : configuration a (1, 1.1, 1.2) and b (2, 2.1, 2.2)
+    let first = &table.get_rows()[0];
+    assert_relative_eq!(first.values[0], 1.6, epsilon = 0.0001);
+    assert_relative_eq!(first.values[1], 1.6, epsilon = 0.0001);
+
+    let array : Array = table.into();
+    assert_eq!(array.get_data().len(), 6);
+    assert_eq!(array.get_data()[0], vec!["fast", "1", "1.60", "1.60"]);
+
+    Ok(())
+}
+
+#[test]
+fn group_by_filtered_test() -> Result<(), Box<dyn std::error::Error>> {
+    let data = get_bench_plottable();
+    let filter = Filter::new_str(BenchKey::Config, |config| config == "a")?;
+    // the filter must match the type of the key
+    assert!(Filter::new_number(BenchKey::Config, |_| true).is_err());
+    assert!(Filter::new_str(BenchKey::Time, |_| true).is_err());
+    assert!(filter.get_filter_str()?("a"));
+    assert!(filter.get_filter_number().is_err());
+    let mut table = data.group_by(
+        &[BenchKey::Tool],
+        &[(BenchKey::Time, MetricName::Median)],
+        &Some(vec![&filter]),
+    )?;
+    table.sort_by_value(0, true)?;
+
+    let groups = table.get_rows().iter().map(|r| r.groups.join("-")).collect::<Vec<_>>();
+    assert_eq!(groups, vec!["slow", "fast"]);
+    assert_relative_eq!(table.get_rows()[0].values[0], 4.1, epsilon = 0.0001);
+
+    // not numeric aggregation
+    assert!(data.group_by(&[BenchKey::Size], &[(BenchKey::Tool, MetricName::Mean)], &None).is_err());
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use std::fmt::{Display, Formatter};
+
+use plot_helper::data::plottable::key::SerieKey;
+use plot_helper::data::plottable::sample::Sample;
+use plot_helper::data::plottable::{Plottable, PlottableSamples, PlottableStruct};
+use plot_helper::generate_plot_key;
+use plot_helper::stats::stats_serie::{MetricName, StatsSerie};
+use serde_derive::{Deserialize, Serialize};
+
+use common::bench_helper::{get_bench_samples, BenchKey};
+
+mod common;
+
+#[macro_use]
+extern crate approx;
+
+generate_plot_key!(
+    TimingKey[
+        Run { "run", Numeric },
+        Time { "time (ns)", Numeric }
+    ]
+);
+
+/// a timing in nanoseconds, above the precision of a f32 (2^24)
+struct TimingSample {
+    run : f64,
+    time : f64,
+}
+
+impl Sample<TimingKey> for TimingSample {
+    fn get_numeric_value(&self, key : &TimingKey) -> f32 {
+        self.get_numeric_value_f64(key) as f32
+    }
+
+    fn get_numeric_value_f64(&self, key : &TimingKey) -> f64 {
+        match key {
+            TimingKey::Run => self.run,
+            TimingKey::Time => self.time,
+        }
+    }
+
+    fn get_string_value(&self, _key : &TimingKey) -> String {
+        panic!("Not a string serie");
+    }
+}
+
+#[test]
+fn f64_precision_test() -> Result<(), Box<dyn std::error::Error>> {
+    let base = 16_777_216.0; // 2^24
+    let data = PlottableStruct::new(vec![
+        TimingSample { run : 0.0, time : base + 1.0 },
+        TimingSample { run : 0.0, time : base + 3.0 },
+        TimingSample { run : 1.0, time : base + 5.0 },
+    ]);
+    assert_eq!(data.get_numeric_series(&TimingKey::Time), vec![base + 1.0, base + 3.0, base + 5.0]);
+    // the f32 value is still available, rounded
+    assert_eq!(data.get_samples()[0].get_numeric_value(&TimingKey::Time), 16_777_216.0_f32);
+
+    let series = data.aggregate(&TimingKey::Run, &TimingKey::Time, &None, &vec!["all".to_string(); 3], &None, Some(MetricName::Mean))?;
+    assert_eq!(series.get_data()["all"], vec![(0.0, base + 2.0), (1.0, base + 5.0)]);
+    assert_eq!(StatsSerie::new(&data.get_numeric_series(&TimingKey::Time)).get_stats(MetricName::Median).value, base + 3.0);
+    Ok(())
+}
+
+#[test]
+fn f32_sample_compatibility_test() {
+    // the bench samples only implement the f32 value
+    let samples = get_bench_samples();
+    assert_eq!(samples[0].get_numeric_value_f64(&BenchKey::Time), samples[0].get_numeric_value(&BenchKey::Time) as f64);
+    let data = PlottableStruct::new(samples);
+    assert_relative_eq!(data.get_numeric_series(&BenchKey::Time)[1], 21.1, epsilon = 1e-5);
+}
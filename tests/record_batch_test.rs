@@ -0,0 +1,145 @@
+#![cfg(feature = "arrow")]
+
+use std::fs::File;
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, Float64Array, Int32Array, RecordBatch, StringArray, TimestampMillisecondArray};
+use arrow::ipc::writer::FileWriter;
+use parquet::arrow::ArrowWriter;
+use plot_helper::data::plot_data::Layout;
+use plot_helper::data::plottable::record_batch::RecordBatchPlottable;
+use plot_helper::data::plottable::Plottable;
+use plot_helper::error::PlotHelperError;
+use plot_helper::plotter::legend::LegendConfig;
+use plot_helper::plotter::plot::plot;
+use plot_helper::plotter::style::FigureStyle;
+use plot_helper::plotter::subplot::Subplot;
+use plot_helper::stats::stats_serie::MetricName;
+
+use common::bench_helper::{assert_same_series, get_bench_plottable, get_bench_samples, BenchKey};
+
+mod common;
+
+#[macro_use]
+extern crate approx;
+
+const COLUMNS : [(BenchKey, &str); 4] = [
+    (BenchKey::Tool, "tool"),
+    (BenchKey::Config, "config"),
+    (BenchKey::Size, "size"),
+    (BenchKey::Time, "time"),
+];
+
+/// get the bench samples as a record batch (the size is an integer column), with an unused column
+fn get_bench_batch() -> RecordBatch {
+    let samples = get_bench_samples();
+    let columns : Vec<(&str, ArrayRef)> = vec![
+        ("tool", Arc::new(StringArray::from_iter_values(samples.iter().map(|sample| sample.tool.clone())))),
+        ("unused", Arc::new(Int32Array::from_iter_values(0..samples.len() as i32))),
+        ("config", Arc::new(StringArray::from_iter_values(samples.iter().map(|sample| sample.config.clone())))),
+        ("size", Arc::new(Int32Array::from_iter_values(samples.iter().map(|sample| sample.size as i32)))),
+        ("time", Arc::new(Float64Array::from_iter_values(samples.iter().map(|sample| sample.time as f64)))),
+    ];
+    RecordBatch::try_from_iter(columns).unwrap()
+}
+
+/// check that the record batches give the same series as the bench samples
+fn check_bench_series(data : &RecordBatchPlottable<BenchKey>) -> Result<(), PlotHelperError> {
+    let expected = get_bench_plottable();
+    assert_eq!(data.get_number_of_samples(), expected.get_number_of_samples());
+    assert_eq!(data.get_string_series(&BenchKey::Tool), expected.get_string_series(&BenchKey::Tool));
+    assert_eq!(data.get_numeric_series(&BenchKey::Size), expected.get_numeric_series(&BenchKey::Size));
+
+    let legends = data.get_string_series(&BenchKey::Tool);
+    let series = data.aggregate(&BenchKey::Config, &BenchKey::Time, &None, &legends, &None, Some(MetricName::Mean))?;
+    let expected_series = expected.aggregate(&BenchKey::Config, &BenchKey::Time, &None, &legends, &None, Some(MetricName::Mean))?;
+    assert_same_series(&series, &expected_series, 1e-5);
+    Ok(())
+}
+
+#[test]
+fn record_batch_plottable_test() -> Result<(), Box<dyn std::error::Error>> {
+    let batch = get_bench_batch();
+    // split in several batches
+    let batches = vec![batch.slice(0, 10), batch.slice(10, batch.num_rows() - 10)];
+    let data = RecordBatchPlottable::new(batch.schema(), batches, &COLUMNS)?;
+    check_bench_series(&data)?;
+    assert_eq!(data.get_arrays(&BenchKey::Time).unwrap().len(), 2);
+
+    // the type of the columns is checked
+    assert!(matches!(RecordBatchPlottable::new(batch.schema(), vec![batch.clone()], &[(BenchKey::Time, "tool")]), Err(PlotHelperError::KeyTypeMismatch { .. })));
+    assert!(RecordBatchPlottable::new(batch.schema(), vec![batch], &[(BenchKey::Time, "missing")]).is_err());
+    Ok(())
+}
+
+#[test]
+fn record_batch_nulls_and_dates_test() -> Result<(), Box<dyn std::error::Error>> {
+    let batch = RecordBatch::try_from_iter(vec![
+        ("tool", Arc::new(StringArray::from(vec![Some("a"), None])) as ArrayRef),
+        ("time", Arc::new(Float64Array::from(vec![Some(1.5), None])) as ArrayRef),
+        ("size", Arc::new(TimestampMillisecondArray::from(vec![1_710_720_000_123, 1_710_720_060_000])) as ArrayRef),
+    ])?;
+    let data = RecordBatchPlottable::new(batch.schema(), vec![batch], &[(BenchKey::Tool, "tool"), (BenchKey::Time, "time"), (BenchKey::Size, "size")])?;
+    assert_eq!(data.get_string_series(&BenchKey::Tool), vec!["a", ""]);
+    let times = data.get_numeric_series(&BenchKey::Time);
+    assert_eq!(times[0], 1.5);
+    assert!(times[1].is_nan());
+    // the timestamps are in seconds
+    assert_relative_eq!(data.get_numeric_series(&BenchKey::Size)[0], 1_710_720_000.123, epsilon = 1e-6);
+    Ok(())
+}
+
+#[test]
+fn record_batch_files_test() -> Result<(), Box<dyn std::error::Error>> {
+    let batch = get_bench_batch();
+    let dir = std::env::temp_dir();
+
+    let parquet_path = dir.join("plot_helper_bench.parquet");
+    let mut writer = ArrowWriter::try_new(File::create(&parquet_path)?, batch.schema(), None)?;
+    writer.write(&batch)?;
+    writer.close()?;
+    let data = RecordBatchPlottable::from_parquet(parquet_path.to_str().unwrap(), &COLUMNS)?;
+    check_bench_series(&data)?;
+    // only the columns of the keys are read
+    assert_eq!(data.get_batches()[0].num_columns(), 4);
+
+    let ipc_path = dir.join("plot_helper_bench.arrow");
+    let mut writer = FileWriter::try_new(File::create(&ipc_path)?, &batch.schema())?;
+    writer.write(&batch)?;
+    writer.finish()?;
+    let data = RecordBatchPlottable::from_arrow_ipc(ipc_path.to_str().unwrap(), &COLUMNS)?;
+    check_bench_series(&data)?;
+    assert_eq!(data.get_batches()[0].num_columns(), 4);
+    Ok(())
+}
+
+#[test]
+fn record_batch_empty_files_test() -> Result<(), Box<dyn std::error::Error>> {
+    // files with the schema, but without batch
+    let schema = get_bench_batch().schema();
+    let dir = std::env::temp_dir();
+
+    let parquet_path = dir.join("plot_helper_empty.parquet");
+    ArrowWriter::try_new(File::create(&parquet_path)?, schema.clone(), None)?.close()?;
+    let ipc_path = dir.join("plot_helper_empty.arrow");
+    FileWriter::try_new(File::create(&ipc_path)?, &schema)?.finish()?;
+
+    for data in [
+        RecordBatchPlottable::from_parquet(parquet_path.to_str().unwrap(), &COLUMNS)?,
+        RecordBatchPlottable::from_arrow_ipc(ipc_path.to_str().unwrap(), &COLUMNS)?,
+    ] {
+        assert_eq!(data.get_number_of_samples(), 0);
+        assert!(data.get_numeric_series(&BenchKey::Time).is_empty());
+        assert!(data.get_string_series(&BenchKey::Tool).is_empty());
+        assert_eq!(data.get_arrays(&BenchKey::Time).map(|arrays| arrays.len()), Some(0));
+
+        // the subplots are drawn with a "no data" message
+        let save_path = dir.join("plot_helper_empty_record_batch.png");
+        let subplots = vec![Subplot::scatter(BenchKey::Size, BenchKey::Time)];
+        plot(&data, &LegendConfig::new(vec![BenchKey::Tool]), save_path.to_str().unwrap(), &Layout::new(1, 1), &subplots, &FigureStyle::default())?;
+    }
+
+    // the columns are still checked
+    assert!(RecordBatchPlottable::<BenchKey>::new(schema, vec![], &[(BenchKey::Time, "missing")]).is_err());
+    Ok(())
+}
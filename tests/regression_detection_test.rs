@@ -0,0 +1,49 @@
+use plot_helper::data::plot_data::Layout;
+use plot_helper::plotter::legend::LegendConfig;
+use plot_helper::plotter::plot::plot;
+use plot_helper::plotter::style::FigureStyle;
+use plot_helper::plotter::subplot::Subplot;
+use plot_helper::static_html::presentation_data::Array;
+use plot_helper::stats::regression_detection::{legend_regressions, RegressionConfig, RegressionMethod, RegressionStatus};
+use plot_helper::stats::stats_serie::MetricName;
+
+use common::nightly_helper::{get_nightly_plottable, NightlyKey};
+
+mod common;
+
+#[macro_use]
+extern crate approx;
+
+#[test]
+fn regression_detection_test() -> Result<(), Box<dyn std::error::Error>> {
+    let data = get_nightly_plottable();
+    let legend = LegendConfig::new(vec![NightlyKey::Tool]);
+
+    // the last week against the two weeks before
+    let config = RegressionConfig::new(RegressionMethod::LatestWindow { window : 7, baseline : Some(14) });
+    let report = legend_regressions(&data, legend.get_legend_keys(), &NightlyKey::Date, &NightlyKey::Time, &None, &config)?;
+    let slow = report.get_result("slow").unwrap();
+    assert_eq!(slow.status, RegressionStatus::Regression);
+    assert_eq!((slow.baseline, slow.current), (7.5, 18.0));
+    assert_eq!(report.get_result("fast").unwrap().status, RegressionStatus::Stable);
+    assert_eq!(report.get_highlights().len(), 7);
+
+    let array = Array::from(&report);
+    assert_eq!(array.get_data().len(), 2);
+    assert!(array.get_data().iter().any(|row| row[0] == "slow" && row[5] == "2024-03-18"));
+
+    // a change point on a linear growth : somewhere in the middle
+    let config = RegressionConfig::new(RegressionMethod::ChangePoint { min_segment : 3 });
+    let report = legend_regressions(&data, legend.get_legend_keys(), &NightlyKey::Date, &NightlyKey::Time, &None, &config)?;
+    assert_eq!(report.get_result("slow").unwrap().status, RegressionStatus::Regression);
+    assert_eq!(report.get_result("fast").unwrap().status, RegressionStatus::Stable);
+
+    // the regressed points highlighted on the line plot
+    let subplots = vec![
+        Subplot::line(NightlyKey::Date, NightlyKey::Time, MetricName::Median).with_highlights(report.get_highlights()),
+    ];
+    let save_path = std::env::temp_dir().join("plot_helper_regressions.png");
+    plot(&data, &legend, save_path.to_str().unwrap(), &Layout::new(1, 1), &subplots, &FigureStyle::default())?;
+    assert!(save_path.is_file());
+    Ok(())
+}
@@ -0,0 +1,138 @@
+#![cfg(feature = "sqlite")]
+
+use plot_helper::data::datetime::Timestamp;
+use plot_helper::data::filtering::Filter;
+use plot_helper::data::plottable::sqlite::SqlitePlottable;
+use plot_helper::data::plottable::Plottable;
+use plot_helper::error::PlotHelperError;
+use plot_helper::stats::stats_serie::MetricName;
+use rusqlite::{params, Connection};
+
+use common::bench_helper::{assert_same_series, get_bench_plottable, get_bench_samples, BenchKey};
+use common::nightly_helper::{get_nightly_plottable, NightlyKey};
+
+mod common;
+
+#[macro_use]
+extern crate approx;
+
+const COLUMNS : [(BenchKey, &str); 4] = [
+    (BenchKey::Tool, "tool"),
+    (BenchKey::Config, "config"),
+    (BenchKey::Size, "size"),
+    (BenchKey::Time, "time"),
+];
+
+/// write the bench samples in a "runs" table of a database in the temp dir (the size is an integer column)
+fn get_bench_database(name : &str) -> Result<String, Box<dyn std::error::Error>> {
+    let path = std::env::temp_dir().join(format!("{}.sqlite", name));
+    let _ = std::fs::remove_file(&path);
+    let connection = Connection::open(&path)?;
+    connection.execute("CREATE TABLE runs (id INTEGER PRIMARY KEY, tool TEXT, config TEXT, size INTEGER, time REAL)", [])?;
+    for sample in get_bench_samples() {
+        connection.execute(
+            "INSERT INTO runs (tool, config, size, time) VALUES (?1, ?2, ?3, ?4)",
+            params![sample.tool, sample.config, sample.size as i64, sample.time as f64],
+        )?;
+    }
+    Ok(path.to_str().unwrap().to_string())
+}
+
+/// check that the data is the same as the bench samples filtered by the filters
+fn check_bench_series(data : &SqlitePlottable<BenchKey>, filters : &Option<Vec<&Filter<BenchKey>>>) -> Result<(), PlotHelperError> {
+    let expected = get_bench_plottable();
+    let expected_legends = expected.get_string_series(&BenchKey::Tool);
+    let expected_series = expected.aggregate(&BenchKey::Size, &BenchKey::Time, filters, &expected_legends, &None, Some(MetricName::Mean))?;
+
+    let legends = data.get_string_series(&BenchKey::Tool);
+    let series = data.aggregate(&BenchKey::Size, &BenchKey::Time, &None, &legends, &None, Some(MetricName::Mean))?;
+    assert_same_series(&series, &expected_series, 1e-5);
+    Ok(())
+}
+
+#[test]
+fn sqlite_table_test() -> Result<(), Box<dyn std::error::Error>> {
+    let path = get_bench_database("plot_helper_bench_table")?;
+    let data = SqlitePlottable::from_table(&path, "runs", &COLUMNS, &None)?;
+    assert_eq!(data.get_number_of_samples(), get_bench_samples().len());
+    assert_eq!(data.get_string_series(&BenchKey::Config), get_bench_plottable().get_string_series(&BenchKey::Config));
+    check_bench_series(&data, &None)?;
+
+    // the errors of the database are returned
+    assert!(SqlitePlottable::from_table(&path, "missing", &COLUMNS, &None).is_err());
+    assert!(matches!(SqlitePlottable::from_table(&path, "runs", &[(BenchKey::Time, "tool")], &None), Err(PlotHelperError::Parse(_))));
+    Ok(())
+}
+
+#[test]
+fn sqlite_filters_test() -> Result<(), Box<dyn std::error::Error>> {
+    let path = get_bench_database("plot_helper_bench_filters")?;
+    // pushed down
+    let size_filter = Filter::new_range(BenchKey::Size, 2.0, f64::INFINITY)?;
+    let tool_filter = Filter::new_one_of(BenchKey::Tool, vec!["fast".to_string()])?;
+    // evaluated on the rows
+    let config_filter = Filter::new_str(BenchKey::Config, |config| config == "a")?;
+    let filters = Some(vec![&size_filter, &tool_filter, &config_filter]);
+
+    let data = SqlitePlottable::from_table(&path, "runs", &COLUMNS, &filters)?;
+    // fast tool, configuration a, size 10 and 2
+    assert_eq!(data.get_number_of_samples(), 6);
+    check_bench_series(&data, &filters)?;
+
+    // the filters can be combined with a query
+    let data = SqlitePlottable::from_query(&path, "SELECT * FROM runs WHERE config = 'b'", &COLUMNS, &Some(vec![&tool_filter]))?;
+    assert_eq!(data.get_number_of_samples(), 9);
+    assert!(data.get_string_series(&BenchKey::Config).iter().all(|config| config == "b"));
+
+    // the key of a filter must have a column
+    assert!(SqlitePlottable::from_table(&path, "runs", &COLUMNS[..2], &filters).is_err());
+    Ok(())
+}
+
+/// write the nightly runs in a "nightly" table of a database in the temp dir
+/// (the date is a RFC 3339 text column, and the tool has an integer id column)
+fn get_nightly_database(name : &str) -> Result<String, Box<dyn std::error::Error>> {
+    let path = std::env::temp_dir().join(format!("{}.sqlite", name));
+    let _ = std::fs::remove_file(&path);
+    let connection = Connection::open(&path)?;
+    connection.execute("CREATE TABLE nightly (date TEXT, tool TEXT, tool_id INTEGER, time REAL)", [])?;
+    let data = get_nightly_plottable();
+    let dates = data.get_series_as_string(&NightlyKey::Date);
+    let tools = data.get_string_series(&NightlyKey::Tool);
+    let times = data.get_numeric_series(&NightlyKey::Time);
+    for ((date, tool), time) in dates.iter().zip(tools.iter()).zip(times.iter()) {
+        connection.execute(
+            "INSERT INTO nightly (date, tool, tool_id, time) VALUES (?1, ?2, ?3, ?4)",
+            params![date, tool, if tool == "fast" { 0 } else { 1 }, time],
+        )?;
+    }
+    Ok(path.to_str().unwrap().to_string())
+}
+
+#[test]
+fn sqlite_filters_column_types_test() -> Result<(), Box<dyn std::error::Error>> {
+    let path = get_nightly_database("plot_helper_nightly_filters")?;
+    let columns = [(NightlyKey::Date, "date"), (NightlyKey::Tool, "tool"), (NightlyKey::Time, "time")];
+    let expected = get_nightly_plottable();
+
+    // a range on the dates of a text column : the same rows as in memory
+    let second_week = Filter::new_range(
+        NightlyKey::Date,
+        "2024-03-11T00:00:00Z".parse::<Timestamp>()?.to_value(),
+        "2024-03-17T23:59:59Z".parse::<Timestamp>()?.to_value(),
+    )?;
+    let filters = Some(vec![&second_week]);
+    let data = SqlitePlottable::from_table(&path, "nightly", &columns, &filters)?;
+    assert_eq!(data.get_number_of_samples(), 14);
+    let series = data.aggregate(&NightlyKey::Date, &NightlyKey::Time, &None, &data.get_string_series(&NightlyKey::Tool), &None, None)?;
+    let expected_series = expected.aggregate(&NightlyKey::Date, &NightlyKey::Time, &filters, &expected.get_string_series(&NightlyKey::Tool), &None, None)?;
+    assert_same_series(&series, &expected_series, 1e-9);
+
+    // the values of a string key read from an integer column
+    let slow_filter = Filter::new_one_of(NightlyKey::Tool, vec!["1".to_string()])?;
+    let query = "SELECT date, tool_id AS tool, time FROM nightly";
+    let data = SqlitePlottable::from_query(&path, query, &columns, &Some(vec![&slow_filter]))?;
+    assert_eq!(data.get_number_of_samples(), 21);
+    assert!(data.get_string_series(&NightlyKey::Tool).iter().all(|tool| tool == "1"));
+    Ok(())
+}